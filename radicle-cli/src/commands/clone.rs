@@ -232,6 +232,7 @@ pub enum CloneError {
     NoSeeds(RepoId),
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn clone<G: Signer>(
     id: RepoId,
     directory: Option<PathBuf>,
@@ -260,11 +261,11 @@ pub fn clone<G: Signer>(
         );
     }
 
-    let results = sync::fetch(id, settings, timeout, node)?;
+    let summary = sync::fetch(id, settings, timeout, node)?;
     let Ok(repository) = storage.repository(id) else {
         // If we don't have the repository locally, even after attempting to fetch,
         // there's nothing we can do.
-        if results.is_empty() {
+        if summary.is_empty() {
             return Err(CloneError::NoSeeds(id));
         } else {
             return Err(CloneError::NotFound(id));
@@ -282,8 +283,8 @@ pub fn clone<G: Signer>(
         }
     }
 
-    if results.success().next().is_none() {
-        if results.failed().next().is_some() {
+    if !summary.is_success() {
+        if !summary.failed.is_empty() {
             term::warning("Fetching failed, local copy is potentially stale");
         } else {
             term::warning("No seeds found, local copy is potentially stale");
@@ -139,7 +139,10 @@ pub fn run(options: Options, _ctx: impl term::Context) -> anyhow::Result<()> {
     }?;
     diff.find_similar(Some(&mut find_opts))?;
 
-    term::Paint::force(options.color);
+    // Only override the global color mode when asked to, eg. not to undo `rad --color`.
+    if options.color {
+        term::Paint::force(true);
+    }
 
     let diff = surf::diff::Diff::try_from(diff)?;
     let mut hi = Highlighter::default();
@@ -697,6 +697,7 @@ where
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn open<R, G>(
     title: Option<String>,
     description: Option<String>,
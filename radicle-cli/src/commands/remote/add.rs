@@ -12,6 +12,7 @@ use crate::commands::rad_follow as follow;
 use crate::commands::rad_sync as sync;
 use crate::project::SetupRemote;
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     rid: RepoId,
     nid: &PublicKey,
@@ -1,24 +1,43 @@
-use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsString;
-use std::ops::ControlFlow;
+use std::fmt;
+use std::io::{self, BufRead as _, IsTerminal as _};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time;
 
 use anyhow::{anyhow, Context as _};
 
 use radicle::node;
-use radicle::node::AliasStore;
-use radicle::node::Seed;
-use radicle::node::{FetchResult, FetchResults, Handle as _, Node, SyncStatus};
+use radicle::node::{Handle as _, Node, Seed, SyncStatus};
 use radicle::prelude::{NodeId, Profile, RepoId};
-use radicle::storage::{ReadRepository, ReadStorage};
-use radicle_term::Element;
 
 use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
-use crate::terminal::format::Author;
-use crate::terminal::{Table, TableOptions};
+
+#[path = "sync/announce.rs"]
+mod announce;
+#[path = "sync/domain.rs"]
+pub mod domain;
+#[path = "sync/fetch.rs"]
+mod fetch;
+#[path = "sync/health.rs"]
+pub mod health;
+#[path = "sync/report.rs"]
+mod report;
+#[path = "sync/seeds.rs"]
+mod seeds;
+#[path = "sync/status.rs"]
+mod status;
+
+pub use announce::announce_inventory;
+pub use fetch::{fetch, FetchFailure, FetchSummary, SeedPipeline, FETCH_RETRY_DELAY};
+
+use announce::announce_refs;
+use fetch::{fetch_with_pipeline, print_protocol_versions, replica_target};
+use report::ReportSink;
+use seeds::Unreachable;
+use status::{sync_check, sync_status, sync_status_all, sync_watch};
 
 pub const HELP: Help = Help {
     name: "sync",
@@ -30,16 +49,34 @@ Usage
     rad sync [--fetch | --announce] [<rid>] [<option>...]
     rad sync --inventory [<option>...]
     rad sync status [<rid>] [<option>...]
+    rad sync status [<rid>] --check [--replicas <count>]
+    rad sync status [<rid>] [--json] [<option>...]
+    rad sync status --all [--json] [<option>...]
+    rad sync status [<rid>] --watch [--interval <secs>] [<option>...]
 
     By default, the current repository is synchronized both ways.
-    If an <rid> is specified, that repository is synced instead.
+    If an <rid> is specified, that repository is synced instead. When
+    neither is available and the standard input isn't a terminal, the
+    <rid> is read from the first line of the standard input, eg.
+    `echo <rid> | rad sync --fetch`.
 
     The process begins by fetching changes from connected seeds,
     followed by announcing local refs to peers, thereby prompting
     them to fetch from us.
 
     When `--fetch` is specified, any number of seeds may be given
-    using the `--seed` option, eg. `--seed <nid>@<addr>:<port>`.
+    using the `--seed` option, eg. `--seed <nid>@<addr>:<port>`. Seeds given
+    with an address are dialed if they aren't connected, even if they aren't
+    in the routing table.
+
+    Seeds can also be given as a domain, eg. `--seed example.com`, in which
+    case the seed published under `_radicle._tcp.example.com` is used: its
+    Node ID is read from a `nid=<nid>` TXT record, and its addresses from the
+    SRV records. The seed is dialed if it isn't connected.
+
+    When announcing, seeds given with an address are dialed first if
+    they aren't connected, and refs are announced to them even if they
+    aren't known to seed the repository.
 
     When `--replicas` is specified, the given replication factor will try
     to be matched. For example, `--replicas 5` will sync with 5 seeds.
@@ -47,8 +84,112 @@ Usage
     When `--fetch` or `--announce` are specified on their own, this command
     will only fetch or announce.
 
+    The `--timeout` bounds the fetching phase and the announcing phase, each
+    as a whole: once it is exhausted, no further seed is connected to,
+    fetched from or announced to in that phase.
+
+    When `--deadline` is specified, the whole command, including connecting,
+    fetching and announcing to every seed, is stopped after the given number
+    of seconds. Operations are not started once the deadline has passed, and
+    the results obtained so far are reported. Unlike `--timeout`, which
+    bounds each phase separately, this gives an upper bound on the total time.
+
+    When `--seed-timeout` is specified, each connection, fetch and announcement
+    is given up after the given number of seconds. This allows slow seeds to
+    be skipped without shortening the phase they're part of, which is still
+    bounded by `--timeout`. Otherwise, each of them may take the time left in
+    its phase.
+
+    When `--retries` is specified with `--fetch`, fetches that fail for a
+    reason that looks transient, eg. a timeout or disconnection, are retried
+    up to the given number of times. The delay between attempts starts at
+    half a second and doubles with each attempt, and no attempt is made once
+    the fetching phase's `--timeout`, or the `--deadline`, is exhausted.
+
+    Seeds that failed to connect or fetch in a previous run are remembered
+    for an hour, or for `sync.seedHealthExpiry` seconds if configured, and
+    are tried after the other seeds. When `--no-seed-cache` is specified,
+    past failures are neither taken into account nor recorded.
+
+    When `--exclude` is specified, the given seeds are never fetched from or
+    announced to, even if they are preferred seeds.
+
+    When `--connected-only` is specified, refs are announced to the connected
+    seeds that are out of sync, without trying to match a replication factor.
+
     If `--inventory` is specified, the node's inventory is announced to
-    the network. This mode does not take an `<rid>`.
+    the network. This mode does not take an `<rid>`. When seeds are given
+    with `--seed <nid>`, the inventory is only announced to those seeds,
+    and seeds that aren't connected are skipped.
+
+    The command exits with status 1 if fetching was requested and no seed
+    could be fetched from, or if refs were announced and no seed synced with
+    them in time. Having no connected seeds to announce to is not an error.
+    Falling short of the `--replicas` target is not an error either, unless
+    `--require-replicas` is specified, in which case the command exits with
+    status 2. The target is then lowered to the number of seeds known for
+    the repository, not counting excluded seeds.
+
+    When running in a terminal, if every seed times out while announcing
+    refs, you are asked whether to retry with twice the timeout.
+
+    When `--quiet` is specified, no progress is displayed, and success and
+    informational messages are omitted. Errors and warnings are still
+    printed, on stderr. This is useful in scripts.
+
+    When `--report` is specified, the outcome of fetching from and announcing
+    to each seed is written to the given file as json once the command
+    completes, in addition to the usual output. Each outcome includes how
+    long it took and, for failures, whether they look `transient`, eg. a
+    disconnection, `permanent`, eg. the seed not having the repository, or
+    were a `timeout`.
+
+    When `RUST_LOG` is set to `info`, `debug` or `trace`, a record of `key=value`
+    fields is emitted for every connection, fetch and announcement, with
+    the seeds involved, their outcome and duration.
+
+    When `status --all` is specified, a summary of the sync status of
+    every seeded repository is displayed, one row per repository. With
+    `--verbose`, the full seed table of each repository is displayed instead.
+
+    When `status --json` is specified, the sync status table is printed as
+    a json array instead, with one object per seed. Our own node is marked
+    with `"local": true`. With `--all`, the array holds one object per
+    seeded repository instead.
+
+    When `status --check` is specified, the sync status table is not
+    displayed. Instead, the number of seeds in sync with the repository is
+    compared to the replication factor given with `--replicas` (default: 3),
+    and the command fails if it isn't met. This is useful for monitoring.
+
+    When `status --filter` is specified, only the seeds in the given sync
+    state are displayed, along with our own node. Filtering on `unknown`
+    displays seeds whose sync status is unknown, even without `--verbose`.
+
+    When `status --limit` is specified, only the given number of seeds
+    are displayed, after sorting. Our own node counts towards the limit.
+
+    Out-of-sync seeds show how many commits of our refs they are missing,
+    and how many commits they have that we don't, eg. `-3 / +1`. This is
+    omitted if the seed's version of our refs isn't in our copy of the
+    repository.
+
+    When `status --verbose` is specified, seeds whose sync status is unknown
+    are also displayed, and out-of-sync seeds show why they're out of sync:
+    `behind` if the seed has an older version of our refs, `ahead` if it has
+    a newer version than we do, `diverged` if neither version contains the
+    other, and `unknown` if the seed's version isn't in our copy of the
+    repository.
+
+    When `status --format csv` or `status --format tsv` is specified, one
+    row is output per seed, after a header, with the `nid`, `alias`,
+    `address`, `status`, `tip` and `timestamp` columns. Node IDs and tips are
+    not abbreviated, and timestamps are in seconds since the epoch.
+
+    When `status --watch` is specified, the sync status table is redrawn
+    every `--interval` seconds (default: 2), until every seed other than our
+    own node is in sync, or the command is interrupted with Ctrl-C. This
+    requires a terminal, and can't be combined with `--json` or `--check`.
 
 Commands
 
@@ -56,14 +197,31 @@ Commands
 
 Options
 
-        --sort-by   <field>   Sort the table by column (options: nid, alias, status)
+        --sort-by   <field>   Sort the table by column (options: nid, alias, status, tip, timestamp)
+        --filter    <state>   Only display seeds in the given state (options: synced, out-of-sync, unknown)
+        --format    <format>  Output format of the status table (options: table, csv, tsv)
+        --limit     <count>   Display at most this many seeds in the status table
+        --check               Fail if fewer seeds than `--replicas` are in sync
+        --all                 Display the status of all seeded repositories
+        --json                Output the sync status as json
+        --watch               Redraw the status table until the repository is fully synced
+        --interval  <secs>    How many seconds to wait between redraws (default: 2)
     -f, --fetch               Turn on fetching (default: true)
     -a, --announce            Turn on ref announcing (default: true)
     -i, --inventory           Turn on inventory announcing (default: false)
-        --timeout   <secs>    How many seconds to wait while syncing
-        --seed      <nid>     Sync with the given node (may be specified multiple times)
+        --connected-only      Only announce to connected seeds
+        --timeout   <secs>    How many seconds to fetch, and to announce (default: `sync.timeout` or 9)
+        --seed-timeout <secs> How many seconds to wait for each seed (default: the timeout)
+        --deadline  <secs>    Stop syncing after this many seconds in total
+        --retries   <count>   Retry failed fetches this many times (default: 0)
+        --seed      <seed>    Sync with the given node, as <nid>, <nid>@<addr> or <domain> (may be specified multiple times)
+        --exclude   <nid>     Never sync with the given node (may be specified multiple times)
+        --no-seed-cache       Don't try seeds that recently failed last
     -r, --replicas  <count>   Sync with a specific number of seeds
+        --require-replicas    Fail if fewer seeds than `--replicas` were synced with
+        --report    <path>    Write the per-seed results as json to the given file
     -v, --verbose             Verbose output
+    -q, --quiet               Only print errors and warnings
         --help                Print help
 "#,
 };
@@ -81,6 +239,8 @@ pub enum SortBy {
     Alias,
     #[default]
     Status,
+    Tip,
+    Timestamp,
 }
 
 impl FromStr for SortBy {
@@ -91,18 +251,54 @@ impl FromStr for SortBy {
             "nid" => Ok(Self::Nid),
             "alias" => Ok(Self::Alias),
             "status" => Ok(Self::Status),
+            "tip" => Ok(Self::Tip),
+            "timestamp" => Ok(Self::Timestamp),
             _ => Err("invalid `--sort-by` field"),
         }
     }
 }
 
+/// Sync state of the seeds to display in the status table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncFilter {
+    Synced,
+    OutOfSync,
+    Unknown,
+}
+
+impl SyncFilter {
+    /// Whether the seed is in this sync state.
+    fn matches(&self, seed: &Seed) -> bool {
+        match (self, &seed.sync) {
+            (Self::Synced, Some(SyncStatus::Synced { .. })) => true,
+            (Self::OutOfSync, Some(SyncStatus::OutOfSync { .. })) => true,
+            (Self::Unknown, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for SyncFilter {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "synced" => Ok(Self::Synced),
+            "out-of-sync" => Ok(Self::OutOfSync),
+            "unknown" => Ok(Self::Unknown),
+            _ => Err("invalid `--filter` state"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SyncMode {
     Repo {
         settings: RepoSync,
         direction: SyncDirection,
     },
-    Inventory,
+    /// Announce the inventory to the given seeds, or to all connected peers if none are given.
+    Inventory { seeds: BTreeSet<NodeId> },
 }
 
 impl Default for SyncMode {
@@ -121,6 +317,15 @@ pub struct RepoSync {
     pub replicas: usize,
     /// Sync with the given list of seeds.
     pub seeds: BTreeSet<NodeId>,
+    /// Addresses of some of the given seeds, dialed if they aren't connected.
+    pub addresses: BTreeMap<NodeId, node::Address>,
+    /// How many times a failed fetch from a seed is retried, if the failure looks transient.
+    pub retries: usize,
+    /// Never sync with these seeds.
+    pub exclude: BTreeSet<NodeId>,
+    /// How long to wait for each connection, fetch or announcement. If not set, the overall
+    /// timeout is used.
+    pub seed_timeout: Option<time::Duration>,
 }
 
 impl RepoSync {
@@ -129,11 +334,15 @@ impl RepoSync {
         Self {
             replicas: seeds.len(),
             seeds,
+            addresses: BTreeMap::new(),
+            retries: 0,
+            exclude: BTreeSet::new(),
+            seed_timeout: None,
         }
     }
 
     /// Use profile to populate sync settings, by adding preferred seeds if no seeds are specified,
-    /// and removing the local node from the set.
+    /// and removing the local node and excluded seeds from the set.
     pub fn with_profile(mut self, profile: &Profile) -> Self {
         // If no seeds were specified, add up to `replica` seeds from the preferred seeds.
         if self.seeds.is_empty() {
@@ -142,11 +351,13 @@ impl RepoSync {
                 .preferred_seeds
                 .iter()
                 .map(|p| p.id)
+                .filter(|nid| !self.exclude.contains(nid))
                 .take(self.replicas)
                 .collect();
         }
         // Remove our local node from the seed set just in case it was added by mistake.
         self.seeds.remove(profile.id());
+        self.seeds.retain(|nid| !self.exclude.contains(nid));
         self
     }
 }
@@ -156,6 +367,10 @@ impl Default for RepoSync {
         Self {
             replicas: 3,
             seeds: BTreeSet::new(),
+            addresses: BTreeMap::new(),
+            retries: 0,
+            exclude: BTreeSet::new(),
+            seed_timeout: None,
         }
     }
 }
@@ -172,8 +387,32 @@ pub enum SyncDirection {
 pub struct Options {
     pub rid: Option<RepoId>,
     pub verbose: bool,
-    pub timeout: time::Duration,
+    /// Only print errors and warnings.
+    pub quiet: bool,
+    pub connected_only: bool,
+    /// Fail if fewer seeds than the target replicas were synced with.
+    pub require_replicas: bool,
+    /// Don't use or update the recent fetch outcomes of seeds. See [`health`].
+    pub no_seed_cache: bool,
+    /// Sync with the seeds published under these domains. See [`domain`].
+    pub seed_domains: BTreeSet<String>,
+    /// Timeout given on the command line, overriding the configured one.
+    pub timeout: Option<time::Duration>,
+    /// Maximum time for the whole invocation, across all phases and seeds.
+    pub deadline: Option<time::Duration>,
     pub sort_by: SortBy,
+    /// Only display the seeds in this sync state, in the status table.
+    pub filter: Option<SyncFilter>,
+    pub all: bool,
+    pub json: bool,
+    pub format: term::table::Format,
+    pub limit: Option<usize>,
+    /// Check the sync status against this replication factor, instead of displaying it.
+    pub check: Option<usize>,
+    /// Redraw the sync status at this interval, until the repository is fully synced.
+    pub watch: Option<time::Duration>,
+    /// Write the per-seed results of the sync to this file, as json.
+    pub report: Option<PathBuf>,
     pub op: Operation,
 }
 
@@ -183,14 +422,33 @@ impl Args for Options {
 
         let mut parser = lexopt::Parser::from_args(args);
         let mut verbose = false;
-        let mut timeout = time::Duration::from_secs(9);
+        let mut quiet = false;
+        let mut connected_only = false;
+        let mut require_replicas = false;
+        let mut no_seed_cache = false;
+        let mut retries = 0;
+        let mut timeout = None;
+        let mut seed_timeout = None;
+        let mut deadline = None;
         let mut rid = None;
         let mut fetch = false;
         let mut announce = false;
         let mut inventory = false;
         let mut replicas = None;
         let mut seeds = BTreeSet::new();
+        let mut seed_domains = BTreeSet::new();
+        let mut exclude = BTreeSet::new();
+        let mut addresses = BTreeMap::new();
         let mut sort_by = SortBy::default();
+        let mut filter = None;
+        let mut all = false;
+        let mut json = false;
+        let mut format = term::table::Format::default();
+        let mut limit = None;
+        let mut check = false;
+        let mut watch = false;
+        let mut interval = None;
+        let mut report = None;
         let mut op: Option<Operation> = None;
 
         while let Some(arg) = parser.next()? {
@@ -198,6 +456,9 @@ impl Args for Options {
                 Long("verbose") | Short('v') => {
                     verbose = true;
                 }
+                Long("quiet") | Short('q') => {
+                    quiet = true;
+                }
                 Long("fetch") | Short('f') => {
                     fetch = true;
                 }
@@ -212,25 +473,106 @@ impl Args for Options {
                 }
                 Long("seed") => {
                     let val = parser.value()?;
-                    let nid = term::args::nid(&val)?;
 
+                    if let Some(domain) = val.to_str().filter(|v| domain::is_domain(v)) {
+                        seed_domains.insert(domain.to_owned());
+                        continue;
+                    }
+                    let (nid, addr) = term::args::seed(&val)?;
+
+                    if let Some(addr) = addr {
+                        addresses.insert(nid, addr);
+                    }
                     seeds.insert(nid);
                 }
+                Long("exclude") => {
+                    let val = parser.value()?;
+                    let nid = term::args::nid(&val)?;
+
+                    exclude.insert(nid);
+                }
                 Long("announce") | Short('a') => {
                     announce = true;
                 }
                 Long("inventory") | Short('i') => {
                     inventory = true;
                 }
+                Long("connected-only") => {
+                    connected_only = true;
+                }
+                Long("require-replicas") => {
+                    require_replicas = true;
+                }
+                Long("no-seed-cache") => {
+                    no_seed_cache = true;
+                }
+                Long("report") => {
+                    report = Some(PathBuf::from(parser.value()?));
+                }
                 Long("sort-by") if matches!(op, Some(Operation::Status)) => {
                     let value = parser.value()?;
                     sort_by = value.parse()?;
                 }
+                Long("filter") if matches!(op, Some(Operation::Status)) => {
+                    let value = parser.value()?;
+                    filter = Some(value.parse()?);
+                }
+                Long("all") if matches!(op, Some(Operation::Status)) => {
+                    all = true;
+                }
+                Long("json") if matches!(op, Some(Operation::Status)) => {
+                    json = true;
+                }
+                Long("format") if matches!(op, Some(Operation::Status)) => {
+                    let value = parser.value()?;
+                    format = value.parse()?;
+                }
+                Long("limit") if matches!(op, Some(Operation::Status)) => {
+                    let value = parser.value()?;
+                    let count = term::args::number(&value)?;
+
+                    if count == 0 {
+                        anyhow::bail!("value for `--limit` must be greater than zero");
+                    }
+                    limit = Some(count);
+                }
+                Long("check") if matches!(op, Some(Operation::Status)) => {
+                    check = true;
+                }
+                Long("watch") if matches!(op, Some(Operation::Status)) => {
+                    watch = true;
+                }
+                Long("interval") if matches!(op, Some(Operation::Status)) => {
+                    let value = parser.value()?;
+                    let secs = term::args::parse_value("interval", value)?;
+
+                    if secs == 0 {
+                        anyhow::bail!("value for `--interval` must be greater than zero");
+                    }
+                    interval = Some(time::Duration::from_secs(secs));
+                }
+                Long("retries") => {
+                    let value = parser.value()?;
+
+                    retries = term::args::number(&value)?;
+                }
                 Long("timeout") | Short('t') => {
                     let value = parser.value()?;
                     let secs = term::args::parse_value("timeout", value)?;
 
-                    timeout = time::Duration::from_secs(secs);
+                    timeout = Some(time::Duration::from_secs(secs));
+                }
+                Long("seed-timeout") => {
+                    let value = parser.value()?;
+                    let secs = term::args::parse_value("seed-timeout", value)?;
+
+                    seed_timeout = Some(time::Duration::from_secs(secs));
+                }
+                Long("deadline") => {
+                    let value = parser.value()?;
+                    let secs = term::args::parse_value("deadline", value)?;
+
+                    deadline = Some(time::Duration::from_secs(secs));
                 }
                 Long("help") | Short('h') => {
                     return Err(Error::Help.into());
@@ -249,25 +591,88 @@ impl Args for Options {
             }
         }
 
+        if json && check {
+            anyhow::bail!("`--json` cannot be used with `--check`");
+        }
+        if all && limit.is_some() {
+            anyhow::bail!("`--limit` cannot be used with `--all`");
+        }
+        if check && (all || limit.is_some()) {
+            anyhow::bail!("`--check` cannot be used with `--all` or `--limit`");
+        }
+        if all && rid.is_some() {
+            anyhow::bail!("`--all` cannot be used with an `<rid>`");
+        }
+        if filter.is_some() && (all || check) {
+            anyhow::bail!("`--filter` cannot be used with `--all` or `--check`");
+        }
+        if quiet && verbose {
+            anyhow::bail!("`--quiet` cannot be used with `--verbose`");
+        }
+        if interval.is_some() && !watch {
+            anyhow::bail!("`--interval` can only be used with `--watch`");
+        }
+        if watch && (json || check || all) {
+            anyhow::bail!("`--watch` cannot be used with `--json`, `--check` or `--all`");
+        }
+        if watch && format != term::table::Format::Table {
+            anyhow::bail!("`--watch` can only be used with the `table` format");
+        }
+        if watch && !io::stdout().is_terminal() {
+            anyhow::bail!(
+                "`--watch` requires a terminal; use `--check` to monitor the sync status from scripts"
+            );
+        }
+
+        if require_replicas && (inventory || op.is_some()) {
+            anyhow::bail!("`--require-replicas` can only be used when syncing a repository");
+        }
+        if !seed_domains.is_empty() && (inventory || op.is_some()) {
+            anyhow::bail!("seeds can only be given as a domain when syncing a repository");
+        }
+        if !addresses.is_empty() && inventory {
+            anyhow::bail!("seeds can only be given with an address when syncing a repository");
+        }
+        if report.is_some() && (inventory || op.is_some()) {
+            anyhow::bail!("`--report` can only be used when syncing a repository");
+        }
+        if connected_only && (inventory || (fetch && !announce)) {
+            anyhow::bail!("`--connected-only` can only be used when announcing refs");
+        }
+
         let sync = if inventory && (fetch || announce) {
             anyhow::bail!("`--inventory` cannot be used with `--fetch` or `--announce`");
         } else if inventory {
-            SyncMode::Inventory
+            SyncMode::Inventory { seeds }
         } else {
             let direction = match (fetch, announce) {
                 (true, true) | (false, false) => SyncDirection::Both,
                 (true, false) => SyncDirection::Fetch,
                 (false, true) => SyncDirection::Announce,
             };
-            let settings = if seeds.is_empty() {
+            if retries > 0 && direction == SyncDirection::Announce {
+                anyhow::bail!("`--retries` can only be used when fetching");
+            }
+            if no_seed_cache && direction == SyncDirection::Announce {
+                anyhow::bail!("`--no-seed-cache` can only be used when fetching");
+            }
+            let settings = if seeds.is_empty() && seed_domains.is_empty() {
                 RepoSync {
                     replicas: replicas.unwrap_or(3),
                     seeds,
+                    addresses,
+                    retries,
+                    exclude,
+                    seed_timeout,
                 }
             } else {
                 RepoSync {
-                    replicas: replicas.unwrap_or(seeds.len()),
+                    replicas: replicas.unwrap_or(seeds.len() + seed_domains.len()),
                     seeds,
+                    addresses,
+                    retries,
+                    exclude,
+                    seed_timeout,
                 }
             };
             SyncMode::Repo {
@@ -280,8 +685,22 @@ impl Args for Options {
             Options {
                 rid,
                 verbose,
+                quiet,
+                connected_only,
+                require_replicas,
+                no_seed_cache,
+                seed_domains,
                 timeout,
+                deadline,
                 sort_by,
+                filter,
+                all,
+                json,
+                format,
+                limit,
+                check: check.then(|| replicas.unwrap_or(RepoSync::default().replicas)),
+                watch: watch.then(|| interval.unwrap_or(time::Duration::from_secs(2))),
+                report,
                 op: op.unwrap_or(Operation::Synchronize(sync)),
             },
             vec![],
@@ -291,15 +710,6 @@ impl Args for Options {
 
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
-    let rid = match options.rid {
-        Some(rid) => rid,
-        None => {
-            let (_, rid) =
-                radicle::rad::cwd().context("Current directory is not a Radicle repository")?;
-
-            rid
-        }
-    };
     let mut node = radicle::Node::new(profile.socket());
     if !node.is_running() {
         anyhow::bail!(
@@ -307,363 +717,305 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         );
     }
 
-    match options.op {
+    if options.op == Operation::Status && options.all {
+        return sync_status_all(&mut node, &profile, &options);
+    }
+
+    let rid = match options.rid {
+        Some(rid) => rid,
+        None => match radicle::rad::cwd() {
+            Ok((_, rid)) => rid,
+            Err(_) if !io::stdin().is_terminal() => rid_from_stdin()?,
+            Err(e) => return Err(e).context("Current directory is not a Radicle repository"),
+        },
+    };
+    match &options.op {
         Operation::Status => {
-            sync_status(rid, &mut node, &profile, &options)?;
+            if let Some(replicas) = options.check {
+                sync_check(rid, replicas, options.quiet, &mut node)?;
+            } else if let Some(interval) = options.watch {
+                sync_watch(rid, interval, &mut node, &profile, &options)?;
+            } else {
+                sync_status(rid, &mut node, &profile, &options)?;
+            }
         }
         Operation::Synchronize(SyncMode::Repo {
             settings,
             direction,
         }) => {
-            let settings = settings.with_profile(&profile);
-
-            if [SyncDirection::Fetch, SyncDirection::Both].contains(&direction) {
-                if !profile.policies()?.is_seeding(&rid)? {
-                    anyhow::bail!("repository {rid} is not seeded");
-                }
-                let results = fetch(rid, settings.clone(), options.timeout, &mut node)?;
-                let success = results.success().count();
-                let failed = results.failed().count();
-
-                if results.is_empty() {
-                    term::error(format!("no seeds found for {rid}"));
-                } else if success == 0 {
-                    term::error(format!("repository fetch from {failed} seed(s) failed"));
-                } else {
-                    term::success!("Fetched repository from {success} seed(s)");
-                }
-            }
-            if [SyncDirection::Announce, SyncDirection::Both].contains(&direction) {
-                announce_refs(rid, settings, options.timeout, &mut node, &profile)?;
+            let report = if options.report.is_some() {
+                ReportSink::new(rid)
+            } else {
+                ReportSink::default()
+            };
+            let result = sync_repo(
+                rid,
+                settings.clone(),
+                direction,
+                report.clone(),
+                &mut node,
+                &profile,
+                &options,
+            );
+            if let Some(path) = &options.report {
+                report
+                    .write(path)
+                    .with_context(|| format!("failed to write report to {}", path.display()))?;
             }
+            result?;
         }
-        Operation::Synchronize(SyncMode::Inventory) => {
-            announce_inventory(node)?;
+        Operation::Synchronize(SyncMode::Inventory { seeds }) => {
+            announce_inventory(node, seeds, options.quiet)?;
         }
     }
     Ok(())
 }
 
-fn sync_status(
+/// Synchronize a repository with its seeds, in the given direction.
+fn sync_repo(
     rid: RepoId,
+    mut settings: RepoSync,
+    direction: &SyncDirection,
+    report: ReportSink,
     node: &mut Node,
     profile: &Profile,
     options: &Options,
 ) -> anyhow::Result<()> {
-    let mut table = Table::<7, term::Label>::new(TableOptions::bordered());
-    let mut seeds: Vec<_> = node.seeds(rid)?.into();
-    let local = node.nid()?;
-    let aliases = profile.aliases();
-
-    table.push([
-        term::format::dim(String::from("●")).into(),
-        term::format::bold(String::from("Node")).into(),
-        term::Label::blank(),
-        term::format::bold(String::from("Address")).into(),
-        term::format::bold(String::from("Status")).into(),
-        term::format::bold(String::from("Tip")).into(),
-        term::format::bold(String::from("Timestamp")).into(),
-    ]);
-    table.divider();
-
-    sort_seeds_by(local, &mut seeds, &aliases, &options.sort_by);
-
-    for seed in seeds {
-        let (icon, status, head, time) = match seed.sync {
-            Some(SyncStatus::Synced { at }) => (
-                term::format::positive("●"),
-                term::format::positive(if seed.nid != local { "synced" } else { "" }),
-                term::format::oid(at.oid),
-                term::format::timestamp(at.timestamp),
-            ),
-            Some(SyncStatus::OutOfSync { remote, .. }) => (
-                term::format::negative("●"),
-                term::format::negative(if seed.nid != local { "out-of-sync" } else { "" }),
-                term::format::oid(remote.oid),
-                term::format::timestamp(remote.timestamp),
-            ),
-            None if options.verbose => (
-                term::format::dim("●"),
-                term::format::dim("unknown"),
-                term::paint(String::new()),
-                term::paint(String::new()),
-            ),
-            None => continue,
-        };
-        let addr = seed
-            .addrs
-            .first()
-            .map(|a| a.addr.to_string())
-            .unwrap_or_default()
-            .into();
-        let (alias, nid) = Author::new(&seed.nid, profile).labels();
-
-        table.push([
-            icon.into(),
-            alias,
-            nid,
-            addr,
-            status.into(),
-            term::format::secondary(head).into(),
-            time.dim().italic().into(),
-        ]);
-    }
-    table.print();
-
-    Ok(())
-}
+    let timeout = sync_timeout(options.timeout, &profile.config);
+    let deadline = options.deadline.map(|d| time::Instant::now() + d);
+    // Seeds found by resolving domains, that are dialed before fetching.
+    let mut found = Vec::new();
 
-fn announce_refs(
-    rid: RepoId,
-    settings: RepoSync,
-    timeout: time::Duration,
-    node: &mut Node,
-    profile: &Profile,
-) -> anyhow::Result<()> {
-    let Ok(repo) = profile.storage.repository(rid) else {
-        return Err(anyhow!(
-            "nothing to announce, repository {rid} is not available locally"
-        ));
-    };
-    let doc = repo.identity_doc()?;
-    let unsynced: Vec<_> = if doc.visibility.is_public() {
-        // All seeds.
-        let all = node.seeds(rid)?;
-        // Seeds in sync with us.
-        let synced = all.iter().filter(|s| s.is_synced());
-        // Replicas not counting our local replica.
-        let replicas = all
-            .iter()
-            .filter(|s| s.is_synced() && &s.nid != profile.id())
-            .count();
-        // Maximum replication factor we can achieve.
-        let max_replicas = all.iter().filter(|s| &s.nid != profile.id()).count();
-        // If the seeds we specified in the sync settings are all synced.
-        let is_seeds_synced = {
-            let synced = synced.map(|s| s.nid).collect::<BTreeSet<_>>();
-            settings.seeds.iter().all(|s| synced.contains(s))
-        };
-        // If we met our desired replica count. Note that this can never exceed the maximum count.
-        let is_replicas_synced = replicas >= settings.replicas.min(max_replicas);
-
-        // Nothing to do if we've met our sync state.
-        if is_seeds_synced && is_replicas_synced {
-            term::success!("Nothing to announce, already in sync with {replicas} node(s) (see `rad sync status`)");
-            return Ok(());
-        }
-        // Return nodes we can announce to.
-        all.connected()
-            .filter(|s| !s.is_synced())
-            .map(|s| s.nid)
-            .collect()
-    } else {
-        node.sessions()?
-            .into_iter()
-            .filter(|s| s.state.is_connected() && doc.is_visible_to(&s.nid))
-            .map(|s| s.nid)
-            .collect()
-    };
+    for domain in &options.seed_domains {
+        let resolver = domain::SystemResolver::new(timeout.min(RESOLVE_TIMEOUT))?;
+        let (nid, addrs) = domain::resolve_seed(domain, &resolver)?;
 
-    if unsynced.is_empty() {
-        term::info!("Not connected to any seeds for {rid}.");
-        return Ok(());
+        if options.verbose {
+            term::info!("Resolved seed {domain} to {}", term::format::node(&nid));
+        }
+        settings.seeds.insert(nid);
+        settings
+            .addresses
+            .entry(nid)
+            .or_insert_with(|| addrs[0].clone());
+        found.push((nid, addrs));
     }
+    // Resolved seeds are added before the preferred seeds, since they were asked for.
+    let settings = settings.with_profile(profile);
+    found.retain(|(nid, _)| settings.seeds.contains(nid));
+
+    let mut ctx = SyncContext {
+        rid,
+        settings,
+        timeout,
+        deadline,
+        unreachable: Unreachable::default(),
+        report,
+        quiet: options.quiet,
+        node,
+    };
+    // Whether no seed could be fetched from.
+    let mut failed = false;
+    // Number of seeds fetched from, and in sync with us after announcing, if requested.
+    let mut fetched = None;
+    let mut in_sync = None;
+
+    if [SyncDirection::Fetch, SyncDirection::Both].contains(direction) {
+        if !profile.policies()?.is_seeding(&rid)? {
+            anyhow::bail!("repository {rid} is not seeded");
+        }
+        // Recent fetch outcomes of seeds, used to try seeds that recently failed last.
+        let mut seed_health = (!options.no_seed_cache).then(|| {
+            let expiry = profile
+                .config
+                .sync
+                .seed_health_expiry
+                .map_or(health::DEFAULT_EXPIRY, time::Duration::from_secs);
+
+            health::SeedHealth::open(health::path(&profile.home), expiry)
+        });
+        for (nid, addrs) in found {
+            let Some(timeout) = remaining(ctx.settings.seed_timeout.unwrap_or(timeout), deadline)
+            else {
+                break;
+            };
+            let connected = ctx
+                .node
+                .sessions()?
+                .iter()
+                .any(|s| s.nid == nid && s.is_connected());
 
-    let mut spinner = term::spinner(format!("Syncing with {} node(s)..", unsynced.len()));
-    let result = node.announce(rid, unsynced, timeout, |event, replicas| match event {
-        node::AnnounceEvent::Announced => ControlFlow::Continue(()),
-        node::AnnounceEvent::RefsSynced { remote } => {
-            spinner.message(format!("Synced with {remote}.."));
-
-            // We're done syncing when both of these conditions are met:
-            //
-            // 1. We've matched or exceeded our target replica count.
-            // 2. We've synced with the seeds specified manually.
-            if replicas.len() >= settings.replicas
-                && settings.seeds.iter().all(|s| replicas.contains(s))
-            {
-                ControlFlow::Break(())
-            } else {
-                ControlFlow::Continue(())
+            if connected || ctx.connect(nid, addrs.into_iter(), timeout)? {
+                ctx.settings.seeds.insert(nid);
             }
         }
-    })?;
-
-    if result.synced.is_empty() {
-        spinner.failed();
-    } else {
-        spinner.message(format!("Synced with {} node(s)", result.synced.len()));
-        spinner.finish();
-    }
-    for seed in result.timeout {
-        term::notice!("Seed {seed} timed out..");
+        let (results, summary, pipeline) = fetch_with_pipeline(&mut ctx, seed_health.as_mut())?;
+
+        if summary.is_empty() {
+            term::error(format!("no seeds found for {rid}"));
+            failed = true;
+        } else if !summary.is_success() {
+            term::error(format!(
+                "repository fetch from {} seed(s) failed",
+                summary.failed.len()
+            ));
+            failed = true;
+        } else if !options.quiet {
+            term::success!("Fetched repository from {} seed(s)", summary.synced.len());
+        }
+        if options.verbose {
+            pipeline.print(summary.synced.len());
+        }
+        if options.verbose && !results.is_empty() {
+            print_protocol_versions(&results, &ctx.node.sessions()?);
+        }
+        fetched = Some(summary.synced.len());
     }
-    if result.synced.is_empty() {
-        anyhow::bail!("all seeds timed out");
+    if [SyncDirection::Announce, SyncDirection::Both].contains(direction) {
+        in_sync = Some(if remaining(timeout, deadline).is_none() {
+            term::warning("deadline reached, refs were not announced");
+            0
+        } else {
+            announce_refs(&mut ctx, options.connected_only, profile)?
+        });
     }
-    Ok(())
-}
-
-pub fn announce_inventory(mut node: Node) -> anyhow::Result<()> {
-    let peers = node.sessions()?.iter().filter(|s| s.is_connected()).count();
-    let spinner = term::spinner(format!("Announcing inventory to {peers} peers.."));
 
-    node.sync_inventory()?;
-    node.announce_inventory()?;
-    spinner.finish();
+    if failed {
+        return Err(Error::Exit { code: EXIT_FAILURE }.into());
+    }
+    if options.require_replicas {
+        let target = replica_target(&ctx.settings, &ctx.node.seeds(rid)?, profile.id());
 
+        if fetched.into_iter().chain(in_sync).any(|n| n < target) {
+            term::error(format!("fewer than {target} replica(s) were synced with"));
+            return Err(Error::Exit { code: EXIT_PARTIAL }.into());
+        }
+    }
     Ok(())
 }
 
-pub fn fetch(
+/// What the phases of syncing a repository share, ie. fetching it from seeds and announcing
+/// our refs to them.
+struct SyncContext<'a> {
     rid: RepoId,
     settings: RepoSync,
+    /// How long each phase may take.
     timeout: time::Duration,
-    node: &mut Node,
-) -> Result<FetchResults, node::Error> {
-    let local = node.nid()?;
-    // Get seeds. This consults the local routing table only.
-    let seeds = node.seeds(rid)?;
-    // Target replicas, clamped by the maximum replicas possible.
-    let replicas = settings
-        .replicas
-        .min(seeds.iter().filter(|s| s.nid != local).count());
-    let sessions = node.sessions()?;
-    let mut results = FetchResults::default();
-    let (connected, mut disconnected) = seeds.partition();
-
-    // Fetch from specified seeds, plus our preferred seeds.
-    for nid in &settings.seeds {
-        if !sessions.iter().any(|s| &s.nid == nid) {
-            term::warning(format!("node {nid} is not connected.. skipping"));
-            continue;
-        }
-        let result = fetch_from(rid, nid, timeout, node)?;
-        results.push(*nid, result);
-    }
-    if results.success().count() >= replicas {
-        return Ok(results);
-    }
+    /// When the whole command must be done by, if ever. See [`remaining`].
+    deadline: Option<time::Instant>,
+    /// Seed addresses that couldn't be connected to, so that they aren't dialed again.
+    unreachable: Unreachable,
+    report: ReportSink,
+    /// Only print errors and warnings.
+    quiet: bool,
+    node: &'a mut Node,
+}
 
-    // Fetch from connected seeds.
-    let connected = connected
-        .into_iter()
-        .filter(|c| !results.contains(&c.nid))
-        .map(|c| c.nid)
-        .take(replicas)
-        .collect::<Vec<_>>();
-    for nid in connected {
-        let result = fetch_from(rid, &nid, timeout, node)?;
-        results.push(nid, result);
-    }
+/// Read a Repository ID from the first line of the standard input.
+fn rid_from_stdin() -> anyhow::Result<RepoId> {
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("failed to read Repository ID from standard input")?;
+    let line = line.trim();
 
-    // Try to connect to disconnected seeds and fetch from them.
-    while results.success().count() < replicas {
-        let Some(seed) = disconnected.pop() else {
-            break;
-        };
-        if seed.nid == local {
-            // Skip our own node.
-            continue;
-        }
-        if connect(
-            seed.nid,
-            seed.addrs.into_iter().map(|ka| ka.addr),
-            timeout,
-            node,
-        )? {
-            let result = fetch_from(rid, &seed.nid, timeout, node)?;
-            results.push(seed.nid, result);
-        }
+    if line.is_empty() {
+        anyhow::bail!(
+            "Current directory is not a Radicle repository, and no Repository ID was given on standard input"
+        );
     }
+    RepoId::from_str(line).map_err(|_| anyhow!("invalid Repository ID '{line}' on standard input"))
+}
+
+/// Exit code when no seed could be fetched from, or no seed synced with the refs we announced.
+pub const EXIT_FAILURE: i32 = 1;
+
+/// Exit code when `--require-replicas` is given and the target replicas were not met.
+pub const EXIT_PARTIAL: i32 = 2;
 
-    Ok(results)
+/// Sync timeout used when none is given on the command line or in the configuration.
+pub const DEFAULT_TIMEOUT: time::Duration = time::Duration::from_secs(9);
+
+/// How long to wait for the name server when resolving seeds. See [`domain`].
+pub const RESOLVE_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+/// The sync timeout: the one given on the command line, if any, otherwise the configured one.
+fn sync_timeout(
+    timeout: Option<time::Duration>,
+    config: &radicle::profile::Config,
+) -> time::Duration {
+    timeout
+        .or(config.sync.timeout.map(time::Duration::from_secs))
+        .unwrap_or(DEFAULT_TIMEOUT)
 }
 
-fn connect(
-    nid: NodeId,
-    addrs: impl Iterator<Item = node::Address>,
-    timeout: time::Duration,
-    node: &mut Node,
-) -> Result<bool, node::Error> {
-    // Try all addresses until one succeeds.
-    for addr in addrs {
-        let spinner = term::spinner(format!(
-            "Connecting to {}@{}..",
-            term::format::tertiary(term::format::node(&nid)),
-            &addr
-        ));
-        let cr = node.connect(
-            nid,
-            addr,
-            node::ConnectOptions {
-                persistent: false,
-                timeout,
-            },
-        )?;
+/// The time an operation may take, given its timeout and the deadline of the whole command.
+/// Returns `None` if the deadline has passed, in which case no new operation should be started.
+fn remaining(timeout: time::Duration, deadline: Option<time::Instant>) -> Option<time::Duration> {
+    let Some(deadline) = deadline else {
+        return Some(timeout);
+    };
+    let left = deadline.saturating_duration_since(time::Instant::now());
 
-        match cr {
-            node::ConnectResult::Connected => {
-                spinner.finish();
-                return Ok(true);
-            }
-            node::ConnectResult::Disconnected { .. } => {
-                spinner.failed();
-                continue;
-            }
-        }
-    }
-    Ok(false)
+    (!left.is_zero()).then_some(timeout.min(left))
 }
 
-fn fetch_from(
-    rid: RepoId,
-    seed: &NodeId,
-    timeout: time::Duration,
-    node: &mut Node,
-) -> Result<FetchResult, node::Error> {
-    let spinner = term::spinner(format!(
-        "Fetching {} from {}..",
-        term::format::tertiary(rid),
-        term::format::tertiary(term::format::node(seed))
-    ));
-    let result = node.fetch(rid, *seed, timeout)?;
+/// Create a spinner, which only prints failures if `quiet` is set, ie. with `--quiet`.
+fn spinner(message: impl ToString, quiet: bool) -> term::Spinner {
+    if quiet {
+        term::quiet_spinner_to(message, io::sink())
+    } else {
+        term::spinner(message)
+    }
+}
 
-    match &result {
-        FetchResult::Success { .. } => {
-            spinner.finish();
-        }
-        FetchResult::Failed { reason } => {
-            spinner.error(reason);
-        }
+/// Log a structured event under the `sync` target, as space-separated `key=value` fields,
+/// eg. `event=connect nid=z6Mk.. addrs=1 outcome=connected elapsed_ms=12`.
+fn log_event(event: &str, fields: &[(&str, &dyn fmt::Display)]) {
+    if !log::log_enabled!(target: "sync", log::Level::Info) {
+        return;
+    }
+    let mut line = format!("event={event}");
+    for (key, value) in fields {
+        line.push_str(&format!(" {key}={value}"));
     }
-    Ok(result)
+    log::info!(target: "sync", "{line}");
 }
 
-fn sort_seeds_by(local: NodeId, seeds: &mut [Seed], aliases: &impl AliasStore, sort_by: &SortBy) {
-    let compare = |a: &Seed, b: &Seed| match sort_by {
-        SortBy::Nid => a.nid.cmp(&b.nid),
-        SortBy::Alias => {
-            let a = aliases.alias(&a.nid);
-            let b = aliases.alias(&b.nid);
-            a.cmp(&b)
-        }
-        SortBy::Status => match (&a.sync, &b.sync) {
-            (Some(_), None) => Ordering::Less,
-            (None, Some(_)) => Ordering::Greater,
-            (Some(a), Some(b)) => a.cmp(b).reverse(),
-            (None, None) => Ordering::Equal,
-        },
-    };
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    // Always show our local node first.
-    seeds.sort_by(|a, b| {
-        if a.nid == local {
-            Ordering::Less
-        } else if b.nid == local {
-            Ordering::Greater
-        } else {
-            compare(a, b)
-        }
-    });
+    #[test]
+    fn test_sync_timeout() {
+        let mut config = radicle::profile::Config::new(node::Alias::new("alice"));
+        assert_eq!(sync_timeout(None, &config), DEFAULT_TIMEOUT);
+
+        config.sync.timeout = Some(30);
+        assert_eq!(sync_timeout(None, &config), time::Duration::from_secs(30));
+
+        // An explicit timeout always wins over the configured one.
+        assert_eq!(
+            sync_timeout(Some(time::Duration::from_secs(3)), &config),
+            time::Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn test_remaining() {
+        let timeout = time::Duration::from_secs(9);
+        let now = time::Instant::now();
+
+        assert_eq!(remaining(timeout, None), Some(timeout));
+        assert_eq!(
+            remaining(timeout, Some(now + time::Duration::from_secs(60))),
+            Some(timeout)
+        );
+        assert!(
+            remaining(timeout, Some(now + time::Duration::from_secs(3))).unwrap()
+                <= time::Duration::from_secs(3)
+        );
+        assert_eq!(remaining(timeout, Some(now)), None);
+    }
 }
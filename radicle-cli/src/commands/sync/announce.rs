@@ -0,0 +1,286 @@
+//! Announcing our refs, or our inventory, to seeds.
+use std::collections::BTreeSet;
+use std::io;
+use std::ops::ControlFlow;
+use std::time;
+
+use anyhow::anyhow;
+
+use radicle::node;
+use radicle::node::{Handle as _, Node};
+use radicle::prelude::{NodeId, Profile};
+use radicle::storage::{ReadRepository, ReadStorage};
+
+use crate::terminal as term;
+
+use super::{log_event, remaining, spinner, SyncContext};
+
+/// Announce our refs to the seeds of a repository. Returns the number of seeds in sync
+/// with us afterwards, not counting our own node.
+///
+/// If all seeds time out and we're running in a terminal, the user is asked whether to
+/// retry with twice the timeout.
+pub(super) fn announce_refs(
+    ctx: &mut SyncContext<'_>,
+    connected_only: bool,
+    profile: &Profile,
+) -> anyhow::Result<usize> {
+    let interactive = term::Interactive::new(io::stderr());
+    let mut timeout = ctx.timeout;
+
+    loop {
+        if let Some(in_sync) = try_announce_refs(ctx, timeout, connected_only, profile)? {
+            return Ok(in_sync);
+        }
+        timeout = timeout.saturating_mul(2);
+
+        // There's no point in retrying past the deadline.
+        if remaining(timeout, ctx.deadline).is_none()
+            || !interactive.retry(format!(
+                "All seeds timed out. Retry with a timeout of {}s?",
+                timeout.as_secs()
+            ))
+        {
+            anyhow::bail!("all seeds timed out");
+        }
+    }
+}
+
+/// Announce our refs once, like [`announce_refs`], within the given timeout. Returns `None`
+/// if all seeds timed out.
+fn try_announce_refs(
+    ctx: &mut SyncContext<'_>,
+    timeout: time::Duration,
+    connected_only: bool,
+    profile: &Profile,
+) -> anyhow::Result<Option<usize>> {
+    let rid = ctx.rid;
+    let started = time::Instant::now();
+    // The timeout bounds the whole announcement, not each connection or announcement.
+    let bound = started + timeout;
+    let deadline = Some(ctx.deadline.map_or(bound, |d| d.min(bound)));
+    let timeout = ctx.settings.seed_timeout.unwrap_or(timeout);
+    let Ok(repo) = profile.storage.repository(rid) else {
+        return Err(anyhow!(
+            "nothing to announce, repository {rid} is not available locally"
+        ));
+    };
+    let doc = repo.identity_doc()?;
+    // Dial the seeds we were given an address for, so that we can announce to them. Excluded
+    // seeds are never announced to, so they aren't dialed either.
+    let sessions = ctx.node.sessions()?;
+    let addresses = ctx
+        .settings
+        .addresses
+        .iter()
+        .filter(|(nid, _)| !ctx.settings.exclude.contains(nid))
+        .map(|(nid, addr)| (*nid, addr.clone()))
+        .collect::<Vec<_>>();
+
+    for (nid, addr) in addresses {
+        if sessions.iter().any(|s| s.nid == nid && s.is_connected()) {
+            continue;
+        }
+        let Some(timeout) = remaining(timeout, deadline) else {
+            term::warning(format!(
+                "deadline reached, not connecting to {}@{addr}",
+                term::format::node(&nid)
+            ));
+            continue;
+        };
+        if !ctx.connect(nid, std::iter::once(addr.clone()), timeout)? {
+            term::error(format!(
+                "failed to connect to {}@{addr}, refs will not be announced to it",
+                term::format::node(&nid)
+            ));
+        }
+    }
+
+    let (node, settings, report, quiet) = (&mut *ctx.node, &ctx.settings, &ctx.report, ctx.quiet);
+    let mut unsynced: Vec<_> = if doc.visibility.is_public() && connected_only {
+        // Connected seeds not in sync with us, regardless of replication targets.
+        node.seeds(rid)?
+            .connected()
+            .filter(|s| !s.is_synced() && &s.nid != profile.id())
+            .map(|s| s.nid)
+            .collect()
+    } else if doc.visibility.is_public() {
+        // All seeds.
+        let all = node.seeds(rid)?;
+        // Seeds in sync with us.
+        let synced = all.iter().filter(|s| s.is_synced());
+        // Replicas not counting our local replica.
+        let replicas = all
+            .iter()
+            .filter(|s| s.is_synced() && &s.nid != profile.id())
+            .count();
+        // Maximum replication factor we can achieve.
+        let max_replicas = all.iter().filter(|s| &s.nid != profile.id()).count();
+        // If the seeds we specified in the sync settings are all synced.
+        let is_seeds_synced = {
+            let synced = synced.map(|s| s.nid).collect::<BTreeSet<_>>();
+            settings.seeds.iter().all(|s| synced.contains(s))
+        };
+        // If we met our desired replica count. Note that this can never exceed the maximum count.
+        let is_replicas_synced = replicas >= settings.replicas.min(max_replicas);
+
+        // Nothing to do if we've met our sync state.
+        if is_seeds_synced && is_replicas_synced {
+            if !quiet {
+                term::success!(
+                    "Nothing to announce, already in sync with {replicas} node(s) (see `rad sync status`)"
+                );
+            }
+            return Ok(Some(replicas));
+        }
+        // Return nodes we can announce to.
+        all.connected()
+            .filter(|s| !s.is_synced())
+            .map(|s| s.nid)
+            .collect()
+    } else {
+        node.sessions()?
+            .into_iter()
+            .filter(|s| s.state.is_connected() && doc.is_visible_to(&s.nid))
+            .map(|s| s.nid)
+            .collect()
+    };
+
+    // Also announce to the seeds we dialed, which may not be known to seed the repository yet.
+    if !settings.addresses.is_empty() {
+        let synced = node.seeds(rid)?;
+        for session in node.sessions()? {
+            if session.is_connected()
+                && settings.addresses.contains_key(&session.nid)
+                && !synced.iter().any(|s| s.nid == session.nid && s.is_synced())
+                && !unsynced.contains(&session.nid)
+                && doc.is_visible_to(&session.nid)
+            {
+                unsynced.push(session.nid);
+            }
+        }
+    }
+
+    unsynced.retain(|nid| !settings.exclude.contains(nid));
+
+    // Seeds already in sync with us, not counting our local replica.
+    let in_sync = node
+        .seeds(rid)?
+        .iter()
+        .filter(|s| s.is_synced() && &s.nid != profile.id())
+        .count();
+
+    if unsynced.is_empty() {
+        log_event(
+            "announce",
+            &[("rid", &rid), ("seeds", &0), ("outcome", &"skipped")],
+        );
+        if !quiet {
+            term::info!("Not connected to any seeds for {rid}.");
+        }
+        return Ok(Some(in_sync));
+    }
+    let seeds = unsynced.len();
+    let Some(timeout) = remaining(timeout, deadline) else {
+        term::warning("deadline reached, refs were not announced");
+        return Ok(Some(in_sync));
+    };
+
+    // Replica target, clamped by the number of seeds we announce to. When only announcing to
+    // connected seeds, we wait for all of them.
+    let target = if connected_only {
+        seeds
+    } else {
+        settings.replicas.min(seeds)
+    };
+    let mut spinner = spinner(format!("Syncing with {} node(s)..", unsynced.len()), quiet);
+    let announced = time::Instant::now();
+    let result = node.announce(rid, unsynced, timeout, |event, replicas| match event {
+        node::AnnounceEvent::Announced => ControlFlow::Continue(()),
+        node::AnnounceEvent::RefsSynced { remote } => {
+            spinner.message(format!(
+                "Synced with {}/{target} node(s), last with {remote}..",
+                replicas.len()
+            ));
+            report.record(|r| r.announced(&remote, Ok(announced.elapsed())));
+
+            // We're done syncing when both of these conditions are met:
+            //
+            // 1. We've matched or exceeded our target replica count.
+            // 2. We've synced with the seeds specified manually.
+            //
+            // When only announcing to connected seeds, we wait for all of them instead.
+            if !connected_only
+                && replicas.len() >= settings.replicas
+                && settings.seeds.iter().all(|s| replicas.contains(s))
+            {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    })?;
+
+    log_event(
+        "announce",
+        &[
+            ("rid", &rid),
+            ("seeds", &seeds),
+            ("synced", &result.synced.len()),
+            ("timed_out", &result.timeout.len()),
+            ("elapsed_ms", &started.elapsed().as_millis()),
+        ],
+    );
+
+    if result.synced.is_empty() {
+        spinner.failed();
+    } else {
+        spinner.message(format!(
+            "Synced with {}/{target} node(s)",
+            result.synced.len()
+        ));
+        spinner.finish();
+    }
+    for seed in result.timeout {
+        term::notice!("Seed {seed} timed out..");
+        report.record(|r| r.announced(&seed, Err(timeout)));
+    }
+    if result.synced.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(in_sync + result.synced.len()))
+}
+
+/// Announce the node's inventory to the given seeds, or to all connected peers if none are given.
+pub fn announce_inventory(
+    mut node: Node,
+    seeds: &BTreeSet<NodeId>,
+    quiet: bool,
+) -> anyhow::Result<()> {
+    let sessions = node.sessions()?;
+    let mut targets = Vec::new();
+
+    for nid in seeds {
+        if !sessions.iter().any(|s| &s.nid == nid && s.is_connected()) {
+            term::warning(format!("node {nid} is not connected.. skipping"));
+            continue;
+        }
+        targets.push(*nid);
+    }
+    let peers = if seeds.is_empty() {
+        sessions.iter().filter(|s| s.is_connected()).count()
+    } else {
+        targets.len()
+    };
+    let spinner = spinner(format!("Announcing inventory to {peers} peers.."), quiet);
+
+    node.sync_inventory()?;
+    if seeds.is_empty() {
+        node.announce_inventory()?;
+    } else {
+        node.announce_inventory_to(targets)?;
+    }
+    spinner.finish();
+
+    Ok(())
+}
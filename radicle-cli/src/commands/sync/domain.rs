@@ -0,0 +1,208 @@
+//! Seeds published in DNS records.
+//!
+//! Organizations can publish a seed under a domain with records for `_radicle._tcp.<domain>`:
+//! `SRV` records pointing to the seed's addresses, and a `TXT` record holding `nid=<nid>`.
+use std::str::FromStr;
+use std::{io, time};
+
+use anyhow::anyhow;
+use hickory_resolver::error::ResolveErrorKind;
+
+use radicle::node;
+use radicle::prelude::NodeId;
+
+/// Prefix of the names under which seeds are published.
+pub const SERVICE_PREFIX: &str = "_radicle._tcp";
+
+/// Looks up DNS records.
+pub trait Resolver {
+    /// Get the priority, port and target host of the `SRV` records of the given name.
+    fn srv(&self, name: &str) -> io::Result<Vec<(u16, u16, String)>>;
+    /// Get the entries of the `TXT` records of the given name.
+    fn txt(&self, name: &str) -> io::Result<Vec<String>>;
+}
+
+/// Resolver using the system resolver configuration.
+pub struct SystemResolver {
+    inner: hickory_resolver::Resolver,
+}
+
+impl SystemResolver {
+    /// Create a resolver from the system configuration, waiting at most `timeout` for
+    /// each response.
+    pub fn new(timeout: time::Duration) -> io::Result<Self> {
+        let (config, mut opts) = hickory_resolver::system_conf::read_system_conf()?;
+        opts.timeout = timeout;
+
+        Ok(Self {
+            inner: hickory_resolver::Resolver::new(config, opts)?,
+        })
+    }
+}
+
+impl Resolver for SystemResolver {
+    fn srv(&self, name: &str) -> io::Result<Vec<(u16, u16, String)>> {
+        match self.inner.srv_lookup(format!("{name}.")) {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .map(|srv| (srv.priority(), srv.port(), srv.target().to_utf8()))
+                .collect()),
+            Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn txt(&self, name: &str) -> io::Result<Vec<String>> {
+        match self.inner.txt_lookup(format!("{name}.")) {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .flat_map(|txt| txt.iter())
+                .map(|data| String::from_utf8_lossy(data).into_owned())
+                .collect()),
+            Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Check whether a seed given on the command line looks like a domain, rather than a Node ID.
+pub fn is_domain(val: &str) -> bool {
+    !val.contains('@')
+        && val.contains('.')
+        && val
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+/// Find the seed published under the given domain, returning its Node ID and addresses,
+/// ordered by priority.
+pub fn resolve_seed(
+    domain: &str,
+    resolver: &impl Resolver,
+) -> anyhow::Result<(NodeId, Vec<node::Address>)> {
+    let name = format!("{SERVICE_PREFIX}.{}", domain.trim_end_matches('.'));
+    let texts = resolver
+        .txt(&name)
+        .map_err(|e| anyhow!("failed to look up the TXT records of `{name}`: {e}"))?;
+    let nid = match texts
+        .iter()
+        .filter_map(|t| t.strip_prefix("nid="))
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [nid] => NodeId::from_str(nid)
+            .map_err(|_| anyhow!("invalid Node ID '{nid}' in the TXT records of `{name}`"))?,
+        [] => anyhow::bail!("no `nid=<nid>` entry found in the TXT records of `{name}`"),
+        _ => anyhow::bail!("more than one Node ID found in the TXT records of `{name}`"),
+    };
+    let mut services = resolver
+        .srv(&name)
+        .map_err(|e| anyhow!("failed to look up the SRV records of `{name}`: {e}"))?;
+    if services.is_empty() {
+        anyhow::bail!("no SRV records found for `{name}`");
+    }
+    services.sort_by_key(|(priority, _, _)| *priority);
+
+    let addrs = services
+        .into_iter()
+        .map(|(_, port, host)| {
+            let addr = format!("{}:{port}", host.trim_end_matches('.'));
+            node::Address::from_str(&addr)
+                .map_err(|_| anyhow!("invalid address '{addr}' in the SRV records of `{name}`"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((nid, addrs))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockResolver {
+        services: Vec<(u16, u16, String)>,
+        texts: Vec<String>,
+    }
+
+    impl Resolver for MockResolver {
+        fn srv(&self, name: &str) -> io::Result<Vec<(u16, u16, String)>> {
+            assert_eq!(name, "_radicle._tcp.example.com");
+            Ok(self.services.clone())
+        }
+
+        fn txt(&self, name: &str) -> io::Result<Vec<String>> {
+            if self.texts.is_empty() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            assert_eq!(name, "_radicle._tcp.example.com");
+            Ok(self.texts.clone())
+        }
+    }
+
+    #[test]
+    fn test_resolve_seed() {
+        let nid = *radicle::crypto::test::signer::MockSigner::from_seed([1; 32]).public_key();
+        let resolver = MockResolver {
+            services: vec![
+                (20, 8776, String::from("backup.example.com.")),
+                (10, 8777, String::from("seed.example.com")),
+            ],
+            texts: vec![String::from("v=1"), format!("nid={nid}")],
+        };
+        let (resolved, addrs) = resolve_seed("example.com.", &resolver).unwrap();
+
+        assert_eq!(resolved, nid);
+        assert_eq!(
+            addrs,
+            vec![
+                "seed.example.com:8777".parse().unwrap(),
+                "backup.example.com:8776".parse().unwrap(),
+            ]
+        );
+
+        // Resolution failures.
+        let err = |resolver: MockResolver| {
+            resolve_seed("example.com", &resolver)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            err(MockResolver::default()),
+            "failed to look up the TXT records of `_radicle._tcp.example.com`: timed out"
+        );
+        assert_eq!(
+            err(MockResolver {
+                texts: vec![String::from("nid=z6Mk")],
+                ..MockResolver::default()
+            }),
+            "invalid Node ID 'z6Mk' in the TXT records of `_radicle._tcp.example.com`"
+        );
+        assert_eq!(
+            err(MockResolver {
+                texts: vec![String::from("v=1")],
+                ..MockResolver::default()
+            }),
+            "no `nid=<nid>` entry found in the TXT records of `_radicle._tcp.example.com`"
+        );
+        assert_eq!(
+            err(MockResolver {
+                texts: vec![format!("nid={nid}")],
+                ..MockResolver::default()
+            }),
+            "no SRV records found for `_radicle._tcp.example.com`"
+        );
+    }
+
+    #[test]
+    fn test_is_domain() {
+        assert!(is_domain("seed.example.com"));
+        assert!(!is_domain(
+            "z6MksFqXN3Yhqk8pTJdUGLwATkRfQvwZXPqR2qMEhbS9wzpT"
+        ));
+        assert!(!is_domain(
+            "z6MksFqXN3Yhqk8pTJdUGLwATkRfQvwZXPqR2qMEhbS9wzpT@seed.example.com:8776"
+        ));
+        assert!(!is_domain("seed example.com"));
+    }
+}
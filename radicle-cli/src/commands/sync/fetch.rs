@@ -0,0 +1,618 @@
+//! Fetching a repository from its seeds.
+use std::sync::mpsc;
+use std::{thread, time};
+
+use serde::Serialize;
+
+use radicle::node;
+use radicle::node::{FetchResult, FetchResults, Handle as _, Node};
+use radicle::prelude::{NodeId, RepoId};
+
+use crate::terminal as term;
+
+use super::health;
+use super::report::ReportSink;
+use super::seeds::{dial_order, Unreachable};
+use super::{log_event, remaining, spinner, RepoSync, SyncContext};
+
+/// How the seeds of a repository were narrowed down during a fetch.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SeedPipeline {
+    /// Seeds known in the routing table.
+    pub known: usize,
+    /// Known seeds that are our own node.
+    pub local: usize,
+    /// Seeds we are connected to.
+    pub connected: usize,
+    /// Disconnected seeds with at least one known address.
+    pub dialable: usize,
+}
+
+impl SeedPipeline {
+    pub(super) fn print(&self, succeeded: usize) {
+        term::info!("{} seed(s) known in the routing table", self.known);
+        term::info!("{} of which are the local node", self.local);
+        term::info!("{} connected", self.connected);
+        term::info!("{} disconnected with a known address", self.dialable);
+        term::info!("{} fetch(es) succeeded", succeeded);
+    }
+}
+
+/// The outcome of fetching a repository from its seeds, for display or machine output.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchSummary {
+    /// Seeds the repository was fetched from.
+    pub synced: Vec<NodeId>,
+    /// Seeds the repository couldn't be fetched from.
+    pub failed: Vec<FetchFailure>,
+    /// Seeds asked for that were skipped, since they weren't connected.
+    pub skipped: Vec<NodeId>,
+    /// Whether the repository was fetched from at least the target number of replicas.
+    pub replicas_met: bool,
+}
+
+/// A seed the repository couldn't be fetched from. See [`FetchSummary`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FetchFailure {
+    pub nid: NodeId,
+    pub reason: String,
+}
+
+impl FetchSummary {
+    /// Summarize the results of fetching from seeds, given the target number of replicas.
+    pub fn new(results: &FetchResults, skipped: Vec<NodeId>, replicas: usize) -> Self {
+        let synced = results
+            .success()
+            .map(|(nid, _, _)| *nid)
+            .collect::<Vec<_>>();
+        let failed = results
+            .failed()
+            .map(|(nid, reason)| FetchFailure {
+                nid: *nid,
+                reason: reason.to_owned(),
+            })
+            .collect();
+
+        Self {
+            replicas_met: synced.len() >= replicas,
+            synced,
+            failed,
+            skipped,
+        }
+    }
+
+    /// Whether no seed was tried, eg. because none were found.
+    pub fn is_empty(&self) -> bool {
+        self.synced.is_empty() && self.failed.is_empty()
+    }
+
+    /// Whether the repository was fetched from at least one seed.
+    pub fn is_success(&self) -> bool {
+        !self.synced.is_empty()
+    }
+}
+
+/// Fetch a repository from its seeds, until the target number of replicas is met.
+pub fn fetch(
+    rid: RepoId,
+    settings: RepoSync,
+    timeout: time::Duration,
+    node: &mut Node,
+) -> Result<FetchSummary, node::Error> {
+    let mut ctx = SyncContext {
+        rid,
+        settings,
+        timeout,
+        deadline: None,
+        unreachable: Unreachable::default(),
+        report: ReportSink::default(),
+        quiet: false,
+        node,
+    };
+    fetch_with_pipeline(&mut ctx, None).map(|(_, summary, _)| summary)
+}
+
+/// Like [`fetch`], but also returns the result of each fetch, and how the seeds were narrowed
+/// down.
+///
+/// If the context has a deadline, no connection or fetch is started once it has passed, and
+/// the results obtained until then are returned. Addresses that were unreachable before
+/// aren't dialed. If the recent fetch outcomes of seeds are given, they are updated and saved
+/// afterwards.
+pub(super) fn fetch_with_pipeline(
+    ctx: &mut SyncContext<'_>,
+    mut seed_health: Option<&mut health::SeedHealth>,
+) -> Result<(FetchResults, FetchSummary, SeedPipeline), node::Error> {
+    let started = time::Instant::now();
+    let replicas = replica_target(&ctx.settings, &ctx.node.seeds(ctx.rid)?, &ctx.node.nid()?);
+    let (results, skipped, pipeline) = fetch_seeds(ctx, seed_health.as_deref_mut())?;
+    let summary = FetchSummary::new(&results, skipped, replicas);
+
+    if let Some(seed_health) = seed_health {
+        for (nid, result) in results.iter() {
+            seed_health.record(*nid, matches!(result, FetchResult::Failed { .. }));
+        }
+        if let Err(e) = seed_health.save() {
+            log::warn!(target: "cli", "Failed to save the health of seeds: {e}");
+        }
+    }
+
+    if remaining(ctx.timeout, ctx.deadline).is_none() {
+        term::warning(format!(
+            "deadline reached, stopped after trying {} seed(s)",
+            results.len()
+        ));
+    }
+
+    log_event(
+        "fetch",
+        &[
+            ("rid", &ctx.rid),
+            ("known", &pipeline.known),
+            ("connected", &pipeline.connected),
+            ("dialable", &pipeline.dialable),
+            ("succeeded", &results.success().count()),
+            ("failed", &results.failed().count()),
+            ("elapsed_ms", &started.elapsed().as_millis()),
+        ],
+    );
+    Ok((results, summary, pipeline))
+}
+
+/// The target number of replicas, clamped by the maximum replicas possible, ie. the number
+/// of known seeds other than our own node and the excluded seeds.
+pub(super) fn replica_target(settings: &RepoSync, seeds: &node::Seeds, local: &NodeId) -> usize {
+    settings.replicas.min(
+        seeds
+            .iter()
+            .filter(|s| &s.nid != local && !settings.exclude.contains(&s.nid))
+            .count(),
+    )
+}
+
+/// Fetch from the seeds of a repository. Returns the result of each fetch, the seeds asked
+/// for that were skipped because they weren't connected, and how the seeds were narrowed down.
+fn fetch_seeds(
+    ctx: &mut SyncContext<'_>,
+    mut seed_health: Option<&mut health::SeedHealth>,
+) -> Result<(FetchResults, Vec<NodeId>, SeedPipeline), node::Error> {
+    // The timeout bounds the whole fetch, not each individual fetch.
+    let bound = time::Instant::now() + ctx.timeout;
+    let deadline = ctx.deadline.map_or(bound, |d| d.min(bound));
+    let local = ctx.node.nid()?;
+    // Get seeds. This consults the local routing table only.
+    let seeds = ctx.node.seeds(ctx.rid)?;
+    let replicas = replica_target(&ctx.settings, &seeds, &local);
+    let sessions = ctx.node.sessions()?;
+    let mut results = FetchResults::default();
+    let (mut connected, mut disconnected) = seeds.partition();
+    let exclude = &ctx.settings.exclude;
+
+    connected.retain(|s| !exclude.contains(&s.nid));
+    disconnected.retain(|s| !exclude.contains(&s.nid));
+
+    if let Some(seed_health) = &seed_health {
+        seed_health.deprioritize(&mut connected, |s| &s.nid);
+        // Disconnected seeds are tried from the end, so recently failed ones go first.
+        disconnected.sort_by_key(|s| !seed_health.is_failing(&s.nid));
+    }
+    let pipeline = SeedPipeline {
+        known: seeds.len(),
+        local: seeds.iter().filter(|s| s.nid == local).count(),
+        connected: connected.iter().filter(|s| s.nid != local).count(),
+        dialable: disconnected
+            .iter()
+            .filter(|s| s.nid != local && !s.addrs.is_empty())
+            .count(),
+    };
+
+    // Fetch from specified seeds, plus our preferred seeds.
+    let per_seed = ctx.settings.seed_timeout.unwrap_or(ctx.timeout);
+    let mut specified = Vec::new();
+    let mut skipped = Vec::new();
+    let asked = ctx
+        .settings
+        .seeds
+        .difference(&ctx.settings.exclude)
+        .copied()
+        .collect::<Vec<_>>();
+
+    for nid in asked {
+        if !sessions.iter().any(|s| s.nid == nid && s.is_connected()) {
+            // Dial the seed directly if we were given its address, since it may not
+            // be in our routing table.
+            let connected = match (
+                ctx.settings.addresses.get(&nid).cloned(),
+                remaining(per_seed, Some(deadline)),
+            ) {
+                (Some(addr), Some(timeout)) => ctx.connect(nid, std::iter::once(addr), timeout)?,
+                _ => false,
+            };
+            if !connected {
+                term::warning(format!("node {nid} is not connected.. skipping"));
+                skipped.push(nid);
+                continue;
+            }
+        }
+        specified.push(nid);
+    }
+    let target = specified.len();
+    fetch_concurrently(ctx, specified, target, deadline, &mut results)?;
+    if results.success().count() >= replicas {
+        return Ok((results, skipped, pipeline));
+    }
+
+    // Fetch from connected seeds, until the target is met.
+    let connected = connected
+        .into_iter()
+        .filter(|c| !results.contains(&c.nid))
+        .map(|c| c.nid)
+        .collect::<Vec<_>>();
+    let target = replicas - results.success().count();
+    fetch_concurrently(ctx, connected, target, deadline, &mut results)?;
+
+    // Try to connect to disconnected seeds and fetch from them.
+    while results.success().count() < replicas {
+        let Some(seed) = disconnected.pop() else {
+            break;
+        };
+        if seed.nid == local {
+            // Skip our own node.
+            continue;
+        }
+        let Some(timeout) = remaining(per_seed, Some(deadline)) else {
+            break;
+        };
+        if ctx.connect(seed.nid, dial_order(seed.addrs).into_iter(), timeout)? {
+            if remaining(timeout, Some(deadline)).is_none() {
+                break;
+            }
+            let result = fetch_from(ctx, &seed.nid, deadline)?;
+            results.push(seed.nid, result);
+        } else if let Some(seed_health) = seed_health.as_mut() {
+            seed_health.record(seed.nid, true);
+        }
+    }
+
+    Ok((results, skipped, pipeline))
+}
+
+/// Fetch from the given connected seeds concurrently, until `target` fetches succeeded or
+/// we run out of seeds.
+///
+/// Since no more fetches than needed to meet the target are in flight at any time, no fetch
+/// is left running once it is met. Results are reported and added to `results` in the order
+/// of the given seeds, regardless of the order in which the fetches complete.
+fn fetch_concurrently(
+    ctx: &SyncContext<'_>,
+    seeds: Vec<NodeId>,
+    target: usize,
+    deadline: time::Instant,
+    results: &mut FetchResults,
+) -> Result<(), node::Error> {
+    /// Sent by the threads fetching from each seed.
+    enum Message {
+        /// A fetch failed, and is being attempted again.
+        Retry { ix: usize, attempt: usize },
+        /// A fetch completed.
+        Done {
+            ix: usize,
+            result: Result<FetchResult, node::Error>,
+        },
+    }
+
+    if seeds.is_empty() || target == 0 {
+        return Ok(());
+    }
+    let (rid, retries) = (ctx.rid, ctx.settings.retries);
+    let (tx, rx) = mpsc::channel();
+    let mut completed = std::iter::repeat_with(|| None)
+        .take(seeds.len())
+        .collect::<Vec<Option<FetchResult>>>();
+    let mut attempts = vec![1; seeds.len()];
+    let mut pending = seeds.iter().enumerate();
+    let mut reported = 0;
+    let mut in_flight = 0;
+    let mut succeeded = 0;
+    // The first error, returned once the fetches in flight are done, so that none of them
+    // outlives this function.
+    let mut error: Option<node::Error> = None;
+    let mut spinner = spinner(
+        format!("Fetching {} from seeds..", term::format::tertiary(rid)),
+        ctx.quiet,
+    );
+
+    loop {
+        // Start as many fetches as needed to meet the target, should they all succeed.
+        while error.is_none() && in_flight + succeeded < target {
+            let Some((ix, nid)) = pending.next() else {
+                break;
+            };
+            if time::Instant::now() >= deadline {
+                break;
+            }
+            let (tx, mut node, nid, settings, report) = (
+                tx.clone(),
+                ctx.node.clone(),
+                *nid,
+                ctx.settings.clone(),
+                ctx.report.clone(),
+            );
+
+            let spawned = thread::Builder::new()
+                .name(format!("fetch-{nid}"))
+                .spawn(move || {
+                    let result = fetch_retrying(
+                        rid,
+                        &nid,
+                        deadline,
+                        &settings,
+                        &report,
+                        &mut node,
+                        |attempt| {
+                            tx.send(Message::Retry { ix, attempt }).ok();
+                        },
+                    );
+                    tx.send(Message::Done { ix, result }).ok();
+                });
+            if let Err(e) = spawned {
+                error = Some(e.into());
+                break;
+            }
+            in_flight += 1;
+        }
+        if in_flight == 0 {
+            break;
+        }
+        spinner.message(format!(
+            "Fetching {} from {in_flight} seed(s)..",
+            term::format::tertiary(rid)
+        ));
+
+        let (ix, result) = match rx.recv() {
+            Ok(Message::Retry { ix, attempt }) => {
+                attempts[ix] = attempt;
+                spinner.message(format!(
+                    "Fetching {} from {in_flight} seed(s).. (attempt {attempt}/{} from {})",
+                    term::format::tertiary(rid),
+                    retries + 1,
+                    term::format::tertiary(term::format::node(&seeds[ix])),
+                ));
+                continue;
+            }
+            Ok(Message::Done {
+                ix,
+                result: Ok(result),
+            }) => (ix, result),
+            Ok(Message::Done { result: Err(e), .. }) => {
+                in_flight -= 1;
+                error = error.or(Some(e));
+                continue;
+            }
+            Err(_) => break,
+        };
+
+        in_flight -= 1;
+        if result.is_success() {
+            succeeded += 1;
+        }
+        completed[ix] = Some(result);
+
+        // Report the results that are next in line.
+        while let Some(slot) = completed.get_mut(reported) {
+            let Some(result) = slot.take() else {
+                break;
+            };
+            let nid = &seeds[reported];
+            let msg = fetch_message(rid, nid, attempts[reported], retries);
+            match &result {
+                FetchResult::Success { .. } => spinner.step_done(msg),
+                FetchResult::Failed { reason, .. } => spinner.step_failed(format!(
+                    "{msg} {} {reason}",
+                    term::format::negative("error:")
+                )),
+            }
+            results.push(*nid, result);
+            reported += 1;
+        }
+    }
+    spinner.clear();
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Base delay between attempts to fetch from a seed. See [`RepoSync::retries`].
+pub const FETCH_RETRY_DELAY: time::Duration = time::Duration::from_millis(500);
+
+pub(super) fn print_protocol_versions(results: &FetchResults, sessions: &[node::Session]) {
+    for (nid, result) in results.iter() {
+        let version = sessions
+            .iter()
+            .find(|s| &s.nid == nid)
+            .and_then(|s| s.version)
+            .map(|v| format!("protocol version {v}"))
+            .unwrap_or_else(|| String::from("protocol version unknown"));
+        let status = match result {
+            FetchResult::Success { .. } => term::format::positive("fetched"),
+            FetchResult::Failed { .. } => term::format::negative("failed"),
+        };
+        term::info!(
+            "{} {} ({})",
+            term::format::tertiary(term::format::node(nid)),
+            status,
+            term::format::dim(version)
+        );
+    }
+}
+
+/// Fetch from a single connected seed, showing a spinner. See [`fetch_retrying`].
+fn fetch_from(
+    ctx: &mut SyncContext<'_>,
+    seed: &NodeId,
+    deadline: time::Instant,
+) -> Result<FetchResult, node::Error> {
+    let (rid, retries) = (ctx.rid, ctx.settings.retries);
+    let mut spinner = spinner(fetch_message(rid, seed, 1, retries), ctx.quiet);
+    let result = fetch_retrying(
+        rid,
+        seed,
+        deadline,
+        &ctx.settings,
+        &ctx.report,
+        ctx.node,
+        |attempt| spinner.message(fetch_message(rid, seed, attempt, retries)),
+    )?;
+
+    match &result {
+        FetchResult::Success { .. } => {
+            spinner.finish();
+        }
+        FetchResult::Failed { reason, .. } => {
+            spinner.error(reason);
+        }
+    }
+    Ok(result)
+}
+
+/// The message describing a fetch from a seed, including the attempt number if it was retried.
+fn fetch_message(rid: RepoId, seed: &NodeId, attempt: usize, retries: usize) -> String {
+    let msg = format!(
+        "Fetching {} from {}..",
+        term::format::tertiary(rid),
+        term::format::tertiary(term::format::node(seed))
+    );
+    if attempt > 1 {
+        format!("{msg} (attempt {attempt}/{})", retries + 1)
+    } else {
+        msg
+    }
+}
+
+/// Fetch from a single seed, retrying failures that look transient up to
+/// [`RepoSync::retries`] times. The delay between attempts starts at [`FETCH_RETRY_DELAY`]
+/// and doubles after each attempt. No attempt is made that can't start before the deadline.
+///
+/// `on_retry` is called with the number of each new attempt, starting at `2`.
+fn fetch_retrying(
+    rid: RepoId,
+    seed: &NodeId,
+    deadline: time::Instant,
+    settings: &RepoSync,
+    report: &ReportSink,
+    node: &mut Node,
+    mut on_retry: impl FnMut(usize),
+) -> Result<FetchResult, node::Error> {
+    let started = time::Instant::now();
+    let mut delay = FETCH_RETRY_DELAY;
+    let mut attempt = 1;
+
+    let result = loop {
+        let left = deadline.saturating_duration_since(time::Instant::now());
+        let timeout = settings.seed_timeout.map_or(left, |t| t.min(left));
+        let result = fetch_result(rid, seed, timeout, node)?;
+        let FetchResult::Failed { kind, .. } = &result else {
+            break result;
+        };
+        if attempt > settings.retries || !kind.is_transient() {
+            break result;
+        }
+        // Only retry if there's time left after waiting.
+        if deadline.saturating_duration_since(time::Instant::now()) <= delay {
+            break result;
+        }
+        thread::sleep(delay);
+
+        delay *= 2;
+        attempt += 1;
+        on_retry(attempt);
+    };
+    report.record(|r| r.fetched(seed, &result, attempt, started.elapsed()));
+
+    Ok(result)
+}
+
+/// Fetch from a single seed, without any output other than logging.
+fn fetch_result(
+    rid: RepoId,
+    seed: &NodeId,
+    timeout: time::Duration,
+    node: &mut Node,
+) -> Result<FetchResult, node::Error> {
+    let started = time::Instant::now();
+    let result = node.fetch(rid, *seed, timeout)?;
+
+    log_event(
+        "fetch_from",
+        &[
+            ("rid", &rid),
+            ("nid", seed),
+            (
+                "outcome",
+                &match &result {
+                    FetchResult::Success { updated, .. } => {
+                        format!("success updated={}", updated.len())
+                    }
+                    FetchResult::Failed { reason, kind } => {
+                        format!("failed reason={reason:?} kind={kind:?}")
+                    }
+                },
+            ),
+            ("elapsed_ms", &started.elapsed().as_millis()),
+        ],
+    );
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use radicle::node::FetchErrorKind;
+
+    #[test]
+    fn test_fetch_summary() {
+        let nid =
+            |n: u8| *radicle::crypto::test::signer::MockSigner::from_seed([n; 32]).public_key();
+        let (alice, bob, eve) = (nid(1), nid(2), nid(3));
+        let mut results = FetchResults::default();
+
+        results.push(
+            alice,
+            FetchResult::Success {
+                updated: vec![],
+                namespaces: Default::default(),
+            },
+        );
+        results.push(
+            bob,
+            FetchResult::Failed {
+                reason: String::from("timed out"),
+                kind: FetchErrorKind::Timeout,
+            },
+        );
+        let summary = FetchSummary::new(&results, vec![eve], 2);
+
+        assert!(summary.is_success());
+        assert!(!summary.is_empty());
+        assert!(!summary.replicas_met);
+        assert!(FetchSummary::new(&results, vec![], 1).replicas_met);
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            serde_json::json!({
+                "synced": [alice],
+                "failed": [{ "nid": bob, "reason": "timed out" }],
+                "skipped": [eve],
+                "replicasMet": false,
+            })
+        );
+
+        let summary = FetchSummary::new(&FetchResults::default(), vec![], 1);
+        assert!(summary.is_empty());
+        assert!(!summary.is_success());
+    }
+}
@@ -0,0 +1,159 @@
+//! Recent fetch outcomes of seeds, kept across invocations, so that seeds which just failed
+//! are tried last.
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::{fs, io, time};
+
+use localtime::LocalTime;
+use serde::{Deserialize, Serialize};
+
+use radicle::prelude::NodeId;
+use radicle::profile::Home;
+
+/// Name of the file the outcomes are kept in, under the CLI directory.
+pub const FILE_NAME: &str = "seed-health.json";
+/// How long an outcome is remembered, unless configured otherwise.
+pub const DEFAULT_EXPIRY: time::Duration = time::Duration::from_secs(60 * 60);
+
+/// Path of the file the outcomes are kept in.
+pub fn path(home: &Home) -> PathBuf {
+    home.cli().join(FILE_NAME)
+}
+
+/// The outcome of the last fetch from a seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outcome {
+    /// Whether we failed to connect to or fetch from the seed.
+    pub failed: bool,
+    /// When the fetch happened, in seconds since the epoch.
+    pub timestamp: u64,
+}
+
+/// The recent fetch outcomes of seeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedHealth {
+    /// File the outcomes are read from and written to.
+    path: PathBuf,
+    /// Outcomes older than this are forgotten, so that seeds get a fair retry.
+    expiry: time::Duration,
+    /// The current time, in seconds since the epoch.
+    now: u64,
+    outcomes: BTreeMap<NodeId, Outcome>,
+}
+
+impl SeedHealth {
+    /// Read the outcomes from the given file, forgetting the expired ones. If the file can't
+    /// be read, eg. because it doesn't exist yet, no outcomes are known.
+    pub fn open(path: impl AsRef<Path>, expiry: time::Duration) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let outcomes = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                log::warn!(
+                    target: "cli",
+                    "Ignoring invalid seed health file {}: {e}",
+                    path.display()
+                );
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        let mut health = Self {
+            path,
+            expiry,
+            now: LocalTime::now().as_secs(),
+            outcomes,
+        };
+        health.expire();
+        health
+    }
+
+    /// Forget the outcomes that are older than the expiry window.
+    fn expire(&mut self) {
+        let (now, expiry) = (self.now, self.expiry.as_secs());
+
+        self.outcomes
+            .retain(|_, o| now.saturating_sub(o.timestamp) < expiry);
+    }
+
+    /// Whether the last fetch from the given seed failed, within the expiry window.
+    pub fn is_failing(&self, nid: &NodeId) -> bool {
+        self.outcomes.get(nid).map_or(false, |o| o.failed)
+    }
+
+    /// Record the outcome of a fetch from the given seed.
+    pub fn record(&mut self, nid: NodeId, failed: bool) {
+        self.outcomes.insert(
+            nid,
+            Outcome {
+                failed,
+                timestamp: self.now,
+            },
+        );
+    }
+
+    /// Move the seeds that recently failed to the end, keeping the order of the others.
+    pub fn deprioritize<T>(&self, seeds: &mut [T], nid: impl Fn(&T) -> &NodeId) {
+        seeds.sort_by_key(|s| self.is_failing(nid(s)));
+    }
+
+    /// Write the outcomes back to the file, creating its directory if needed.
+    ///
+    /// The outcomes are written to a temporary file that then replaces the file, so that
+    /// concurrent invocations never read a partially written file.
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(&self.outcomes)?;
+        let dir = self
+            .path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(&json)?;
+        tmp.persist(&self.path).map_err(|e| e.error)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_seed_health() {
+        let tmp = tempfile::tempdir().unwrap();
+        // The directory is created when saving.
+        let path = tmp.path().join("cli").join(FILE_NAME);
+        let nid =
+            |n: u8| *radicle::crypto::test::signer::MockSigner::from_seed([n; 32]).public_key();
+        let (alice, bob, eve) = (nid(1), nid(2), nid(3));
+
+        // A missing file is the same as an empty one.
+        let mut health = SeedHealth::open(&path, DEFAULT_EXPIRY);
+        health.record(alice, true);
+        health.record(bob, false);
+        health.save().unwrap();
+
+        let health = SeedHealth::open(&path, DEFAULT_EXPIRY);
+        assert!(health.is_failing(&alice));
+        assert!(!health.is_failing(&bob));
+        assert!(!health.is_failing(&eve));
+
+        // Failing seeds are moved last, and the others keep their order.
+        let mut seeds = vec![alice, eve, bob];
+        health.deprioritize(&mut seeds, |s| s);
+        assert_eq!(seeds, vec![eve, bob, alice]);
+
+        // Outcomes are forgotten once they expire.
+        let mut health = SeedHealth {
+            now: health.now + DEFAULT_EXPIRY.as_secs(),
+            ..health
+        };
+        health.expire();
+        assert!(!health.is_failing(&alice));
+        assert!(health.outcomes.is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+//! The per-seed outcomes of syncing a repository, written with `--report`.
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time;
+
+use serde::Serialize;
+
+use radicle::node::{FetchErrorKind, FetchResult};
+use radicle::prelude::{NodeId, RepoId};
+
+/// Where the per-seed outcomes of syncing a repository are recorded, if `--report` was
+/// specified. Clones record into the same report, so that it can be shared with the threads
+/// fetching from each seed.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ReportSink(Option<Arc<Mutex<Report>>>);
+
+impl ReportSink {
+    /// Record into a new report for the given repository.
+    pub(super) fn new(rid: RepoId) -> Self {
+        Self(Some(Arc::new(Mutex::new(Report::new(rid)))))
+    }
+
+    /// Add to the report, if one is being written.
+    pub(super) fn record(&self, f: impl FnOnce(&mut Report)) {
+        if let Some(report) = &self.0 {
+            f(&mut report.lock().unwrap_or_else(PoisonError::into_inner));
+        }
+    }
+
+    /// Write the report to the given file, if one is being written.
+    pub(super) fn write(&self, path: &Path) -> io::Result<()> {
+        let Some(report) = &self.0 else {
+            return Ok(());
+        };
+        let json =
+            serde_json::to_string_pretty(&*report.lock().unwrap_or_else(PoisonError::into_inner))?;
+
+        std::fs::write(path, json + "\n")
+    }
+}
+
+/// The per-seed outcomes of syncing a repository, written with `--report`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct Report {
+    rid: RepoId,
+    /// When the sync started, written as the milliseconds elapsed since.
+    #[serde(rename = "elapsedMs", serialize_with = "serialize_elapsed_ms")]
+    started: time::Instant,
+    fetch: Vec<FetchReport>,
+    announce: Vec<AnnounceReport>,
+}
+
+/// The final result of fetching from a seed, in a [`Report`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FetchReport {
+    nid: NodeId,
+    #[serde(flatten)]
+    outcome: FetchOutcome,
+    attempts: usize,
+    elapsed_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "outcome")]
+enum FetchOutcome {
+    Success {
+        updated: usize,
+    },
+    Failed {
+        reason: String,
+        category: &'static str,
+    },
+}
+
+/// The result of announcing to a seed, in a [`Report`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnnounceReport {
+    nid: NodeId,
+    #[serde(flatten)]
+    outcome: AnnounceOutcome,
+    elapsed_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "outcome")]
+enum AnnounceOutcome {
+    Synced,
+    Failed { category: &'static str },
+}
+
+impl Report {
+    fn new(rid: RepoId) -> Self {
+        Self {
+            rid,
+            started: time::Instant::now(),
+            fetch: Vec::new(),
+            announce: Vec::new(),
+        }
+    }
+
+    /// Record the final result of fetching from a seed, after the given number of attempts.
+    pub(super) fn fetched(
+        &mut self,
+        seed: &NodeId,
+        result: &FetchResult,
+        attempts: usize,
+        elapsed: time::Duration,
+    ) {
+        let outcome = match result {
+            FetchResult::Success { updated, .. } => FetchOutcome::Success {
+                updated: updated.len(),
+            },
+            FetchResult::Failed { reason, kind } => FetchOutcome::Failed {
+                reason: reason.clone(),
+                category: failure_category(kind),
+            },
+        };
+        self.fetch.push(FetchReport {
+            nid: *seed,
+            outcome,
+            attempts,
+            elapsed_ms: elapsed.as_millis(),
+        });
+    }
+
+    /// Record the result of announcing to a seed: how long it took to sync, or the
+    /// timeout it didn't sync within.
+    pub(super) fn announced(
+        &mut self,
+        seed: &NodeId,
+        result: Result<time::Duration, time::Duration>,
+    ) {
+        let (outcome, elapsed) = match result {
+            Ok(elapsed) => (AnnounceOutcome::Synced, elapsed),
+            Err(timeout) => (
+                AnnounceOutcome::Failed {
+                    category: "timeout",
+                },
+                timeout,
+            ),
+        };
+        self.announce.push(AnnounceReport {
+            nid: *seed,
+            outcome,
+            elapsed_ms: elapsed.as_millis(),
+        });
+    }
+}
+
+fn serialize_elapsed_ms<S: serde::Serializer>(
+    started: &time::Instant,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(started.elapsed().as_millis())
+}
+
+/// The category of a fetch failure, as reported with `--report`.
+fn failure_category(kind: &FetchErrorKind) -> &'static str {
+    if kind.is_transient() {
+        "transient"
+    } else {
+        "permanent"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::str::FromStr;
+
+    #[test]
+    fn test_report() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("report.json");
+        let rid = RepoId::from_str("rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5").unwrap();
+        let nid =
+            |n: u8| *radicle::crypto::test::signer::MockSigner::from_seed([n; 32]).public_key();
+        let (alice, bob) = (nid(1), nid(2));
+        let report = ReportSink::new(rid);
+
+        report.record(|r| {
+            r.fetched(
+                &alice,
+                &FetchResult::Success {
+                    updated: vec![],
+                    namespaces: Default::default(),
+                },
+                1,
+                time::Duration::from_millis(120),
+            )
+        });
+        // Clones record into the same report, like the threads fetching from each seed.
+        report.clone().record(|r| {
+            r.fetched(
+                &bob,
+                &FetchResult::Failed {
+                    reason: String::from("cannot fetch rad:z3gqc as it is not seeded"),
+                    kind: FetchErrorKind::Policy,
+                },
+                2,
+                time::Duration::from_millis(300),
+            )
+        });
+        report.record(|r| r.announced(&alice, Ok(time::Duration::from_millis(40))));
+        report.record(|r| r.announced(&bob, Err(time::Duration::from_secs(9))));
+        report.write(&path).unwrap();
+
+        let mut json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(json["elapsedMs"].is_u64());
+        json.as_object_mut().unwrap().remove("elapsedMs");
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "rid": rid,
+                "fetch": [{
+                    "nid": alice,
+                    "outcome": "success",
+                    "updated": 0,
+                    "attempts": 1,
+                    "elapsedMs": 120,
+                }, {
+                    "nid": bob,
+                    "outcome": "failed",
+                    "reason": "cannot fetch rad:z3gqc as it is not seeded",
+                    "category": "permanent",
+                    "attempts": 2,
+                    "elapsedMs": 300,
+                }],
+                "announce": [{
+                    "nid": alice,
+                    "outcome": "synced",
+                    "elapsedMs": 40,
+                }, {
+                    "nid": bob,
+                    "outcome": "failed",
+                    "category": "timeout",
+                    "elapsedMs": 9000,
+                }],
+            })
+        );
+    }
+}
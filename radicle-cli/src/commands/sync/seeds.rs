@@ -0,0 +1,241 @@
+//! Connecting to the seeds of a repository.
+use std::cmp;
+use std::time;
+
+use radicle::node;
+use radicle::node::Node;
+use radicle::prelude::NodeId;
+
+use crate::terminal as term;
+
+use super::{log_event, spinner, SyncContext};
+
+/// Seed addresses that couldn't be connected to while syncing a repository.
+///
+/// These aren't dialed again, so that we don't wait for an unreachable seed more than once,
+/// eg. when fetching and then announcing.
+#[derive(Debug, Default)]
+pub(super) struct Unreachable(Vec<(NodeId, node::Address)>);
+
+impl Unreachable {
+    /// Dial a seed using `connect`, skipping the addresses that were unreachable before.
+    /// If none are left, the seed isn't dialed at all.
+    fn dial<E>(
+        &mut self,
+        nid: NodeId,
+        addrs: Vec<node::Address>,
+        connect: impl FnOnce(Vec<node::Address>) -> Result<bool, E>,
+    ) -> Result<bool, E> {
+        let (skipped, addrs): (Vec<_>, Vec<_>) = addrs
+            .into_iter()
+            .partition(|addr| self.0.iter().any(|(n, a)| *n == nid && a == addr));
+
+        if addrs.is_empty() {
+            if !skipped.is_empty() {
+                term::notice!(
+                    "Skipping {}, which couldn't be reached earlier..",
+                    term::format::node(&nid)
+                );
+            }
+            return Ok(false);
+        }
+        let connected = connect(addrs.clone())?;
+        if !connected {
+            self.0.extend(addrs.into_iter().map(|addr| (nid, addr)));
+        }
+        Ok(connected)
+    }
+}
+
+impl SyncContext<'_> {
+    /// Connect to a seed, trying its addresses that weren't unreachable before. Returns
+    /// whether we are connected.
+    pub(super) fn connect(
+        &mut self,
+        nid: NodeId,
+        addrs: impl Iterator<Item = node::Address>,
+        timeout: time::Duration,
+    ) -> Result<bool, node::Error> {
+        let (node, quiet) = (&mut *self.node, self.quiet);
+
+        self.unreachable.dial(nid, addrs.collect(), |addrs| {
+            dial(nid, addrs, timeout, quiet, node)
+        })
+    }
+}
+
+fn dial(
+    nid: NodeId,
+    addrs: Vec<node::Address>,
+    timeout: time::Duration,
+    quiet: bool,
+    node: &mut Node,
+) -> Result<bool, node::Error> {
+    let started = time::Instant::now();
+    let Some(first) = addrs.first() else {
+        return Ok(false);
+    };
+    let spinner = spinner(
+        format!(
+            "Connecting to {}@{}{}..",
+            term::format::tertiary(term::format::node(&nid)),
+            first,
+            match addrs.len() - 1 {
+                0 => String::new(),
+                n => format!(" (and {n} other address(es))"),
+            }
+        ),
+        quiet,
+    );
+    // Try all addresses, starting a new attempt if the previous one is taking too long.
+    let cr = node.connect_any(
+        nid,
+        addrs,
+        node::ConnectOptions {
+            persistent: false,
+            timeout,
+        },
+        node::CONNECT_RACE_DELAY,
+    )?;
+
+    log_event(
+        "connect",
+        &[
+            ("nid", &nid),
+            ("addrs", &addrs.len()),
+            (
+                "outcome",
+                &match &cr {
+                    node::ConnectResult::Connected => String::from("connected"),
+                    node::ConnectResult::Disconnected { reason } => {
+                        format!("disconnected reason={reason:?}")
+                    }
+                },
+            ),
+            ("elapsed_ms", &started.elapsed().as_millis()),
+        ],
+    );
+
+    match cr {
+        node::ConnectResult::Connected => {
+            spinner.finish();
+            Ok(true)
+        }
+        node::ConnectResult::Disconnected { .. } => {
+            spinner.failed();
+            Ok(false)
+        }
+    }
+}
+
+/// Order the known addresses of a seed for dialing.
+///
+/// Duplicate addresses are removed, keeping their most recent successful connection.
+/// Addresses we have previously connected to successfully come first, most recent first,
+/// followed by the rest, ordered by address family: IPv4, IPv6, DNS, then Tor.
+pub(super) fn dial_order(
+    addrs: impl IntoIterator<Item = node::KnownAddress>,
+) -> Vec<node::Address> {
+    let mut known: Vec<node::KnownAddress> = Vec::new();
+
+    for ka in addrs {
+        if let Some(k) = known.iter_mut().find(|k| k.addr == ka.addr) {
+            k.last_success = k.last_success.max(ka.last_success);
+        } else {
+            known.push(ka);
+        }
+    }
+    known.sort_by_key(|ka| {
+        (
+            cmp::Reverse(ka.last_success),
+            u8::from(node::address::AddressType::from(&ka.addr)),
+        )
+    });
+    known.into_iter().map(|ka| ka.addr).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use localtime::LocalTime;
+    use radicle::node::address::Source;
+
+    fn known(addr: &str, last_success: Option<u64>) -> node::KnownAddress {
+        node::KnownAddress {
+            last_success: last_success.map(LocalTime::from_secs),
+            ..node::KnownAddress::new(addr.parse().unwrap(), Source::Peer)
+        }
+    }
+
+    #[test]
+    fn test_unreachable_dialed_once() {
+        let nid = *radicle::crypto::test::signer::MockSigner::default().public_key();
+        let addr: node::Address = "seed.example.com:8776".parse().unwrap();
+        let mut unreachable = Unreachable::default();
+        let mut attempts = 0;
+
+        // The same dead seed, as found when syncing two repositories.
+        for _ in 0..2 {
+            let connected = unreachable
+                .dial(nid, vec![addr.clone()], |_| {
+                    attempts += 1;
+                    Ok::<_, node::Error>(false)
+                })
+                .unwrap();
+            assert!(!connected);
+        }
+        assert_eq!(attempts, 1);
+
+        // Other addresses of the seed are still tried.
+        let other: node::Address = "192.0.2.1:8776".parse().unwrap();
+        unreachable
+            .dial(nid, vec![addr, other.clone()], |addrs| {
+                assert_eq!(addrs, vec![other]);
+                attempts += 1;
+                Ok::<_, node::Error>(true)
+            })
+            .unwrap();
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn test_dial_order_dedup() {
+        let addrs = dial_order([
+            known("seed.radicle.xyz:8776", None),
+            known("1.1.1.1:8776", None),
+            known("seed.radicle.xyz:8776", None),
+            known("1.1.1.1:8776", None),
+        ]);
+
+        assert_eq!(
+            addrs,
+            vec![
+                "1.1.1.1:8776".parse().unwrap(),
+                "seed.radicle.xyz:8776".parse().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dial_order_reachability() {
+        let addrs = dial_order([
+            known("1.1.1.1:8776", None),
+            known("[::1]:8776", None),
+            known("seed.radicle.xyz:8776", Some(10)),
+            known("2.2.2.2:8776", Some(20)),
+            // A duplicate that was successful keeps its most recent success.
+            known("[::1]:8776", Some(5)),
+        ]);
+
+        assert_eq!(
+            addrs,
+            vec![
+                "2.2.2.2:8776".parse().unwrap(),
+                "seed.radicle.xyz:8776".parse().unwrap(),
+                "[::1]:8776".parse().unwrap(),
+                "1.1.1.1:8776".parse().unwrap(),
+            ]
+        );
+    }
+}
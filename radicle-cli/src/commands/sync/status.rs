@@ -0,0 +1,676 @@
+//! Displaying and checking the sync status of repositories.
+use std::cmp::Ordering;
+use std::{fmt, thread, time};
+
+use anyhow::Context as _;
+use serde::Serialize;
+
+use radicle::git;
+use radicle::node;
+use radicle::node::{AliasStore, Handle as _, Node, Seed, SyncStatus};
+use radicle::prelude::{NodeId, Profile, RepoId};
+use radicle::storage::{ReadRepository, ReadStorage};
+use radicle_term::Element;
+
+use crate::terminal as term;
+use crate::terminal::format::Author;
+use crate::terminal::{Table, TableOptions};
+
+use super::{Options, SortBy, SyncFilter};
+
+/// Redraw the sync status table until all seeds are in sync.
+pub(super) fn sync_watch(
+    rid: RepoId,
+    interval: time::Duration,
+    node: &mut Node,
+    profile: &Profile,
+    options: &Options,
+) -> anyhow::Result<()> {
+    // Number of lines drawn by the previous refresh, that are erased before redrawing.
+    let mut drawn = 0;
+
+    loop {
+        term::erase_lines(drawn);
+
+        let (synced, lines) = sync_status(rid, node, profile, options)?;
+        if synced {
+            term::success!("Repository {rid} is fully synced");
+            return Ok(());
+        }
+        term::print(term::format::dim(format!(
+            "Refreshing every {}s, press Ctrl-C to stop..",
+            interval.as_secs()
+        )));
+        drawn = lines + 1;
+        thread::sleep(interval);
+    }
+}
+
+/// Display the sync status of a repository.
+/// Returns whether all seeds other than our own node are in sync, whether or not they are
+/// shown, along with the number of lines printed.
+pub(super) fn sync_status(
+    rid: RepoId,
+    node: &mut Node,
+    profile: &Profile,
+    options: &Options,
+) -> anyhow::Result<(bool, usize)> {
+    let seeds = node.seeds(rid)?;
+    let local = node.nid()?;
+
+    print_sync_status(rid, seeds.into(), local, profile, options)
+}
+
+/// Display the sync status of a repository, given its seeds. See [`sync_status`].
+fn print_sync_status(
+    rid: RepoId,
+    mut seeds: Vec<Seed>,
+    local: NodeId,
+    profile: &Profile,
+    options: &Options,
+) -> anyhow::Result<(bool, usize)> {
+    let mut table = Table::<7, term::Label>::new(TableOptions::bordered());
+    let aliases = profile.aliases();
+    let remotes = seeds.iter().filter(|s| s.nid != local).collect::<Vec<_>>();
+    let synced = !remotes.is_empty() && remotes.iter().all(|s| s.is_synced());
+    // Seeds with an unknown status are shown when verbose, or when explicitly asked for.
+    let unknown = options.verbose || options.filter == Some(SyncFilter::Unknown);
+
+    if let Some(filter) = options.filter {
+        // Always keep our own node, for reference.
+        seeds.retain(|s| s.nid == local || filter.matches(s));
+    }
+    sort_seeds_by(local, &mut seeds, &aliases, &options.sort_by);
+
+    if let Some(limit) = options.limit {
+        seeds.truncate(limit);
+    }
+    if options.json {
+        let rows = seeds
+            .iter()
+            .filter_map(|seed| {
+                let (status, tip) = seed_status(seed, unknown)?;
+
+                Some(SeedRow {
+                    nid: seed.nid,
+                    alias: aliases.alias(&seed.nid),
+                    address: seed.addrs.first().map(|a| a.addr.to_string()),
+                    status,
+                    tip: tip.map(|at| at.oid),
+                    timestamp: tip.map(|at| at.timestamp.as_secs()),
+                    local: seed.nid == local,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string(&rows)?);
+
+        return Ok((synced, 1));
+    }
+    if options.format != term::table::Format::Table {
+        // Unlike the table, the values aren't abbreviated or styled.
+        let mut table = Table::<6, String>::new(TableOptions::default());
+        table.header(["nid", "alias", "address", "status", "tip", "timestamp"].map(String::from));
+
+        for seed in &seeds {
+            let Some((status, tip)) = seed_status(seed, unknown) else {
+                continue;
+            };
+            table.push([
+                seed.nid.to_string(),
+                aliases
+                    .alias(&seed.nid)
+                    .map(|a| a.to_string())
+                    .unwrap_or_default(),
+                seed.addrs
+                    .first()
+                    .map(|a| a.addr.to_string())
+                    .unwrap_or_default(),
+                status.to_owned(),
+                tip.map(|at| at.oid.to_string()).unwrap_or_default(),
+                tip.map(|at| at.timestamp.as_secs().to_string())
+                    .unwrap_or_default(),
+            ]);
+        }
+        let out = table.render_as(options.format);
+        print!("{out}");
+
+        return Ok((synced, out.lines().count()));
+    }
+    // Only needed to show how far seeds are out of sync.
+    let repo = profile.storage.repository(rid).ok();
+
+    table.push([
+        term::format::dim(String::from("●")).into(),
+        term::format::bold(String::from("Node")).into(),
+        term::Label::blank(),
+        term::format::bold(String::from("Address")).into(),
+        term::format::bold(String::from("Status")).into(),
+        term::format::bold(String::from("Tip")).into(),
+        term::format::bold(String::from("Timestamp")).into(),
+    ]);
+    table.divider();
+
+    for seed in seeds {
+        let (icon, status, head, time) = match seed.sync {
+            Some(SyncStatus::Synced { at }) => (
+                term::format::positive("●"),
+                term::format::positive(if seed.nid != local {
+                    String::from("synced")
+                } else {
+                    String::new()
+                }),
+                term::format::oid(at.oid),
+                term::format::timestamp(at.timestamp),
+            ),
+            Some(SyncStatus::OutOfSync {
+                local: ours,
+                remote,
+            }) => (
+                term::format::negative("●"),
+                term::format::negative(match &repo {
+                    _ if seed.nid == local => String::new(),
+                    Some(repo) => {
+                        let mut details = Vec::new();
+                        if options.verbose {
+                            details
+                                .push(OutOfSyncReason::new(repo, ours.oid, remote.oid).to_string());
+                        }
+                        if let Some((behind, ahead)) = distance(repo, ours.oid, remote.oid) {
+                            details.push(format!("-{behind} / +{ahead}"));
+                        }
+                        if details.is_empty() {
+                            String::from("out-of-sync")
+                        } else {
+                            format!("out-of-sync ({})", details.join(", "))
+                        }
+                    }
+                    None => String::from("out-of-sync"),
+                }),
+                term::format::oid(remote.oid),
+                term::format::timestamp(remote.timestamp),
+            ),
+            None if unknown => (
+                term::format::dim("●"),
+                term::format::dim(String::from("unknown")),
+                term::paint(String::new()),
+                term::paint(String::new()),
+            ),
+            None => continue,
+        };
+        let addr = seed
+            .addrs
+            .first()
+            .map(|a| a.addr.to_string())
+            .unwrap_or_default()
+            .into();
+        let (alias, nid) = Author::with_aliases(&seed.nid, &aliases, profile).labels();
+
+        table.push([
+            icon.into(),
+            alias,
+            nid,
+            addr,
+            status.into(),
+            term::format::secondary(head).into(),
+            time.dim().italic().into(),
+        ]);
+    }
+    table.print_as(options.format);
+
+    Ok((
+        synced,
+        table.rows(term::Constraint::from_env().unwrap_or_default()),
+    ))
+}
+
+/// A seed, as printed by `rad sync status --json`.
+#[derive(Debug, Serialize)]
+struct SeedRow {
+    nid: NodeId,
+    alias: Option<node::Alias>,
+    address: Option<String>,
+    status: &'static str,
+    tip: Option<git::Oid>,
+    timestamp: Option<u64>,
+    /// Whether this is our own node.
+    local: bool,
+}
+
+/// A seeded repository, as printed by `rad sync status --all --json`.
+#[derive(Debug, Serialize)]
+struct RepoRow {
+    rid: RepoId,
+    name: Option<String>,
+    /// Number of seeds in sync, not counting our own node.
+    synced: usize,
+    /// Number of seeds, not counting our own node.
+    total: usize,
+    status: &'static str,
+    tip: Option<git::Oid>,
+    timestamp: Option<u64>,
+}
+
+/// The sync status of a seed and the tip it has, as shown in machine-readable output.
+/// Returns `None` for seeds with an unknown status, unless they should be shown.
+fn seed_status(seed: &Seed, unknown: bool) -> Option<(&'static str, Option<&node::SyncedAt>)> {
+    match &seed.sync {
+        Some(SyncStatus::Synced { at }) => Some(("synced", Some(at))),
+        Some(SyncStatus::OutOfSync { remote, .. }) => Some(("out-of-sync", Some(remote))),
+        None if unknown => Some(("unknown", None)),
+        None => None,
+    }
+}
+
+/// Why a seed is out of sync with our own refs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutOfSyncReason {
+    /// The seed has an older version of our refs.
+    Behind,
+    /// The seed has a newer version of our refs than we do.
+    Ahead,
+    /// Neither version of our refs contains the other.
+    Diverged,
+    /// The seed's version of our refs isn't in our repository.
+    Unknown,
+}
+
+impl OutOfSyncReason {
+    /// Compare our `rad/sigrefs` head with the seed's.
+    fn new(repo: &impl ReadRepository, local: git::Oid, remote: git::Oid) -> Self {
+        match (
+            repo.is_ancestor_of(remote, local),
+            repo.is_ancestor_of(local, remote),
+        ) {
+            (Ok(true), _) => Self::Behind,
+            (_, Ok(true)) => Self::Ahead,
+            (Ok(false), Ok(false)) => Self::Diverged,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for OutOfSyncReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Behind => write!(f, "behind"),
+            Self::Ahead => write!(f, "ahead"),
+            Self::Diverged => write!(f, "diverged"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Count the commits of our `rad/sigrefs` head that the seed's is missing, and the commits of
+/// the seed's head that ours is missing. Returns `None` if the seed's head isn't in our
+/// repository.
+fn distance(
+    repo: &radicle::storage::git::Repository,
+    local: git::Oid,
+    remote: git::Oid,
+) -> Option<(usize, usize)> {
+    repo.backend
+        .graph_ahead_behind(local.into(), remote.into())
+        .ok()
+}
+
+/// Check that the repository is in sync with at least the given number of seeds.
+pub(super) fn sync_check(
+    rid: RepoId,
+    replicas: usize,
+    quiet: bool,
+    node: &mut Node,
+) -> anyhow::Result<()> {
+    let local = node.nid()?;
+    let synced = node
+        .seeds(rid)?
+        .iter()
+        .filter(|s| s.nid != local && matches!(s.sync, Some(SyncStatus::Synced { .. })))
+        .count();
+
+    if synced < replicas {
+        anyhow::bail!("repository {rid} is in sync with {synced} of {replicas} replica(s)");
+    }
+    if !quiet {
+        term::success!("Repository {rid} is in sync with {synced} of {replicas} replica(s)");
+    }
+
+    Ok(())
+}
+
+/// Get the name of a repository for display in `status --all`, failing if the repository
+/// isn't local or its identity can't be loaded. Repositories that aren't projects have no name.
+fn repo_name(storage: &impl ReadStorage, rid: RepoId) -> anyhow::Result<Option<String>> {
+    if !storage.contains(&rid)? {
+        anyhow::bail!("repository is not local");
+    }
+    let doc = storage
+        .repository(rid)?
+        .identity_doc()
+        .context("failed to load repository identity")?;
+
+    Ok(doc.project().ok().map(|p| p.name().to_owned()))
+}
+
+pub(super) fn sync_status_all(
+    node: &mut Node,
+    profile: &Profile,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let mut table = Table::<6, term::Label>::new(TableOptions::bordered());
+    let local = node.nid()?;
+    let policies = profile.policies()?;
+    let mut repos = Vec::new();
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+
+    for policy in policies.seed_policies()? {
+        if policy.policy != node::policy::Policy::Allow {
+            continue;
+        }
+        let rid = policy.rid;
+        // A single broken repository shouldn't prevent displaying the others.
+        let name = match repo_name(&profile.storage, rid) {
+            Ok(name) => name,
+            Err(e) => {
+                if options.json {
+                    log::warn!(target: "cli", "Skipping {rid}: {e}");
+                } else {
+                    term::warning(format!("skipping {rid}: {e}"));
+                }
+                skipped += 1;
+                continue;
+            }
+        };
+        repos.push((rid, name));
+    }
+
+    // Operators may seed hundreds of repositories, so the node is queried for all of them
+    // without waiting for each response in turn.
+    let cmds = repos
+        .iter()
+        .map(|(rid, _)| node::Command::Seeds {
+            rid: *rid,
+            page_size: None,
+        })
+        .collect::<Vec<_>>();
+    let responses = node.pipeline::<node::Seeds, _>(cmds, node::PIPELINE_DEPTH, node.timeout());
+
+    for ((rid, name), seeds) in repos.into_iter().zip(responses) {
+        let seeds = seeds?;
+        let remotes = seeds.iter().filter(|s| s.nid != local).collect::<Vec<_>>();
+        let total = remotes.len();
+        let synced = remotes.iter().filter(|s| s.is_synced()).count();
+        // The worst status amongst remote seeds: being out of sync is worse than being unknown.
+        let status = if remotes
+            .iter()
+            .any(|s| matches!(s.sync, Some(SyncStatus::OutOfSync { .. })))
+        {
+            "out-of-sync"
+        } else if remotes.iter().any(|s| s.sync.is_none()) {
+            "unknown"
+        } else if total > 0 {
+            "synced"
+        } else {
+            "no seeds"
+        };
+        // The most recent tip known, including our own.
+        let tip = seeds
+            .iter()
+            .filter_map(|s| match &s.sync {
+                Some(SyncStatus::Synced { at }) => Some(*at),
+                Some(SyncStatus::OutOfSync { remote, .. }) => Some(*remote),
+                None => None,
+            })
+            .max();
+
+        rows.push((rid, name, synced, total, status, tip, seeds));
+    }
+
+    if options.json {
+        let rows = rows
+            .into_iter()
+            .map(|(rid, name, synced, total, status, tip, _)| RepoRow {
+                rid,
+                name,
+                synced,
+                total,
+                status,
+                tip: tip.map(|at| at.oid),
+                timestamp: tip.map(|at| at.timestamp.as_secs()),
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string(&rows)?);
+
+        return Ok(());
+    }
+    if skipped > 0 {
+        term::warning(format!(
+            "{skipped} seeded repository(ies) skipped, see above for details"
+        ));
+    }
+    if options.verbose {
+        // Show the seed table of every repository, instead of a summary.
+        for (i, (rid, name, .., seeds)) in rows.into_iter().enumerate() {
+            if i > 0 {
+                term::blank();
+            }
+            term::info!(
+                "{} {}",
+                term::format::tertiary(rid),
+                term::format::bold(name.unwrap_or_default())
+            );
+            print_sync_status(rid, seeds.into(), local, profile, options)?;
+        }
+        return Ok(());
+    }
+
+    table.push([
+        term::format::bold(String::from("Repository")).into(),
+        term::format::bold(String::from("Name")).into(),
+        term::format::bold(String::from("Replicas")).into(),
+        term::format::bold(String::from("Status")).into(),
+        term::format::bold(String::from("Tip")).into(),
+        term::format::bold(String::from("Timestamp")).into(),
+    ]);
+    table.divider();
+
+    for (rid, name, synced, total, status, tip, _) in rows {
+        let status = match status {
+            "synced" => term::format::positive(status),
+            "out-of-sync" => term::format::negative(status),
+            _ => term::format::dim(status),
+        };
+        let (head, time) = match tip {
+            Some(at) => (
+                term::format::oid(at.oid),
+                term::format::timestamp(at.timestamp),
+            ),
+            None => (term::paint(String::new()), term::paint(String::new())),
+        };
+
+        table.push([
+            term::format::tertiary(rid).into(),
+            term::format::bold(name.unwrap_or_default()).into(),
+            format!("{synced}/{total}").into(),
+            status.into(),
+            term::format::secondary(head).into(),
+            time.dim().italic().into(),
+        ]);
+    }
+    table.print_as(options.format);
+
+    Ok(())
+}
+
+/// Compare seeds by the refs they have of ours.
+/// Seeds with an unknown sync status are always last.
+fn compare_tips(
+    a: &Seed,
+    b: &Seed,
+    f: impl Fn(&node::SyncedAt, &node::SyncedAt) -> Ordering,
+) -> Ordering {
+    let tip = |seed: &Seed| match &seed.sync {
+        Some(SyncStatus::Synced { at }) => Some(*at),
+        Some(SyncStatus::OutOfSync { remote, .. }) => Some(*remote),
+        None => None,
+    };
+    match (tip(a), tip(b)) {
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(a), Some(b)) => f(&a, &b),
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn sort_seeds_by(local: NodeId, seeds: &mut [Seed], aliases: &impl AliasStore, sort_by: &SortBy) {
+    let compare = |a: &Seed, b: &Seed| match sort_by {
+        SortBy::Nid => a.nid.cmp(&b.nid),
+        SortBy::Alias => {
+            let a = aliases.alias(&a.nid);
+            let b = aliases.alias(&b.nid);
+            a.cmp(&b)
+        }
+        SortBy::Status => match (&a.sync, &b.sync) {
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b).reverse(),
+            (None, None) => Ordering::Equal,
+        },
+        SortBy::Tip => compare_tips(a, b, |a, b| a.oid.cmp(&b.oid)),
+        // Least recently synced first, ie. the seeds furthest behind.
+        SortBy::Timestamp => compare_tips(a, b, |a, b| a.timestamp.cmp(&b.timestamp)),
+    };
+
+    // Always show our local node first.
+    seeds.sort_by(|a, b| {
+        if a.nid == local {
+            Ordering::Less
+        } else if b.nid == local {
+            Ordering::Greater
+        } else {
+            compare(a, b)
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use localtime::LocalTime;
+    use radicle::storage::WriteRepository as _;
+
+    #[test]
+    fn test_sort_seeds_by_timestamp() {
+        let nid =
+            |n: u8| *radicle::crypto::test::signer::MockSigner::from_seed([n; 32]).public_key();
+        let at = |secs: u64| node::SyncedAt {
+            oid: git::raw::Oid::zero().into(),
+            timestamp: LocalTime::from_secs(secs),
+        };
+        let (local, a, b, c) = (nid(1), nid(2), nid(3), nid(4));
+        let mut seeds = vec![
+            Seed::new(c, vec![], None, None),
+            Seed::new(b, vec![], None, Some(SyncStatus::Synced { at: at(20) })),
+            Seed::new(
+                a,
+                vec![],
+                None,
+                Some(SyncStatus::OutOfSync {
+                    local: at(30),
+                    remote: at(10),
+                }),
+            ),
+            Seed::new(local, vec![], None, Some(SyncStatus::Synced { at: at(30) })),
+        ];
+        sort_seeds_by(
+            local,
+            &mut seeds,
+            &HashMap::<NodeId, node::Alias>::new(),
+            &SortBy::Timestamp,
+        );
+
+        assert_eq!(
+            seeds.iter().map(|s| s.nid).collect::<Vec<_>>(),
+            vec![local, a, b, c]
+        );
+    }
+
+    #[test]
+    fn test_repo_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = radicle::crypto::test::signer::MockSigner::default();
+        let storage = radicle::test::fixtures::storage(tmp.path(), &signer).unwrap();
+        let mut rids = storage
+            .repositories()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.rid)
+            .collect::<Vec<_>>();
+        let valid = rids.len();
+
+        // A repository that was seeded but never fetched.
+        let missing = radicle::test::arbitrary::gen::<RepoId>(1);
+        // A repository without an identity.
+        let broken = radicle::test::arbitrary::gen::<RepoId>(2);
+        radicle::git::raw::Repository::init_bare(storage.path_of(&broken)).unwrap();
+        rids.extend([missing, broken]);
+
+        let (names, skipped): (Vec<_>, Vec<_>) = rids
+            .into_iter()
+            .map(|rid| repo_name(&storage, rid))
+            .partition(|r| r.is_ok());
+
+        assert_eq!(names.len(), valid);
+        assert!(names.into_iter().all(|n| n.unwrap().is_some()));
+        assert_eq!(skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_out_of_sync_reason() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = radicle::crypto::test::signer::MockSigner::default();
+        let storage = radicle::test::fixtures::storage(tmp.path(), &signer).unwrap();
+        let rid = storage.repositories().unwrap()[0].rid;
+        let repo = storage.repository(rid).unwrap();
+        let raw = repo.raw();
+        let commit = |msg: &str, parent: git::Oid| -> git::Oid {
+            let parent = raw.find_commit(parent.into()).unwrap();
+            let sig = parent.author();
+
+            raw.commit(None, &sig, &sig, msg, &parent.tree().unwrap(), &[&parent])
+                .unwrap()
+                .into()
+        };
+        let base = radicle::storage::refs::RefsAt::new(&repo, *signer.public_key())
+            .unwrap()
+            .at;
+        let left = commit("Left", base);
+        let right = commit("Right", base);
+        let missing = git::Oid::from(radicle::git::raw::Oid::zero());
+
+        assert_eq!(
+            OutOfSyncReason::new(&repo, left, base),
+            OutOfSyncReason::Behind
+        );
+        assert_eq!(
+            OutOfSyncReason::new(&repo, base, left),
+            OutOfSyncReason::Ahead
+        );
+        assert_eq!(
+            OutOfSyncReason::new(&repo, left, right),
+            OutOfSyncReason::Diverged
+        );
+        assert_eq!(
+            OutOfSyncReason::new(&repo, base, missing),
+            OutOfSyncReason::Unknown
+        );
+
+        assert_eq!(distance(&repo, left, base), Some((1, 0)));
+        assert_eq!(distance(&repo, base, left), Some((0, 1)));
+        assert_eq!(distance(&repo, left, right), Some((1, 1)));
+        assert_eq!(distance(&repo, base, missing), None);
+    }
+}
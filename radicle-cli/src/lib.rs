@@ -1,6 +1,5 @@
 #![allow(clippy::collapsible_if)]
 #![allow(clippy::or_fun_call)]
-#![allow(clippy::too_many_arguments)]
 pub mod commands;
 pub mod git;
 pub mod node;
@@ -52,6 +52,10 @@ fn parse_args() -> anyhow::Result<Command> {
             Long("version") => {
                 command = Some(Command::Version);
             }
+            Long("color") => {
+                let mode: term::ColorMode = parser.value()?.parse()?;
+                term::Paint::set_color(mode);
+            }
             Value(val) if command.is_none() => {
                 if val == *"." {
                     command = Some(Command::Other(vec![OsString::from("inspect")]));
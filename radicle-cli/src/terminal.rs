@@ -94,6 +94,7 @@ where
                     process::exit(1);
                 }
                 Some(Error::WithHint { hint, .. }) => Some(hint),
+                Some(Error::Exit { code }) => process::exit(*code),
                 None => None,
             };
             io::error(format!("rad {}: {err}", help.name));
@@ -108,6 +109,9 @@ where
     match cmd.run(options, DefaultContext) {
         Ok(()) => process::exit(0),
         Err(err) => {
+            if let Some(Error::Exit { code }) = err.downcast_ref::<Error>() {
+                process::exit(*code);
+            }
             terminal::fail(help.name, &err);
             process::exit(1);
         }
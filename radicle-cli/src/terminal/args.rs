@@ -30,6 +30,10 @@ pub enum Error {
         err: anyhow::Error,
         hint: &'static str,
     },
+    /// If this error is returned from a command, the process exits with the given code.
+    /// The command is expected to have reported the failure already.
+    #[error("exited with code {code}")]
+    Exit { code: i32 },
 }
 
 pub struct Help {
@@ -139,6 +143,22 @@ pub fn addr(val: &OsString) -> anyhow::Result<Address> {
     Address::from_str(&val).map_err(|_| anyhow!("invalid address '{}'", val))
 }
 
+/// Parse a seed, given either as `<nid>` or as `<nid>@<addr>`.
+pub fn seed(val: &OsString) -> anyhow::Result<(NodeId, Option<Address>)> {
+    let val = val.to_string_lossy();
+    let (id, addr) = match val.split_once('@') {
+        Some((id, addr)) => (id, Some(addr)),
+        None => (val.as_ref(), None),
+    };
+    let id = NodeId::from_str(id).map_err(|_| anyhow!("invalid Node ID '{}'", id))?;
+    let Some(addr) = addr else {
+        return Ok((id, None));
+    };
+    let addr = Address::from_str(addr).map_err(|_| anyhow!("invalid address '{}'", addr))?;
+
+    Ok((id, Some(addr)))
+}
+
 pub fn number(val: &OsString) -> anyhow::Result<usize> {
     let val = val.to_string_lossy();
     usize::from_str(&val).map_err(|_| anyhow!("invalid number '{}'", val))
@@ -165,6 +165,19 @@ impl<'a> Author<'a> {
         }
     }
 
+    /// Like [`Author::new`], but looks up the alias in an already opened alias store.
+    pub fn with_aliases(
+        nid: &'a NodeId,
+        aliases: &impl AliasStore,
+        profile: &Profile,
+    ) -> Author<'a> {
+        Self {
+            nid,
+            alias: aliases.alias(nid),
+            you: nid == profile.id(),
+        }
+    }
+
     pub fn alias(&self) -> Option<term::Label> {
         self.alias.as_ref().map(|a| a.to_string().into())
     }
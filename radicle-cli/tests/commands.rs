@@ -1590,6 +1590,146 @@ fn rad_sync() {
     .unwrap();
 }
 
+#[test]
+fn rad_sync_fetch_unreachable_seeds() {
+    let mut environment = Environment::new();
+    let mut eve = environment.node(Config::test(Alias::new("eve")));
+    let rid = RepoId::from_urn("rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5").unwrap();
+    let now = localtime::LocalTime::now().as_secs();
+    let timeout = time::Duration::from_secs(3);
+    // Listeners that accept connections, but never complete a handshake.
+    let listeners = (0..5)
+        .map(|_| net::TcpListener::bind((net::Ipv4Addr::LOCALHOST, 0)).unwrap())
+        .collect::<Vec<_>>();
+
+    for (i, listener) in listeners.iter().enumerate() {
+        let nid =
+            *radicle::crypto::test::signer::MockSigner::from_seed([i as u8 + 1; 32]).public_key();
+
+        eve.db
+            .addresses_mut()
+            .insert(
+                &nid,
+                node::Features::SEED,
+                Alias::new(format!("seed{i}")),
+                0,
+                now,
+                [node::KnownAddress::new(
+                    node::Address::from(listener.local_addr().unwrap()),
+                    node::address::Source::Imported,
+                )],
+            )
+            .unwrap();
+        eve.db.routing_mut().insert([&rid], nid, now).unwrap();
+    }
+    eve.policies.seed(&rid, Scope::All).unwrap();
+    eve.config.peers = node::config::PeerConfig::Static;
+
+    let eve = eve.spawn();
+    let mut node = radicle::Node::new(eve.home.socket());
+    let started = time::Instant::now();
+    let summary = radicle_cli::commands::rad_sync::fetch(
+        rid,
+        radicle_cli::commands::rad_sync::RepoSync::default(),
+        timeout,
+        &mut node,
+    )
+    .unwrap();
+
+    // The timeout bounds the whole fetch, rather than each connection attempt.
+    assert!(!summary.is_success());
+    assert!(!summary.replicas_met);
+    assert!(
+        started.elapsed() < timeout * 2,
+        "fetch took {:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn rad_sync_announce_seed_addr() {
+    let mut environment = Environment::new();
+    let working = environment.tmp().join("working");
+    let alice = environment.node(config::node("alice"));
+    let bob = environment.node(config::node("bob"));
+    let acme = RepoId::from_str("z42hL2jL4XNk6K8oHQaSWfMgCL7ji").unwrap();
+
+    fixtures::repository(working.join("acme"));
+
+    test(
+        "examples/rad-init.md",
+        working.join("acme"),
+        Some(&alice.home),
+        [],
+    )
+    .unwrap();
+
+    let alice = alice.spawn();
+    let mut bob = bob.spawn();
+
+    bob.handle.seed(acme, Scope::All).unwrap();
+
+    // Alice isn't connected to Bob, and doesn't know that Bob seeds the repository.
+    let seed = format!("{}@{}", bob.id, bob.addr);
+
+    test(
+        "examples/rad-sync-announce-seed-addr.md",
+        working.join("acme"),
+        Some(&alice.home),
+        [("SEED", seed.as_str())],
+    )
+    .unwrap();
+}
+
+#[test]
+fn rad_sync_report() {
+    let mut environment = Environment::new();
+    let working = environment.tmp().join("working");
+    let report = environment.tmp().join("report.json");
+    let alice = environment.node(config::node("alice"));
+    let bob = environment.node(config::node("bob"));
+    let acme = RepoId::from_str("z42hL2jL4XNk6K8oHQaSWfMgCL7ji").unwrap();
+
+    fixtures::repository(working.join("acme"));
+
+    test(
+        "examples/rad-init.md",
+        working.join("acme"),
+        Some(&alice.home),
+        [],
+    )
+    .unwrap();
+
+    let alice = alice.spawn();
+    let mut bob = bob.spawn();
+
+    bob.handle.seed(acme, Scope::All).unwrap();
+    bob.connect(&alice);
+    bob.routes_to(&[(acme, alice.id)]);
+    bob.rad(
+        "sync",
+        &[
+            &acme.to_string(),
+            "--fetch",
+            "--report",
+            report.to_str().unwrap(),
+        ],
+        working.join("bob"),
+    )
+    .unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+
+    assert_eq!(json["rid"], serde_json::json!(acme));
+    assert!(json["elapsedMs"].is_u64());
+    assert_eq!(json["fetch"].as_array().unwrap().len(), 1);
+    assert_eq!(json["fetch"][0]["nid"], serde_json::json!(alice.id));
+    assert_eq!(json["fetch"][0]["outcome"], "success");
+    assert_eq!(json["fetch"][0]["attempts"], 1);
+    assert_eq!(json["announce"], serde_json::json!([]));
+}
+
 #[test]
 //
 //     alice -- seed -- bob
@@ -11,7 +11,6 @@ use nonempty::NonEmpty;
 use once_cell::sync::Lazy;
 use radicle_git_ext::commit::trailers::OwnedTrailer;
 
-use crate::change::store::Version;
 use crate::signatures;
 use crate::trailers::CommitTrailer;
 use crate::{
@@ -111,8 +110,10 @@ impl change::Storage for git2::Repository {
             message,
             embeds,
             contents,
+            timestamp,
+            version,
         } = spec;
-        let manifest = store::Manifest::new(type_name, Version::default());
+        let manifest = store::Manifest::new(type_name, version);
         let revision = write_manifest(self, &manifest, embeds, &contents)?;
         let tree = self.find_tree(revision)?;
         let signature = {
@@ -140,6 +141,7 @@ impl change::Storage for git2::Repository {
                 .iter()
                 .map(|p| trailers::CommitTrailer::Related(**p).into()),
             tree,
+            timestamp,
         )?;
 
         Ok(Entry {
@@ -272,6 +274,7 @@ fn load_contents(repo: &git2::Repository, tree: &git2::Tree) -> Result<Contents,
     NonEmpty::collect(ops.into_values()).ok_or_else(|| error::Load::NoChange(tree.id().into()))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn write_commit(
     repo: &git2::Repository,
     resource: Option<git2::Oid>,
@@ -280,6 +283,7 @@ fn write_commit(
     signature: ExtendedSignature,
     trailers: impl IntoIterator<Item = OwnedTrailer>,
     tree: git2::Tree,
+    explicit: Option<Timestamp>,
 ) -> Result<(Oid, Timestamp), error::Create> {
     let trailers: Vec<OwnedTrailer> = trailers
         .into_iter()
@@ -321,6 +325,17 @@ fn write_commit(
     } else {
         (author, timestamp)
     };
+    // An explicit timestamp takes precedence, eg. so that imported changes keep their dates.
+    let (author, timestamp) = match explicit {
+        Some(explicit) => (
+            Author {
+                time: git_ext::author::Time::new(explicit as i64, 0),
+                ..author
+            },
+            explicit as i64,
+        ),
+        None => (author, timestamp),
+    };
 
     let oid = Commit::new(
         tree.id(),
@@ -47,6 +47,10 @@ pub struct Template<Id> {
     pub message: String,
     pub embeds: Vec<Embed>,
     pub contents: NonEmpty<Vec<u8>>,
+    /// Timestamp of the change. If not set, the current time is used.
+    pub timestamp: Option<Timestamp>,
+    /// Version recorded in the change manifest.
+    pub version: Version,
 }
 
 /// Entry contents.
@@ -154,7 +158,7 @@ impl Manifest {
 }
 
 /// COB version.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Version(NonZeroUsize);
 
 impl Default for Version {
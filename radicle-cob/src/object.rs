@@ -9,8 +9,8 @@ use thiserror::Error;
 
 pub mod collaboration;
 pub use collaboration::{
-    create, get, info, list, parse_refstr, remove, update, CollaborativeObject, Create, Evaluate,
-    Update, Updated,
+    create, get, get_from_refs, get_matching, info, list, parse_refstr, remove, update,
+    CollaborativeObject, Create, Evaluate, Update, Updated,
 };
 
 pub mod storage;
@@ -14,7 +14,7 @@ mod create;
 pub use create::{create, Create};
 
 mod get;
-pub use get::get;
+pub use get::{get, get_from_refs, get_matching};
 
 pub mod info;
 
@@ -21,6 +21,9 @@ pub struct Create {
     pub embeds: Vec<Embed>,
     /// COB version.
     pub version: Version,
+    /// Timestamp of the change, eg. when importing objects. If not set, the current
+    /// time is used.
+    pub timestamp: Option<change::Timestamp>,
 }
 
 impl Create {
@@ -31,6 +34,8 @@ impl Create {
             message: self.message,
             embeds: self.embeds,
             contents: self.contents,
+            timestamp: self.timestamp,
+            version: self.version,
         }
     }
 }
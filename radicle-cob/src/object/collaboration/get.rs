@@ -1,6 +1,9 @@
 // Copyright © 2022 The Radicle Link Contributors
 
-use crate::{change_graph::ChangeGraph, CollaborativeObject, Evaluate, ObjectId, Store, TypeName};
+use crate::{
+    change_graph::ChangeGraph, object::Reference, CollaborativeObject, Evaluate, ObjectId, Store,
+    TypeName,
+};
 
 use super::error;
 
@@ -30,3 +33,49 @@ where
         .map(|graph| graph.evaluate(storage).map_err(error::Retrieve::evaluate))
         .transpose()
 }
+
+/// Get a [`CollaborativeObject`] from a subset of its references, if it
+/// exists.
+///
+/// Only the references for which `filter` returns `true` are used as
+/// tips when loading the object's history, eg. to get the object as
+/// seen by a single peer. See [`get`] for the other parameters.
+pub fn get_matching<T, S>(
+    storage: &S,
+    typename: &TypeName,
+    oid: &ObjectId,
+    filter: impl Fn(&Reference) -> bool,
+) -> Result<Option<CollaborativeObject<T>>, error::Retrieve>
+where
+    T: Evaluate<S>,
+    S: Store,
+{
+    let tip_refs = storage
+        .objects(typename, oid)
+        .map_err(|err| error::Retrieve::Refs { err: Box::new(err) })?;
+
+    let tips = tip_refs.iter().filter(|r| filter(r));
+
+    ChangeGraph::load(storage, tips, typename, oid)
+        .map(|graph| graph.evaluate(storage).map_err(error::Retrieve::evaluate))
+        .transpose()
+}
+
+/// Get a [`CollaborativeObject`] from references that were already
+/// looked up, eg. with [`crate::object::Storage::types`] when getting
+/// several objects at once. This saves looking up the references of
+/// each object. See [`get`] for the other parameters.
+pub fn get_from_refs<'a, T, S>(
+    storage: &S,
+    typename: &TypeName,
+    oid: &ObjectId,
+    tip_refs: impl Iterator<Item = &'a Reference> + 'a,
+) -> Result<Option<CollaborativeObject<T>>, error::Retrieve>
+where
+    T: Evaluate<S>,
+    S: Store,
+{
+    ChangeGraph::load(storage, tip_refs, typename, oid)
+        .map(|graph| graph.evaluate(storage).map_err(error::Retrieve::evaluate))
+        .transpose()
+}
@@ -7,7 +7,7 @@ use radicle_crypto::PublicKey;
 
 use crate::{
     change, change_graph::ChangeGraph, history::EntryId, CollaborativeObject, Embed, Evaluate,
-    ObjectId, Store, TypeName,
+    ObjectId, Store, TypeName, Version,
 };
 
 use super::error;
@@ -35,6 +35,11 @@ pub struct Update {
     pub message: String,
     /// Embedded files.
     pub embeds: Vec<Embed>,
+    /// Timestamp of the change, eg. when importing objects. If not set, the current
+    /// time is used.
+    pub timestamp: Option<change::Timestamp>,
+    /// COB version, recorded in the manifest of the new change.
+    pub version: Version,
 }
 
 /// Update an existing [`CollaborativeObject`].
@@ -75,6 +80,8 @@ where
         embeds,
         changes,
         message,
+        timestamp,
+        version,
     } = args;
 
     let existing_refs = storage
@@ -97,6 +104,8 @@ where
             contents: changes,
             type_name: typename.clone(),
             message,
+            timestamp,
+            version,
         },
     )?;
     let head = entry.id;
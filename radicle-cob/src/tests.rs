@@ -36,6 +36,7 @@ fn roundtrip() {
             message: "creating xyz.rad.issue".to_string(),
             embeds: vec![],
             version: Version::default(),
+            timestamp: None,
         },
     )
     .unwrap();
@@ -70,6 +71,7 @@ fn list_cobs() {
             message: "creating xyz.rad.issue".to_string(),
             embeds: vec![],
             version: Version::default(),
+            timestamp: None,
         },
     )
     .unwrap();
@@ -86,6 +88,7 @@ fn list_cobs() {
             message: "commenting xyz.rad.issue".to_string(),
             embeds: vec![],
             version: Version::default(),
+            timestamp: None,
         },
     )
     .unwrap();
@@ -122,6 +125,7 @@ fn update_cob() {
             message: "creating xyz.rad.issue".to_string(),
             embeds: vec![],
             version: Version::default(),
+            timestamp: None,
         },
     )
     .unwrap();
@@ -142,6 +146,8 @@ fn update_cob() {
             type_name: typename.clone(),
             embeds: vec![],
             message: "commenting xyz.rad.issue".to_string(),
+            timestamp: None,
+            version: Version::default(),
         },
     )
     .unwrap();
@@ -183,6 +189,7 @@ fn traverse_cobs() {
             message: "creating xyz.rad.issue".to_string(),
             embeds: vec![],
             version: Version::default(),
+            timestamp: None,
         },
     )
     .unwrap();
@@ -207,6 +214,8 @@ fn traverse_cobs() {
             type_name: typename,
             embeds: vec![],
             message: "commenting on xyz.rad.issue".to_string(),
+            timestamp: None,
+            version: Version::default(),
         },
     )
     .unwrap();
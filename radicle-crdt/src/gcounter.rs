@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use radicle_crypto::PublicKey;
+use serde::{Deserialize, Serialize};
+
+use crate::ord::Max;
+use crate::Semilattice;
+
+/// Grow-only counter.
+///
+/// Each replica only increments its own count, and the value of the counter is the sum of
+/// the counts of all replicas. Since merging keeps the greatest count of each replica,
+/// increments made concurrently by different replicas are never lost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GCounter<K = PublicKey> {
+    counts: BTreeMap<K, Max<u64>>,
+}
+
+impl<K: Ord> GCounter<K> {
+    /// Increment the count of the given replica by one.
+    pub fn incr(&mut self, replica: K) {
+        self.counts.entry(replica).or_default().incr();
+    }
+
+    /// Increment the count of the given replica by `n`. Like [`Max::incr`], the addition
+    /// is saturating.
+    pub fn incr_by(&mut self, replica: K, n: u64) {
+        *self.counts.entry(replica).or_default() += n;
+    }
+
+    /// The count of the given replica.
+    pub fn get(&self, replica: &K) -> u64 {
+        self.counts.get(replica).map_or(0, |c| *c.get())
+    }
+
+    /// The value of the counter, ie. the sum of the counts of all replicas.
+    pub fn value(&self) -> u64 {
+        self.counts
+            .values()
+            .fold(0u64, |sum, c| sum.saturating_add(*c.get()))
+    }
+}
+
+impl<K: Ord> Default for GCounter<K> {
+    fn default() -> Self {
+        Self {
+            counts: BTreeMap::default(),
+        }
+    }
+}
+
+impl<K: Ord> Semilattice for GCounter<K> {
+    fn merge(&mut self, other: Self) {
+        self.counts.merge(other.counts);
+    }
+}
+
+#[cfg(any(test, feature = "test"))]
+impl<K: Ord + qcheck::Arbitrary> qcheck::Arbitrary for GCounter<K> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let counts = BTreeMap::<K, u8>::arbitrary(g);
+
+        Self {
+            counts: counts
+                .into_iter()
+                .map(|(k, v)| (k, Max::from(v as u64)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    #[quickcheck]
+    fn prop_semilattice(a: GCounter<u8>, b: GCounter<u8>, c: GCounter<u8>) {
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_concurrent_increments(base: GCounter, x: PublicKey, y: PublicKey, n: u8, m: u8) {
+        if x == y {
+            return;
+        }
+        let mut a = base.clone();
+        let mut b = base.clone();
+
+        a.incr_by(x, n as u64);
+        (0..m).for_each(|_| b.incr(y));
+
+        let merged = a.clone().join(b.clone());
+        assert_eq!(merged.value(), base.value() + n as u64 + m as u64);
+        assert_eq!(merged, b.join(a));
+    }
+
+    #[test]
+    fn test_value() {
+        let mut counter = GCounter::<u8>::default();
+        assert_eq!(counter.value(), 0);
+
+        counter.incr(1);
+        counter.incr(1);
+        counter.incr_by(2, 3);
+        assert_eq!(counter.get(&1), 2);
+        assert_eq!(counter.get(&2), 3);
+        assert_eq!(counter.get(&3), 0);
+        assert_eq!(counter.value(), 5);
+
+        // Merging an older copy doesn't lose increments.
+        let mut older = GCounter::default();
+        older.incr(1);
+        assert_eq!(counter.clone().join(older).value(), 5);
+
+        counter.incr_by(2, u64::MAX);
+        assert_eq!(counter.value(), u64::MAX);
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut counter = GCounter::<u8>::default();
+        counter.incr_by(1, 2);
+
+        let json = serde_json::to_value(&counter).unwrap();
+        assert_eq!(json, serde_json::json!({ "1": 2 }));
+        assert_eq!(
+            serde_json::from_value::<GCounter<u8>>(json).unwrap(),
+            counter
+        );
+    }
+}
@@ -2,7 +2,11 @@
 #![allow(clippy::bool_assert_comparison)]
 #![allow(clippy::collapsible_else_if)]
 #![allow(clippy::type_complexity)]
+use std::collections::{btree_map, hash_map, BTreeMap, HashMap};
+use std::hash::Hash;
+
 pub mod clock;
+pub mod gcounter;
 pub mod gmap;
 pub mod gset;
 pub mod immutable;
@@ -18,6 +22,7 @@ pub mod test;
 ////////////////////////////////////////////////////////////////////////////////
 
 pub use clock::Lamport;
+pub use gcounter::GCounter;
 pub use gmap::GMap;
 pub use gset::GSet;
 pub use immutable::Immutable;
@@ -73,6 +78,52 @@ impl Semilattice for bool {
     }
 }
 
+/// Tuples are merged element-wise.
+macro_rules! impl_semilattice_tuple {
+    ($($t:ident $i:tt),+) => {
+        impl<$($t: Semilattice),+> Semilattice for ($($t,)+) {
+            fn merge(&mut self, other: Self) {
+                $(self.$i.merge(other.$i);)+
+            }
+        }
+    };
+}
+
+impl_semilattice_tuple!(A 0, B 1);
+impl_semilattice_tuple!(A 0, B 1, C 2);
+impl_semilattice_tuple!(A 0, B 1, C 2, D 3);
+
+/// Maps are merged by taking the union of their keys, and merging the values of keys present
+/// in both maps. See also [`GMap`].
+impl<K: Ord, V: Semilattice> Semilattice for BTreeMap<K, V> {
+    fn merge(&mut self, other: Self) {
+        for (k, v) in other {
+            match self.entry(k) {
+                btree_map::Entry::Occupied(mut e) => e.get_mut().merge(v),
+                btree_map::Entry::Vacant(e) => {
+                    e.insert(v);
+                }
+            }
+        }
+    }
+}
+
+/// Same as the [`BTreeMap`] instance.
+impl<K: Eq + Hash, V: Semilattice> Semilattice for HashMap<K, V> {
+    fn merge(&mut self, other: Self) {
+        for (k, v) in other {
+            match self.entry(k) {
+                hash_map::Entry::Occupied(mut e) => e.get_mut().merge(v),
+                hash_map::Entry::Vacant(e) => {
+                    e.insert(v);
+                }
+            }
+        }
+    }
+}
+
+/// Join all semilattices of an iterator, starting from the default value, which should be
+/// the identity of [`Semilattice::join`]. An empty iterator thus yields the default value.
 pub fn fold<S>(i: impl IntoIterator<Item = S>) -> S
 where
     S: Semilattice + Default,
@@ -82,6 +133,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
     use crate::{test, Max, Min, Semilattice};
     use qcheck_macros::quickcheck;
 
@@ -95,6 +148,61 @@ mod tests {
         test::assert_laws(&a, &b, &c);
     }
 
+    #[quickcheck]
+    fn prop_btreemap_laws(
+        a: BTreeMap<u8, Max<u8>>,
+        b: BTreeMap<u8, Max<u8>>,
+        c: BTreeMap<u8, Max<u8>>,
+    ) {
+        test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_hashmap_laws(
+        a: HashMap<u8, Min<u8>>,
+        b: HashMap<u8, Min<u8>>,
+        c: HashMap<u8, Min<u8>>,
+    ) {
+        test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_pair_laws(a: (Max<u8>, Min<u8>), b: (Max<u8>, Min<u8>), c: (Max<u8>, Min<u8>)) {
+        test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_quadruple_laws(
+        a: (Max<u8>, Min<u8>, bool, Max<u16>),
+        b: (Max<u8>, Min<u8>, bool, Max<u16>),
+        c: (Max<u8>, Min<u8>, bool, Max<u16>),
+    ) {
+        test::assert_laws(&a, &b, &c);
+    }
+
+    #[test]
+    fn test_tuple() {
+        assert_eq!(
+            (Max::from(1), Min::from(1)).join((Max::from(2), Min::from(2))),
+            (Max::from(2), Min::from(1))
+        );
+        assert_eq!(
+            (Max::from(3), Min::from(3), false).join((Max::from(2), Min::from(2), true)),
+            (Max::from(3), Min::from(2), true)
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let a = BTreeMap::from_iter([(1, Max::from(1)), (2, Max::from(2))]);
+        let b = BTreeMap::from_iter([(2, Max::from(3)), (3, Max::from(1))]);
+
+        assert_eq!(
+            a.join(b),
+            BTreeMap::from_iter([(1, Max::from(1)), (2, Max::from(3)), (3, Max::from(1))])
+        );
+    }
+
     #[test]
     fn test_bool() {
         assert_eq!(false.join(false), false);
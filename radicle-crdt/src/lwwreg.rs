@@ -1,4 +1,5 @@
 use num_traits::Bounded;
+use serde::{Deserialize, Serialize};
 
 use crate::clock;
 use crate::ord::Max;
@@ -6,8 +7,10 @@ use crate::Semilattice;
 
 /// Last-Write-Wins Register.
 ///
-/// In case of conflict, uses the [`Semilattice`] instance of `T` to merge.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// In case of conflict, uses the [`Semilattice`] instance of `T` to merge. Values that aren't
+/// semilattices themselves can be wrapped in [`Max`] or [`crate::Min`], so that ties are
+/// broken deterministically, by keeping the greatest or smallest value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LWWReg<T, C = clock::Lamport> {
     clock: Max<C>,
     value: T,
@@ -104,6 +107,18 @@ mod tests {
         assert_eq!(a.join(b).get(), &Min::from(0));
     }
 
+    #[test]
+    fn test_serde() {
+        let reg = LWWReg::new(Max::from(42u8), 7u64);
+        let json = serde_json::to_value(&reg).unwrap();
+
+        assert_eq!(json, serde_json::json!({ "clock": 7, "value": 42 }));
+        assert_eq!(
+            serde_json::from_value::<LWWReg<Max<u8>, u64>>(json).unwrap(),
+            reg
+        );
+    }
+
     #[test]
     fn test_set_get() {
         let mut reg = LWWReg::new(Max::from(42), 1);
@@ -14,6 +14,11 @@ impl<T> Max<T> {
         &self.0
     }
 
+    /// Sort key of this value. Same as [`Max::get`].
+    pub fn key(&self) -> &T {
+        self.get()
+    }
+
     pub fn into_inner(self) -> T {
         self.0
     }
@@ -25,6 +30,18 @@ impl<T: num_traits::SaturatingAdd + num_traits::One> Max<T> {
     }
 }
 
+impl<T> ops::AddAssign<T> for Max<T>
+where
+    T: num_traits::SaturatingAdd + PartialOrd,
+{
+    /// Add to the value and merge the result, such that the value never decreases.
+    /// Like [`Max::incr`], the addition is saturating.
+    fn add_assign(&mut self, rhs: T) {
+        let value = self.0.saturating_add(&rhs);
+        self.merge(Self(value));
+    }
+}
+
 impl<T> Default for Max<T>
 where
     T: Bounded,
@@ -56,6 +73,14 @@ impl<T: PartialOrd> Semilattice for Max<T> {
     }
 }
 
+/// Join all values. An empty iterator yields the default, ie. the smallest value.
+/// See [`crate::fold`].
+impl<T: Bounded + PartialOrd> FromIterator<Max<T>> for Max<T> {
+    fn from_iter<I: IntoIterator<Item = Max<T>>>(iter: I) -> Self {
+        crate::fold(iter)
+    }
+}
+
 impl<T: Bounded> Bounded for Max<T> {
     fn min_value() -> Self {
         Self::from(T::min_value())
@@ -66,10 +91,47 @@ impl<T: Bounded> Bounded for Max<T> {
     }
 }
 
-#[allow(clippy::derive_ord_xor_partial_ord)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, Serialize, Deserialize)]
+/// A value that only decreases when merged.
+///
+/// Ordering is reversed with respect to `T`: the smaller the inner value, the greater the `Min`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
-pub struct Min<T>(pub T);
+pub struct Min<T>(T);
+
+impl<T> Min<T> {
+    pub fn get(&self) -> &T {
+        &self.0
+    }
+
+    /// Sort key of this value, ie. the inner value.
+    ///
+    /// Note that sorting by key yields the reverse order of sorting by `Min<T>` itself.
+    pub fn key(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: num_traits::SaturatingSub + num_traits::One> Min<T> {
+    pub fn decr(&mut self) {
+        self.0 = self.0.saturating_sub(&T::one());
+    }
+}
+
+impl<T> ops::SubAssign<T> for Min<T>
+where
+    T: num_traits::SaturatingSub + PartialOrd,
+{
+    /// Subtract from the value and merge the result, such that the value never increases.
+    /// Like [`Min::decr`], the subtraction is saturating.
+    fn sub_assign(&mut self, rhs: T) {
+        let value = self.0.saturating_sub(&rhs);
+        self.merge(Self(value));
+    }
+}
 
 impl<T> Default for Min<T>
 where
@@ -103,6 +165,15 @@ where
     }
 }
 
+impl<T> cmp::Ord for Min<T>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
 impl<T: PartialOrd> Semilattice for Min<T> {
     fn merge(&mut self, other: Self) {
         if other.0 < self.0 {
@@ -111,6 +182,25 @@ impl<T: PartialOrd> Semilattice for Min<T> {
     }
 }
 
+/// Join all values. An empty iterator yields the default, ie. the greatest inner value.
+/// See [`crate::fold`].
+impl<T: Bounded + PartialOrd> FromIterator<Min<T>> for Min<T> {
+    fn from_iter<I: IntoIterator<Item = Min<T>>>(iter: I) -> Self {
+        crate::fold(iter)
+    }
+}
+
+impl<T: Bounded> Bounded for Min<T> {
+    /// Since ordering is reversed, the smallest `Min` holds the greatest value.
+    fn min_value() -> Self {
+        Self::from(T::max_value())
+    }
+
+    fn max_value() -> Self {
+        Self::from(T::min_value())
+    }
+}
+
 #[cfg(any(test, feature = "test"))]
 mod arbitrary {
     use super::*;
@@ -127,3 +217,150 @@ mod arbitrary {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    #[quickcheck]
+    fn prop_min_ord(a: Min<u8>, b: Min<u8>) {
+        assert_eq!(Some(a.cmp(&b)), a.partial_cmp(&b));
+        assert_eq!(a.cmp(&b), b.0.cmp(&a.0));
+    }
+
+    #[quickcheck]
+    fn prop_max_ord(a: Max<u8>, b: Max<u8>) {
+        assert_eq!(Some(a.cmp(&b)), a.partial_cmp(&b));
+        assert_eq!(a.cmp(&b), a.0.cmp(&b.0));
+    }
+
+    #[quickcheck]
+    fn prop_min_laws(a: Min<u8>, b: Min<u8>, c: Min<u8>) {
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_max_laws(a: Max<u8>, b: Max<u8>, c: Max<u8>) {
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_min_merge(a: Min<u8>, b: Min<u8>) {
+        let mut c = a;
+        c.merge(b);
+
+        assert_eq!(c.into_inner(), cmp::min(a.0, b.0));
+        assert_eq!(c, cmp::max(a, b));
+    }
+
+    #[quickcheck]
+    fn prop_max_from_iter(values: Vec<Max<u8>>) {
+        let joined = values.iter().fold(Max::default(), |acc, v| acc.join(*v));
+
+        assert_eq!(values.iter().copied().collect::<Max<u8>>(), joined);
+        assert_eq!(crate::fold(values), joined);
+    }
+
+    #[quickcheck]
+    fn prop_min_from_iter(values: Vec<Min<u8>>) {
+        let joined = values.iter().fold(Min::default(), |acc, v| acc.join(*v));
+
+        assert_eq!(values.iter().copied().collect::<Min<u8>>(), joined);
+        assert_eq!(crate::fold(values), joined);
+    }
+
+    #[test]
+    fn test_from_iter_empty() {
+        assert_eq!(
+            std::iter::empty::<Max<u8>>().collect::<Max<u8>>(),
+            Max::from(u8::MIN)
+        );
+        assert_eq!(
+            std::iter::empty::<Min<u8>>().collect::<Min<u8>>(),
+            Min::from(u8::MAX)
+        );
+    }
+
+    #[quickcheck]
+    fn prop_max_add_assign(a: Max<u8>) {
+        let mut b = a;
+        let mut c = a;
+
+        b += 1;
+        c.incr();
+
+        assert_eq!(b, c);
+    }
+
+    #[quickcheck]
+    fn prop_min_sub_assign(a: Min<u8>) {
+        let mut b = a;
+        let mut c = a;
+
+        b -= 1;
+        c.decr();
+
+        assert_eq!(b, c);
+    }
+
+    #[quickcheck]
+    fn prop_max_serde(a: Max<u64>) {
+        let json = serde_json::to_value(a).unwrap();
+
+        assert_eq!(json, serde_json::to_value(a.0).unwrap());
+        assert_eq!(serde_json::from_value::<Max<u64>>(json).unwrap(), a);
+    }
+
+    #[quickcheck]
+    fn prop_min_serde(a: Min<u64>) {
+        let json = serde_json::to_value(a).unwrap();
+
+        assert_eq!(json, serde_json::to_value(a.0).unwrap());
+        assert_eq!(serde_json::from_value::<Min<u64>>(json).unwrap(), a);
+    }
+
+    #[test]
+    fn test_assign_ops() {
+        let mut max = Max::from(u8::MAX - 1);
+        max += 2;
+        assert_eq!(max, Max::from(u8::MAX));
+
+        let mut max = Max::from(1i8);
+        max += -1;
+        assert_eq!(max, Max::from(1));
+
+        let mut min = Min::from(u8::MIN + 1);
+        min -= 2;
+        assert_eq!(min, Min::from(u8::MIN));
+
+        let mut min = Min::from(1i8);
+        min -= -1;
+        assert_eq!(min, Min::from(1));
+    }
+
+    #[test]
+    fn test_min_bounded() {
+        assert_eq!(Min::<u8>::min_value(), Min::from(u8::MAX));
+        assert_eq!(Min::<u8>::max_value(), Min::from(u8::MIN));
+        assert!(Min::<u8>::min_value() < Min::<u8>::max_value());
+        assert_eq!(Min::<u8>::default(), Min::<u8>::min_value());
+    }
+
+    #[test]
+    fn test_min_ord() {
+        assert_eq!(Min::from(1).cmp(&Min::from(2)), cmp::Ordering::Greater);
+        assert_eq!(Min::from(2).cmp(&Min::from(1)), cmp::Ordering::Less);
+        assert_eq!(Min::from(1).cmp(&Min::from(1)), cmp::Ordering::Equal);
+        assert!(Min::from(1) > Min::from(2));
+
+        let set = BTreeSet::from_iter([Min::from(1), Min::from(3), Min::from(2)]);
+        assert_eq!(
+            set.into_iter().map(|m| m.0).collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+    }
+}
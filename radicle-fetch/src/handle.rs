@@ -10,6 +10,7 @@ use radicle::storage::git::Repository;
 use radicle::storage::ReadRepository;
 
 use crate::policy::{Allowed, BlockList};
+use crate::progress::Progress;
 use crate::transport::{ConnectionStream, Transport};
 
 /// The handle used for pulling or cloning changes from a remote peer.
@@ -28,6 +29,8 @@ pub struct Handle<S> {
     pub(crate) blocked: BlockList,
     // Signals to the pack writer to interrupt the process
     pub(crate) interrupt: Arc<AtomicBool>,
+    // Updated by the pack writer as packs are received
+    pub(crate) progress: Progress,
 }
 
 impl<S> Handle<S> {
@@ -51,6 +54,7 @@ impl<S> Handle<S> {
             transport,
             blocked,
             interrupt: Arc::new(AtomicBool::new(false)),
+            progress: Progress::default(),
         })
     }
 
@@ -74,6 +78,11 @@ impl<S> Handle<S> {
         self.interrupt.store(true, atomic::Ordering::Relaxed);
     }
 
+    /// The progress of the packs received, which can be read while fetching.
+    pub fn progress(&self) -> Progress {
+        self.progress.clone()
+    }
+
     pub fn verified(&self, head: Oid) -> Result<Doc<Verified>, DocError> {
         Ok(self.repo.identity_doc_at(head)?.doc)
     }
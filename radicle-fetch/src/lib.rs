@@ -1,6 +1,7 @@
 pub mod git;
 pub mod handle;
 pub mod policy;
+pub mod progress;
 pub mod transport;
 
 pub(crate) mod sigrefs;
@@ -11,6 +12,7 @@ mod state;
 
 pub use handle::Handle;
 pub use policy::{Allowed, BlockList, Scope};
+pub use progress::Progress;
 pub use state::{FetchLimit, FetchResult};
 pub use transport::Transport;
 
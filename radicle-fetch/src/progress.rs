@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use gix_features::progress::prodash::messages::MessageLevel;
+use gix_features::progress::prodash::progress::{Id, Step, StepShared, UNKNOWN};
+use gix_features::progress::prodash::{Count, NestedProgress, Progress as _, Unit};
+
+/// Progress id of the pack bytes read, see `gix_pack::bundle::write::ProgressId`.
+const READ_PACK_BYTES: Id = *b"BWRB";
+/// Progress id of the objects indexed, see `gix_pack::index::write::ProgressId`.
+const INDEX_OBJECTS: Id = *b"IWIO";
+
+/// Progress of the packs received during a fetch.
+///
+/// The counters are updated by the pack writer, and can be read from other threads
+/// while the fetch is ongoing.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    received: StepShared,
+    indexed: StepShared,
+    total: StepShared,
+}
+
+impl Progress {
+    /// Pack bytes received so far, across all the packs of the fetch.
+    pub fn received_bytes(&self) -> usize {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    /// Objects indexed so far, in the pack being received.
+    pub fn indexed_objects(&self) -> usize {
+        self.indexed.load(Ordering::Relaxed)
+    }
+
+    /// Objects in the pack being received, or zero if not known yet.
+    pub fn total_objects(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// The progress tree passed to the pack writer.
+    pub(crate) fn tracker(&self) -> Tracker {
+        Tracker::new(self.clone(), UNKNOWN)
+    }
+}
+
+/// A progress tree that updates a [`Progress`] with the steps of the pack writer that it
+/// knows about, and discards the others.
+pub(crate) struct Tracker {
+    progress: Progress,
+    id: Id,
+    name: Option<String>,
+    step: StepShared,
+    max: Option<Step>,
+    unit: Option<Unit>,
+}
+
+impl Tracker {
+    fn new(progress: Progress, id: Id) -> Self {
+        let step = match id {
+            READ_PACK_BYTES => progress.received.clone(),
+            INDEX_OBJECTS => progress.indexed.clone(),
+            _ => Arc::new(AtomicUsize::default()),
+        };
+        Self {
+            progress,
+            id,
+            name: None,
+            step,
+            max: None,
+            unit: None,
+        }
+    }
+}
+
+impl Count for Tracker {
+    fn set(&self, step: Step) {
+        self.step.store(step, Ordering::Relaxed);
+    }
+
+    fn step(&self) -> Step {
+        self.step.load(Ordering::Relaxed)
+    }
+
+    fn inc_by(&self, step: Step) {
+        self.step.fetch_add(step, Ordering::Relaxed);
+    }
+
+    fn counter(&self) -> StepShared {
+        self.step.clone()
+    }
+}
+
+impl gix_features::progress::prodash::Progress for Tracker {
+    fn init(&mut self, max: Option<Step>, unit: Option<Unit>) {
+        if self.id == INDEX_OBJECTS {
+            // Each pack is indexed from scratch.
+            self.progress.indexed.store(0, Ordering::Relaxed);
+            self.progress
+                .total
+                .store(max.unwrap_or_default(), Ordering::Relaxed);
+        }
+        self.max = max;
+        self.unit = unit;
+    }
+
+    fn unit(&self) -> Option<Unit> {
+        self.unit.clone()
+    }
+
+    fn max(&self) -> Option<Step> {
+        self.max
+    }
+
+    fn set_max(&mut self, max: Option<Step>) -> Option<Step> {
+        std::mem::replace(&mut self.max, max)
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.name = Some(name);
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn message(&self, _level: MessageLevel, message: String) {
+        log::trace!(target: "fetch", "{message}");
+    }
+}
+
+impl NestedProgress for Tracker {
+    type SubProgress = Self;
+
+    fn add_child(&mut self, name: impl Into<String>) -> Self {
+        self.add_child_with_id(name, UNKNOWN)
+    }
+
+    fn add_child_with_id(&mut self, name: impl Into<String>, id: Id) -> Self {
+        let mut child = Self::new(self.progress.clone(), id);
+        child.set_name(name.into());
+        child
+    }
+}
@@ -238,9 +238,12 @@ impl FetchState {
 
         let wants_haves = step.wants_haves(&handle.repo, &refs)?;
         if !wants_haves.wants.is_empty() {
-            handle
-                .transport
-                .fetch(wants_haves, handle.interrupt.clone(), handshake)?;
+            handle.transport.fetch(
+                wants_haves,
+                handle.interrupt.clone(),
+                &handle.progress,
+                handshake,
+            )?;
         } else {
             log::trace!(target: "fetch", "Nothing to fetch")
         };
@@ -22,6 +22,7 @@ use thiserror::Error;
 
 use crate::git::oid;
 use crate::git::repository;
+use crate::progress::Progress;
 
 /// Open a reader and writer stream to pass to the ls-refs and fetch
 /// processes for communicating during their respective protocols.
@@ -119,6 +120,7 @@ where
         &mut self,
         wants_haves: WantsHaves,
         interrupt: Arc<AtomicBool>,
+        progress: &Progress,
         handshake: &handshake::Outcome,
     ) -> io::Result<()> {
         log::trace!(
@@ -137,7 +139,7 @@ where
                 },
                 handshake,
                 Connection::new(read, write, FetchConnection::AllowReuse, self.repo.clone()),
-                &mut progress::Discard,
+                &mut progress.tracker(),
             )
             .map_err(io_other)?
         };
@@ -711,6 +711,7 @@ async fn issue_update_handler(
             issue.edit_comment(id, body, embeds, &signer)?
         }
         issue::Action::CommentRedact { id } => issue.redact_comment(id, &signer)?,
+        issue::Action::Remove => issues.tombstone(&issue_id.into(), &signer)?,
     };
 
     announce_refs(node, repo.id())?;
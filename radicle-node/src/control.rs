@@ -12,7 +12,7 @@ use serde_json as json;
 
 use crate::identity::RepoId;
 use crate::node::NodeId;
-use crate::node::{Command, CommandResult};
+use crate::node::{Command, CommandResult, FetchEvent, FetchOptions, Seed};
 use crate::runtime;
 use crate::runtime::thread;
 
@@ -102,24 +102,54 @@ where
                 }
             }
         }
-        Command::Fetch { rid, nid, timeout } => {
-            fetch(rid, nid, timeout, writer, &mut handle)?;
+        Command::Fetch {
+            rid,
+            nid,
+            timeout,
+            opts,
+        } => {
+            fetch(rid, nid, timeout, opts, writer, &mut handle)?;
         }
         Command::Config => {
             let config = handle.config()?;
 
             CommandResult::Okay(config).to_writer(writer)?;
         }
+        Command::Features => {
+            let features = handle.config()?.features();
+
+            CommandResult::Okay(features).to_writer(writer)?;
+        }
         Command::ListenAddrs => {
             let addrs = handle.listen_addrs()?;
 
             CommandResult::Okay(addrs).to_writer(writer)?;
         }
-        Command::Seeds { rid } => {
+        Command::Seeds {
+            rid,
+            page_size: None,
+        } => {
             let seeds = handle.seeds(rid)?;
 
             CommandResult::Okay(seeds).to_writer(writer)?;
         }
+        Command::Seeds {
+            rid,
+            page_size: Some(size),
+        } => {
+            // The seeds are looked up and sorted once, then sent a page at a time.
+            let mut seeds: Vec<Seed> = handle.seeds(rid)?.into();
+            seeds.sort_by_key(|s| s.nid);
+
+            if seeds.is_empty() {
+                // Always respond, even if there is nothing to send.
+                CommandResult::Okay(seeds).to_writer(writer)?;
+            } else {
+                for page in seeds.chunks(size.max(1)) {
+                    CommandResult::Okay(page).to_writer(&mut writer)?;
+                }
+            }
+        }
         Command::Sessions => {
             let sessions = handle.sessions()?;
 
@@ -162,8 +192,8 @@ where
 
             CommandResult::Okay(refs).to_writer(writer)?;
         }
-        Command::AnnounceInventory => {
-            if let Err(e) = handle.announce_inventory() {
+        Command::AnnounceInventory { peers } => {
+            if let Err(e) = handle.announce_inventory_to(peers) {
                 return Err(CommandError::Runtime(e));
             }
             CommandResult::ok().to_writer(writer).ok();
@@ -209,10 +239,21 @@ fn fetch<W: Write, H: Handle<Error = runtime::HandleError>>(
     id: RepoId,
     node: NodeId,
     timeout: time::Duration,
+    opts: FetchOptions,
     mut writer: W,
     handle: &mut H,
 ) -> Result<(), CommandError> {
-    match handle.fetch(id, node, timeout) {
+    let result = if opts.progress {
+        handle.fetch_with_progress(id, node, timeout, |progress| {
+            // If the client is gone, writing the result fails below.
+            CommandResult::Okay(FetchEvent::Progress { progress })
+                .to_writer(&mut writer)
+                .ok();
+        })
+    } else {
+        handle.fetch(id, node, timeout)
+    };
+    match result {
         Ok(result) => {
             json::to_writer(&mut writer, &result)?;
         }
@@ -312,4 +353,69 @@ mod tests {
         assert!(handle.unfollow(peer).unwrap());
         assert!(!handle.unfollow(peer).unwrap());
     }
+
+    #[test]
+    fn test_seeds_paged() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let rid = test::arbitrary::gen::<RepoId>(1);
+        let nids = test::arbitrary::set::<NodeId>(1000..1001);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let mut node = Node::new(&socket);
+        let handle = test::handle::Handle::default();
+
+        handle
+            .seeds
+            .lock()
+            .unwrap()
+            .extend(nids.iter().map(|nid| Seed::new(*nid, vec![], None, None)));
+        thread::spawn({
+            let handle = handle.clone();
+
+            move || crate::control::listen(listener, handle)
+        });
+
+        // Wait for node to be online.
+        while !node.is_running() {}
+
+        let mut expected = nids.into_iter().collect::<Vec<_>>();
+        expected.sort();
+
+        // The seeds are sent in full pages, except for the last one.
+        let pages = node
+            .call::<Vec<Seed>>(
+                Command::Seeds {
+                    rid,
+                    page_size: Some(64),
+                },
+                time::Duration::from_secs(6),
+            )
+            .unwrap()
+            .map(|page| page.unwrap().len())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.len(), 16);
+        assert!(pages[..15].iter().all(|len| *len == 64));
+        assert_eq!(pages[15], 40);
+
+        // Iterating over all pages yields every seed exactly once.
+        let paged = node
+            .seeds_paged(rid, 64)
+            .unwrap()
+            .map(|s| s.map(|s| s.nid))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(paged, expected);
+
+        // The first seeds can be used without waiting for the others.
+        let limited = node
+            .seeds_paged(rid, 64)
+            .unwrap()
+            .take(10)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(limited.len(), 10);
+
+        // The unpaginated call still returns everything.
+        assert_eq!(node.seeds(rid).unwrap().len(), expected.len());
+    }
 }
@@ -1,11 +1,12 @@
+use std::collections::HashMap;
 use std::net;
 use std::os::unix::net::UnixStream;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::{fmt, io, time};
 
 use crossbeam_channel as chan;
-use radicle::node::{ConnectOptions, ConnectResult, Seeds};
+use radicle::node::{ConnectOptions, ConnectResult, FetchProgress, Seeds};
 use radicle::storage::refs::RefsAt;
 use reactor::poller::popol::PopolWaker;
 use thiserror::Error;
@@ -23,6 +24,9 @@ use crate::wire;
 use crate::wire::StreamId;
 use crate::worker::TaskResult;
 
+/// How often the progress of a fetch is checked, when it is requested.
+const FETCH_PROGRESS_INTERVAL: time::Duration = time::Duration::from_millis(200);
+
 /// An error resulting from a handle method.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -61,9 +65,40 @@ impl<T> From<chan::SendError<T>> for Error {
     }
 }
 
+/// Progress of the fetches performed by the workers, by repository and remote.
+#[derive(Debug, Clone, Default)]
+pub struct Fetching(Arc<Mutex<HashMap<(RepoId, NodeId), radicle_fetch::Progress>>>);
+
+impl Fetching {
+    /// Track the progress of a fetch, until [`Fetching::finish`] is called.
+    pub(crate) fn start(&self, rid: RepoId, remote: NodeId, progress: radicle_fetch::Progress) {
+        self.0.lock().unwrap().insert((rid, remote), progress);
+    }
+
+    /// Stop tracking the progress of a fetch.
+    pub(crate) fn finish(&self, rid: &RepoId, remote: &NodeId) {
+        self.0.lock().unwrap().remove(&(*rid, *remote));
+    }
+
+    /// Get the progress of a fetch, if it was started.
+    fn progress(&self, rid: &RepoId, remote: &NodeId) -> Option<FetchProgress> {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&(*rid, *remote))
+            .map(|p| FetchProgress {
+                received_bytes: p.received_bytes() as u64,
+                indexed_objects: p.indexed_objects(),
+                total_objects: p.total_objects(),
+            })
+    }
+}
+
 pub struct Handle {
     pub(crate) home: Home,
     pub(crate) controller: reactor::Controller<wire::Control, PopolWaker>,
+    /// Progress of ongoing fetches, updated by the workers.
+    pub(crate) fetching: Fetching,
 
     /// Whether a shutdown was initiated or not. Prevents attempting to shutdown twice.
     shutdown: Arc<AtomicBool>,
@@ -89,6 +124,7 @@ impl Clone for Handle {
         Self {
             home: self.home.clone(),
             controller: self.controller.clone(),
+            fetching: self.fetching.clone(),
             shutdown: self.shutdown.clone(),
             emitter: self.emitter.clone(),
         }
@@ -104,6 +140,7 @@ impl Handle {
         Self {
             home,
             controller,
+            fetching: Fetching::default(),
             shutdown: Arc::default(),
             emitter,
         }
@@ -208,6 +245,37 @@ impl radicle::node::Handle for Handle {
         receiver.recv().map_err(Error::from)
     }
 
+    fn fetch_with_progress(
+        &mut self,
+        id: RepoId,
+        from: NodeId,
+        timeout: time::Duration,
+        mut progress: impl FnMut(FetchProgress),
+    ) -> Result<FetchResult, Error> {
+        let (sender, receiver) = chan::bounded(1);
+        self.command(service::Command::Fetch(id, from, timeout, sender))?;
+
+        let mut last = None;
+        loop {
+            match receiver.recv_timeout(FETCH_PROGRESS_INTERVAL) {
+                Ok(result) => return Ok(result),
+                Err(chan::RecvTimeoutError::Timeout) => {
+                    // The fetch may still be queued, in which case there's no progress yet.
+                    let Some(current) = self.fetching.progress(&id, &from) else {
+                        continue;
+                    };
+                    if last.as_ref() != Some(&current) {
+                        progress(current.clone());
+                        last = Some(current);
+                    }
+                }
+                Err(chan::RecvTimeoutError::Disconnected) => {
+                    return Err(Error::ChannelDisconnected)
+                }
+            }
+        }
+    }
+
     fn follow(&mut self, id: NodeId, alias: Option<Alias>) -> Result<bool, Error> {
         let (sender, receiver) = chan::bounded(1);
         self.command(service::Command::Follow(id, alias, sender))?;
@@ -239,7 +307,12 @@ impl radicle::node::Handle for Handle {
     }
 
     fn announce_inventory(&mut self) -> Result<(), Error> {
-        self.command(service::Command::AnnounceInventory)
+        self.command(service::Command::AnnounceInventory(None))
+            .map_err(Error::from)
+    }
+
+    fn announce_inventory_to(&mut self, peers: Vec<NodeId>) -> Result<(), Error> {
+        self.command(service::Command::AnnounceInventory(Some(peers)))
             .map_err(Error::from)
     }
 
@@ -266,6 +339,7 @@ impl radicle::node::Handle for Handle {
                     nid: *nid,
                     addr: s.addr.clone(),
                     state: s.state.clone(),
+                    version: s.version,
                 })
                 .collect();
             sender.send(sessions).ok();
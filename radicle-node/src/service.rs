@@ -38,7 +38,8 @@ use crate::identity::{Doc, RepoId};
 use crate::node::routing;
 use crate::node::routing::InsertResult;
 use crate::node::{
-    Address, Alias, Features, FetchResult, HostName, Seed, Seeds, SyncStatus, SyncedAt,
+    Address, Alias, Features, FetchErrorKind, FetchResult, HostName, Seed, Seeds, SyncStatus,
+    SyncedAt,
 };
 use crate::prelude::*;
 use crate::runtime::Emitter;
@@ -169,8 +170,8 @@ pub type QueryState = dyn Fn(&dyn ServiceState) -> Result<(), CommandError> + Se
 pub enum Command {
     /// Announce repository references for given repository to peers.
     AnnounceRefs(RepoId, chan::Sender<RefsAt>),
-    /// Announce local repositories to peers.
-    AnnounceInventory,
+    /// Announce local repositories to the given peers, or to all peers if `None`.
+    AnnounceInventory(Option<Vec<NodeId>>),
     /// Announce local inventory to peers.
     SyncInventory(chan::Sender<bool>),
     /// Connect to node with the given address.
@@ -201,7 +202,7 @@ impl fmt::Debug for Command {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::AnnounceRefs(id, _) => write!(f, "AnnounceRefs({id})"),
-            Self::AnnounceInventory => write!(f, "AnnounceInventory"),
+            Self::AnnounceInventory(peers) => write!(f, "AnnounceInventory({peers:?})"),
             Self::SyncInventory(_) => write!(f, "SyncInventory(..)"),
             Self::Connect(id, addr, opts) => write!(f, "Connect({id}, {addr}, {opts:?})"),
             Self::Disconnect(id) => write!(f, "Disconnect({id})"),
@@ -244,6 +245,19 @@ enum TryFetchError<'a> {
     Namespaces(#[from] NamespacesError),
 }
 
+impl TryFetchError<'_> {
+    /// The kind of error, as reported to the user.
+    fn kind(&self) -> FetchErrorKind {
+        match self {
+            Self::SessionNotFound | Self::SessionNotConnected => FetchErrorKind::Disconnected,
+            Self::Namespaces(
+                NamespacesError::BlockedPolicy { .. } | NamespacesError::NoFollowed { .. },
+            ) => FetchErrorKind::Policy,
+            _ => FetchErrorKind::Other,
+        }
+    }
+}
+
 /// Fetch state for an ongoing fetch.
 #[derive(Debug)]
 struct FetchState {
@@ -776,12 +790,11 @@ where
                     error!(target: "service", "Error announcing refs: {err}");
                 }
             },
-            Command::AnnounceInventory => {
-                if let Err(err) = self
-                    .storage
-                    .inventory()
-                    .and_then(|i| self.announce_inventory(i))
-                {
+            Command::AnnounceInventory(peers) => {
+                if let Err(err) = self.storage.inventory().and_then(|i| match &peers {
+                    None => self.announce_inventory(i),
+                    Some(peers) => self.announce_inventory_to(i, |nid| peers.contains(nid)),
+                }) {
                     error!(target: "service", "Error announcing inventory: {err}");
                 }
             }
@@ -869,6 +882,7 @@ where
                 if let Some(c) = channel {
                     c.send(FetchResult::Failed {
                         reason: e.to_string(),
+                        kind: e.kind(),
                     })
                     .ok();
                 }
@@ -957,10 +971,12 @@ where
 
                 // For now, we only disconnect the remote in case of timeout. In the future,
                 // there may be other reasons to disconnect.
+                let kind = err.kind();
+
                 if err.is_timeout() {
                     self.outbox.disconnect(remote, DisconnectReason::Fetch(err));
                 }
-                FetchResult::Failed { reason }
+                FetchResult::Failed { reason, kind }
             }
         };
 
@@ -1158,6 +1174,7 @@ where
             for resp in &fetching.subscribers {
                 resp.send(FetchResult::Failed {
                     reason: format!("disconnected: {reason}"),
+                    kind: FetchErrorKind::Disconnected,
                 })
                 .ok();
             }
@@ -1212,6 +1229,13 @@ where
         self.dequeue_fetch();
     }
 
+    /// Record the protocol version of the frames received from a peer.
+    pub fn received_version(&mut self, remote: NodeId, version: u8) {
+        if let Some(session) = self.sessions.get_mut(&remote) {
+            session.version = Some(version);
+        }
+    }
+
     pub fn received_message(&mut self, remote: NodeId, message: Message) {
         if let Err(err) = self.handle_message(&remote, message) {
             // If there's an error, stop processing messages from this peer.
@@ -1999,6 +2023,15 @@ where
 
     /// Announce our inventory to all connected peers.
     fn announce_inventory(&mut self, inventory: Vec<RepoId>) -> Result<(), storage::Error> {
+        self.announce_inventory_to(inventory, |_| true)
+    }
+
+    /// Announce our inventory to the connected peers matching the given predicate.
+    fn announce_inventory_to(
+        &mut self,
+        inventory: Vec<RepoId>,
+        is_target: impl Fn(&NodeId) -> bool,
+    ) -> Result<(), storage::Error> {
         let time = if self.clock > self.last_announce {
             self.clock.as_millis()
         } else if self.last_announce - self.clock < LocalDuration::from_secs(1) {
@@ -2020,7 +2053,10 @@ where
 
         self.outbox.announce(
             msg.signed(&self.signer),
-            self.sessions.connected().map(|(_, p)| p),
+            self.sessions
+                .connected()
+                .filter(|(nid, _)| is_target(nid))
+                .map(|(_, p)| p),
             self.db.gossip_mut(),
         );
         self.last_announce = LocalTime::from_millis(time as u128);
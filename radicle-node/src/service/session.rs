@@ -58,6 +58,9 @@ pub struct Session {
     pub subscribe: Option<message::Subscribe>,
     /// Last time a message was received from the peer.
     pub last_active: LocalTime,
+    /// Protocol version of the frames received from the peer, if any were received
+    /// on the current connection.
+    pub version: Option<u8>,
 
     /// Connection attempts. For persistent peers, Tracks
     /// how many times we've attempted to connect. We reset this to zero
@@ -98,6 +101,7 @@ impl Session {
             subscribe: None,
             persistent,
             last_active: LocalTime::default(),
+            version: None,
             attempts: 1,
             rng,
             limits,
@@ -125,6 +129,7 @@ impl Session {
             subscribe: None,
             persistent,
             last_active: time,
+            version: None,
             attempts: 0,
             rng,
             limits,
@@ -222,6 +227,7 @@ impl Session {
     /// that was requested.
     pub fn to_disconnected(&mut self, since: LocalTime, retry_at: LocalTime) {
         self.state = State::Disconnected { since, retry_at };
+        self.version = None;
     }
 
     /// Return to initial state from disconnected state. This state transition
@@ -94,6 +94,7 @@ impl Environment {
             public_explorer: explorer::Explorer::default(),
             preferred_seeds: vec![],
             web: web::Config::default(),
+            sync: cli::SyncConfig::default(),
         }
     }
 
@@ -7,7 +7,9 @@ use radicle::git;
 use radicle::storage::refs::RefsAt;
 
 use crate::identity::RepoId;
-use crate::node::{Alias, Config, ConnectOptions, ConnectResult, Event, FetchResult, Seeds};
+use crate::node::{
+    Alias, Config, ConnectOptions, ConnectResult, Event, FetchProgress, FetchResult, Seed, Seeds,
+};
 use crate::runtime::HandleError;
 use crate::service::policy;
 use crate::service::NodeId;
@@ -17,6 +19,7 @@ pub struct Handle {
     pub updates: Arc<Mutex<Vec<RepoId>>>,
     pub seeding: Arc<Mutex<HashSet<RepoId>>>,
     pub following: Arc<Mutex<HashSet<NodeId>>>,
+    pub seeds: Arc<Mutex<Vec<Seed>>>,
 }
 
 impl radicle::node::Handle for Handle {
@@ -49,7 +52,7 @@ impl radicle::node::Handle for Handle {
     }
 
     fn seeds(&mut self, _id: RepoId) -> Result<Seeds, Self::Error> {
-        unimplemented!();
+        Ok(Seeds::from(self.seeds.lock().unwrap().clone()))
     }
 
     fn fetch(
@@ -64,6 +67,16 @@ impl radicle::node::Handle for Handle {
         })
     }
 
+    fn fetch_with_progress(
+        &mut self,
+        id: RepoId,
+        from: NodeId,
+        timeout: time::Duration,
+        _progress: impl FnMut(FetchProgress),
+    ) -> Result<FetchResult, Self::Error> {
+        self.fetch(id, from, timeout)
+    }
+
     fn seed(&mut self, id: RepoId, _scope: policy::Scope) -> Result<bool, Self::Error> {
         Ok(self.seeding.lock().unwrap().insert(id))
     }
@@ -100,6 +113,10 @@ impl radicle::node::Handle for Handle {
         Ok(())
     }
 
+    fn announce_inventory_to(&mut self, _peers: Vec<NodeId>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     fn sync_inventory(&mut self) -> Result<bool, Self::Error> {
         unimplemented!()
     }
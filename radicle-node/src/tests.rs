@@ -1025,6 +1025,37 @@ fn test_inventory_relay() {
     );
 }
 
+#[test]
+fn test_inventory_announce_to_peers() {
+    let mut alice = Peer::new("alice", [7, 7, 7, 7]);
+    let bob = Peer::new("bob", [8, 8, 8, 8]);
+    let eve = Peer::new("eve", [9, 9, 9, 9]);
+
+    alice.init();
+    alice.wake(); // Run all periodic tasks now so they don't trigger later.
+    alice.connect_to(&bob);
+    alice.connect_from(&eve);
+    // Drain the announcements sent on connection.
+    alice.inventory_announcements(bob.id()).for_each(drop);
+    alice.inventory_announcements(eve.id()).for_each(drop);
+
+    alice.elapse(LocalDuration::from_secs(1)); // Make sure our announcement is fresh.
+    alice.command(service::Command::AnnounceInventory(Some(vec![
+        bob.node_id()
+    ])));
+
+    assert_matches!(
+        alice.inventory_announcements(bob.id()).next(),
+        Some(Message::Announcement(Announcement { node, .. }))
+        if node == alice.node_id()
+    );
+    assert_matches!(
+        alice.inventory_announcements(eve.id()).next(),
+        None,
+        "The inventory is only announced to the given peers"
+    );
+}
+
 #[test]
 fn test_persistent_peer_reconnect_attempt() {
     use std::collections::HashSet;
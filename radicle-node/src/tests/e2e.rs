@@ -174,7 +174,7 @@ fn test_replication() {
 
     let updated = match result {
         FetchResult::Success { updated, .. } => updated,
-        FetchResult::Failed { reason } => {
+        FetchResult::Failed { reason, .. } => {
             panic!("Fetch failed from {}: {reason}", bob.id);
         }
     };
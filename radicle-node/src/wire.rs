@@ -3,7 +3,7 @@ mod message;
 mod protocol;
 mod varint;
 
-pub use frame::StreamId;
+pub use frame::{StreamId, PROTOCOL_VERSION};
 pub use message::{AddressType, MessageType};
 pub use protocol::{Control, Wire, WireReader, WireSession, WireWriter};
 
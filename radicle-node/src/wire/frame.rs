@@ -16,9 +16,16 @@ const CONTROL_CLOSE: u8 = 1;
 const CONTROL_EOF: u8 = 2;
 
 /// Protocol version.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Version([u8; 4]);
 
+impl Version {
+    /// The version number, without the magic sequence.
+    pub fn number(&self) -> u8 {
+        self.0[3]
+    }
+}
+
 impl wire::Encode for Version {
     fn encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
         writer.write_all(&PROTOCOL_VERSION.0)?;
@@ -171,6 +171,8 @@ enum Peer {
         nid: NodeId,
         inbox: Deserializer<Frame>,
         streams: Streams,
+        /// Protocol version of the last frame received from the peer.
+        version: Option<frame::Version>,
     },
     /// The peer was scheduled for disconnection. Once the transport is handed over
     /// by the reactor, we can consider it disconnected.
@@ -206,6 +208,7 @@ impl Peer {
             nid,
             inbox: Deserializer::default(),
             streams: Streams::new(link),
+            version: None,
         }
     }
 
@@ -581,13 +584,22 @@ where
                     nid,
                     inbox,
                     streams,
+                    version,
                     ..
                 }) = self.peers.get_mut(&id)
                 {
                     inbox.input(&data);
 
                     loop {
-                        match inbox.deserialize_next() {
+                        let frame = inbox.deserialize_next();
+
+                        if let Ok(Some(Frame { version: v, .. })) = &frame {
+                            if version.as_ref() != Some(v) {
+                                *version = Some(*v);
+                                self.service.received_version(*nid, v.number());
+                            }
+                        }
+                        match frame {
                             Ok(Some(Frame {
                                 data: FrameData::Control(frame::Control::Open { stream }),
                                 ..
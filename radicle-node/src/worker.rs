@@ -12,6 +12,7 @@ use crossbeam_channel as chan;
 
 use radicle::identity::RepoId;
 use radicle::node::notifications;
+use radicle::node::FetchErrorKind;
 use radicle::prelude::NodeId;
 use radicle::storage::refs::RefsAt;
 use radicle::storage::{ReadRepository, ReadStorage};
@@ -69,6 +70,26 @@ impl FetchError {
     pub fn is_timeout(&self) -> bool {
         matches!(self, FetchError::Io(e) if e.kind() == io::ErrorKind::TimedOut)
     }
+
+    /// The kind of error, as reported to the user.
+    pub fn kind(&self) -> FetchErrorKind {
+        match self {
+            Self::Io(e) => match e.kind() {
+                io::ErrorKind::TimedOut => FetchErrorKind::Timeout,
+                io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::NotConnected
+                | io::ErrorKind::UnexpectedEof => FetchErrorKind::Disconnected,
+                _ => FetchErrorKind::Other,
+            },
+            Self::Fetch(fetch::error::Fetch::Validation) => FetchErrorKind::Validation,
+            Self::Policy(radicle_fetch::policy::error::Policy::BlockedPolicy { .. }) => {
+                FetchErrorKind::Policy
+            }
+            _ => FetchErrorKind::Other,
+        }
+    }
 }
 
 /// Error returned by fetch responder.
@@ -311,7 +332,10 @@ impl Worker {
             channels,
             notifs,
         )?;
-        let result = handle.fetch(rid, &self.storage, &mut cache, *limit, remote, refs_at)?;
+        self.handle.fetching.start(rid, remote, handle.progress());
+        let result = handle.fetch(rid, &self.storage, &mut cache, *limit, remote, refs_at);
+        self.handle.fetching.finish(&rid, &remote);
+        let result = result?;
 
         if let Err(e) = garbage::collect(&self.storage, rid, *expiry) {
             // N.b. ensure that `git gc` works in debug mode.
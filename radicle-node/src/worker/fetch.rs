@@ -60,6 +60,13 @@ impl Handle {
         }
     }
 
+    /// The progress of the packs received, which can be read while fetching.
+    pub fn progress(&self) -> radicle_fetch::Progress {
+        match self {
+            Self::Clone { handle, .. } | Self::Pull { handle, .. } => handle.progress(),
+        }
+    }
+
     pub fn fetch(
         self,
         rid: RepoId,
@@ -233,6 +240,20 @@ where
                                         type_name: identifier.type_name,
                                         err: e.into(),
                                     })?;
+                            } else {
+                                // The fetched changes may have tombstoned the issue.
+                                cob::cache::Remove::<cob::issue::Issue>::remove(
+                                    cache,
+                                    &identifier.id,
+                                )
+                                .map(|_| ())
+                                .map_err(|e| {
+                                    error::Cache::Remove {
+                                        id: identifier.id,
+                                        type_name: identifier.type_name,
+                                        err: e.into(),
+                                    }
+                                })?;
                             }
                         } else if identifier.is_patch() {
                             if let Some(patch) = patches.get(&identifier.id)? {
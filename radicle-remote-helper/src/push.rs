@@ -679,7 +679,9 @@ fn sync(
     }
     let message = format!("Syncing with {} node(s)..", connected.len());
     let mut spinner = if io::stderr().is_terminal() {
-        cli::spinner_to(message, io::stderr(), io::stderr())
+        let mut spinner = cli::spinner_to(message, io::stderr(), io::stderr());
+        spinner.show_elapsed();
+        spinner
     } else {
         cli::spinner_to(message, io::stderr(), io::sink())
     };
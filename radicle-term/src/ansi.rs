@@ -7,10 +7,10 @@ mod color;
 mod paint;
 mod style;
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 mod windows;
 
-pub use color::Color;
+pub use color::{Color, ColorDepth, ColorMode};
 pub use paint::paint;
 pub use paint::Filled;
 pub use paint::Paint;
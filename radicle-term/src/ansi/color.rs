@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 
 use super::{Paint, Style};
 
@@ -30,7 +31,147 @@ pub enum Color {
     RGB(u8, u8, u8),
 }
 
+/// When output should be colored. See [`Paint::set_color`].
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+pub enum ColorMode {
+    /// Color output written to a terminal that supports it, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    /// Always color output.
+    Always,
+    /// Never color output, even if `CLICOLOR_FORCE` is set.
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err("invalid color mode, expected one of: auto, always, never"),
+        }
+    }
+}
+
+/// The colors a terminal is able to display.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub enum ColorDepth {
+    /// The eight basic colors.
+    Basic,
+    /// The 256 colors of the xterm palette.
+    Fixed,
+    /// 24-bit RGB colors.
+    Truecolor,
+}
+
+impl ColorDepth {
+    /// Detect the color depth of a terminal from whether it supports 24-bit colors, and the
+    /// value of its `TERM` environment variable.
+    pub fn from_env(truecolor: bool, term: Option<&str>) -> Self {
+        if truecolor {
+            Self::Truecolor
+        } else if term.map_or(false, |t| t.contains("256color")) {
+            Self::Fixed
+        } else {
+            Self::Basic
+        }
+    }
+}
+
+/// The xterm palette levels of each channel in the 6x6x6 color cube, ie. colors `16` to `231`.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Approximate RGB values of the basic colors, in the xterm palette.
+const BASIC: [(Color, (u8, u8, u8)); 8] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+];
+
+/// Squared distance between two RGB colors.
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
 impl Color {
+    /// Get the closest color the terminal is able to display, given its color depth.
+    /// Colors that can be displayed are returned as-is.
+    pub fn degrade(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (_, ColorDepth::Truecolor) => self,
+            (Color::RGB(r, g, b), ColorDepth::Fixed) => Color::Fixed(Self::nearest_fixed(r, g, b)),
+            (Color::RGB(r, g, b), ColorDepth::Basic) => Self::nearest_basic((r, g, b)),
+            // The first 16 colors are the basic colors, and their bright variants.
+            (Color::Fixed(n @ 0..=15), ColorDepth::Basic) => BASIC[n as usize % 8].0,
+            (Color::Fixed(n), ColorDepth::Basic) => Self::nearest_basic(Self::fixed_rgb(n)),
+            _ => self,
+        }
+    }
+
+    /// Get the 256-color palette index closest to the given RGB color.
+    fn nearest_fixed(r: u8, g: u8, b: u8) -> u8 {
+        let level = |c: u8| {
+            (0..CUBE_LEVELS.len())
+                .min_by_key(|i| (CUBE_LEVELS[*i] as i32 - c as i32).abs())
+                .unwrap_or_default() as u8
+        };
+        let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+        // The grayscale ramp goes from `8` to `238`, in steps of `10`.
+        let avg = (r as u32 + g as u32 + b as u32) / 3;
+        let gray = 232 + ((avg.saturating_sub(3) / 10).min(23)) as u8;
+
+        if distance(Self::fixed_rgb(gray), (r, g, b)) < distance(Self::fixed_rgb(cube), (r, g, b)) {
+            gray
+        } else {
+            cube
+        }
+    }
+
+    /// Get the basic color closest to the given RGB color.
+    fn nearest_basic(rgb: (u8, u8, u8)) -> Self {
+        BASIC
+            .iter()
+            .min_by_key(|(_, c)| distance(*c, rgb))
+            .map(|(color, _)| *color)
+            .unwrap_or_default()
+    }
+
+    /// Get the RGB value of a color from the 256-color palette.
+    fn fixed_rgb(n: u8) -> (u8, u8, u8) {
+        match n {
+            0..=7 => BASIC[n as usize].1,
+            8 => (127, 127, 127),
+            9..=15 => {
+                let (r, g, b) = BASIC[n as usize - 8].1;
+                let brighten = |c: u8| if c > 0 { 255 } else { 0 };
+
+                (brighten(r), brighten(g), brighten(b))
+            }
+            16..=231 => {
+                let n = n - 16;
+                (
+                    CUBE_LEVELS[n as usize / 36],
+                    CUBE_LEVELS[(n as usize / 6) % 6],
+                    CUBE_LEVELS[n as usize % 6],
+                )
+            }
+            232..=255 => {
+                let v = 8 + 10 * (n - 232);
+                (v, v, v)
+            }
+        }
+    }
+
     /// Constructs a new `Paint` structure that encapsulates `item` with the
     /// foreground color set to the color `self`.
     #[inline]
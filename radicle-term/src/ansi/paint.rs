@@ -1,11 +1,11 @@
 use std::io::IsTerminal as _;
 use std::os::fd::{AsRawFd, BorrowedFd};
 use std::sync::atomic::{AtomicBool, AtomicI32};
-use std::{fmt, sync};
+use std::{env, fmt, sync};
 
 use once_cell::sync::Lazy;
 
-use super::color::Color;
+use super::color::{Color, ColorDepth, ColorMode};
 use super::style::{Property, Style};
 
 /// What file is used for text output.
@@ -110,6 +110,18 @@ impl<T> Paint<T> {
         Paint::new(item).fg(Color::Fixed(color))
     }
 
+    /// Like [`Paint::rgb`], but if the terminal doesn't support 24-bit colors, the foreground
+    /// is set to the closest color it supports instead. See [`Paint::color_depth`].
+    pub fn rgb_adaptive(r: u8, g: u8, b: u8, item: T) -> Paint<T> {
+        Paint::new(item).fg(Color::RGB(r, g, b).degrade(Paint::color_depth()))
+    }
+
+    /// Like [`Paint::fixed`], but if the terminal doesn't support 256 colors, the foreground
+    /// is set to the closest basic color instead. See [`Paint::color_depth`].
+    pub fn fixed_adaptive(color: u8, item: T) -> Paint<T> {
+        Paint::new(item).fg(Color::Fixed(color).degrade(Paint::color_depth()))
+    }
+
     pub const fn red(item: T) -> Paint<T> {
         Paint::new(item).fg(Color::Red)
     }
@@ -263,12 +275,14 @@ impl Paint<()> {
         let clicolor = anstyle_query::clicolor();
         let clicolor_enabled = clicolor.unwrap_or(false);
         let clicolor_disabled = !clicolor.unwrap_or(true);
+        // Disabling styling takes precedence over the environment.
+        if !ENABLED.load(sync::atomic::Ordering::SeqCst) {
+            return false;
+        }
         let terminal = TERMINAL.load(sync::atomic::Ordering::SeqCst);
         let is_terminal = unsafe { BorrowedFd::borrow_raw(terminal).is_terminal() };
-        let is_enabled = ENABLED.load(sync::atomic::Ordering::SeqCst);
 
         is_terminal
-            && is_enabled
             && !anstyle_query::no_color()
             && !clicolor_disabled
             && (anstyle_query::term_supports_color() || clicolor_enabled || anstyle_query::is_ci())
@@ -281,6 +295,15 @@ impl Paint<()> {
         *TRUECOLOR
     }
 
+    /// Get the color depth supported by the terminal: 24-bit colors if [`Paint::truecolor`]
+    /// holds, otherwise the 256-color palette if advertized by the `TERM` environment variable.
+    pub fn color_depth() -> ColorDepth {
+        static DEPTH: Lazy<ColorDepth> = Lazy::new(|| {
+            ColorDepth::from_env(Paint::truecolor(), env::var("TERM").ok().as_deref())
+        });
+        *DEPTH
+    }
+
     /// Enable paint styling.
     pub fn enable() {
         ENABLED.store(true, sync::atomic::Ordering::SeqCst);
@@ -302,6 +325,36 @@ impl Paint<()> {
     pub fn disable() {
         ENABLED.store(false, sync::atomic::Ordering::SeqCst);
     }
+
+    /// Set when paint styling is used, eg. from a `--color` option. In [`ColorMode::Auto`]
+    /// mode, styling depends on the terminal and the environment, eg. `NO_COLOR`.
+    pub fn set_color(mode: ColorMode) {
+        match mode {
+            ColorMode::Auto => {
+                Self::force(false);
+                Self::enable();
+            }
+            ColorMode::Always => {
+                Self::force(true);
+                Self::enable();
+            }
+            ColorMode::Never => {
+                Self::force(false);
+                Self::disable();
+            }
+        }
+    }
+
+    /// Get the color mode last set, see [`Paint::set_color`].
+    pub fn color_mode() -> ColorMode {
+        if FORCED.load(sync::atomic::Ordering::SeqCst) {
+            ColorMode::Always
+        } else if !ENABLED.load(sync::atomic::Ordering::SeqCst) {
+            ColorMode::Never
+        } else {
+            ColorMode::Auto
+        }
+    }
 }
 
 /// An object filled with a background color.
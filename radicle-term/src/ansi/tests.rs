@@ -3,8 +3,8 @@ use std::sync::Mutex;
 use super::Color::*;
 use super::Paint;
 
-/// Ensures tests are running serially.
-static SERIAL: Mutex<()> = Mutex::new(());
+/// Ensures tests are running serially. Held by any test changing whether styling is enabled.
+pub(crate) static SERIAL: Mutex<()> = Mutex::new(());
 
 #[test]
 fn colors_enabled() {
@@ -278,3 +278,58 @@ fn wrapping() {
             .to_string()
     );
 }
+
+#[test]
+fn color_depth_from_env() {
+    use super::ColorDepth;
+
+    assert_eq!(
+        ColorDepth::from_env(true, Some("xterm")),
+        ColorDepth::Truecolor
+    );
+    assert_eq!(ColorDepth::from_env(true, None), ColorDepth::Truecolor);
+    assert_eq!(
+        ColorDepth::from_env(false, Some("xterm-256color")),
+        ColorDepth::Fixed
+    );
+    assert_eq!(
+        ColorDepth::from_env(false, Some("xterm")),
+        ColorDepth::Basic
+    );
+    assert_eq!(ColorDepth::from_env(false, None), ColorDepth::Basic);
+}
+
+#[test]
+fn color_degradation() {
+    use super::ColorDepth;
+
+    // Colors the terminal supports are left as-is.
+    assert_eq!(
+        RGB(70, 130, 180).degrade(ColorDepth::Truecolor),
+        RGB(70, 130, 180)
+    );
+    assert_eq!(Fixed(100).degrade(ColorDepth::Truecolor), Fixed(100));
+    assert_eq!(Fixed(100).degrade(ColorDepth::Fixed), Fixed(100));
+    assert_eq!(Red.degrade(ColorDepth::Basic), Red);
+    assert_eq!(Unset.degrade(ColorDepth::Basic), Unset);
+
+    // RGB colors degrade to the closest palette color.
+    assert_eq!(RGB(255, 0, 0).degrade(ColorDepth::Fixed), Fixed(196));
+    assert_eq!(RGB(0, 0, 0).degrade(ColorDepth::Fixed), Fixed(16));
+    assert_eq!(RGB(95, 135, 175).degrade(ColorDepth::Fixed), Fixed(67));
+    assert_eq!(RGB(128, 128, 128).degrade(ColorDepth::Fixed), Fixed(244));
+
+    // And to the closest basic color.
+    assert_eq!(RGB(250, 10, 10).degrade(ColorDepth::Basic), Red);
+    assert_eq!(RGB(10, 200, 30).degrade(ColorDepth::Basic), Green);
+    assert_eq!(RGB(20, 20, 20).degrade(ColorDepth::Basic), Black);
+    assert_eq!(RGB(240, 240, 240).degrade(ColorDepth::Basic), White);
+    assert_eq!(RGB(0, 190, 200).degrade(ColorDepth::Basic), Cyan);
+
+    // Palette colors degrade to basic colors.
+    assert_eq!(Fixed(1).degrade(ColorDepth::Basic), Red);
+    assert_eq!(Fixed(12).degrade(ColorDepth::Basic), Blue);
+    assert_eq!(Fixed(196).degrade(ColorDepth::Basic), Red);
+    assert_eq!(Fixed(226).degrade(ColorDepth::Basic), Yellow);
+    assert_eq!(Fixed(232).degrade(ColorDepth::Basic), Black);
+}
@@ -103,6 +103,18 @@ pub fn viewport() -> Option<Size> {
         .ok()
 }
 
+/// Move the cursor up by the given number of lines and clear everything below it, so that
+/// these lines can be redrawn in place.
+pub fn erase_lines(lines: usize) {
+    if lines > 0 {
+        print!(
+            "{}\r{}",
+            termion::cursor::Up(lines as u16),
+            termion::clear::AfterCursor
+        );
+    }
+}
+
 pub fn headline(headline: impl fmt::Display) {
     println!();
     println!("{}", style(headline).bold());
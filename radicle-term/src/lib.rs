@@ -16,15 +16,18 @@ pub mod vstack;
 use std::fmt;
 use std::io::IsTerminal;
 
-pub use ansi::Color;
 pub use ansi::{paint, Filled, Paint, Style};
+pub use ansi::{Color, ColorDepth, ColorMode};
 pub use editor::Editor;
 pub use element::{Constraint, Element, Line, Size};
 pub use hstack::HStack;
 pub use inquire::ui::Styled;
 pub use io::*;
 pub use label::{label, Label};
-pub use spinner::{spinner, spinner_to, Spinner};
+pub use spinner::{
+    multi_spinner, multi_spinner_to, progress, progress_to, quiet_spinner_to, spinner, spinner_to,
+    spinner_to_with_style, spinner_with_style, MultiSpinner, Spinner, SpinnerRow, SpinnerStyle,
+};
 pub use table::{Table, TableOptions};
 pub use textarea::{textarea, TextArea};
 pub use vstack::{VStack, VStackOptions};
@@ -56,6 +59,12 @@ impl Interactive {
             true
         }
     }
+
+    /// Ask whether to retry something that failed. When not interactive, this never
+    /// retries, and doesn't wait for input.
+    pub fn retry(&self, prompt: impl fmt::Display) -> bool {
+        self.yes() && confirm(prompt)
+    }
 }
 
 impl From<Interactive> for bool {
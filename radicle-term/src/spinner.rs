@@ -1,6 +1,7 @@
 use std::io::{IsTerminal, Write};
 use std::mem::ManuallyDrop;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::{fmt, io, thread, time};
 
 use crate::io::{ERROR_PREFIX, WARNING_PREFIX};
@@ -16,23 +17,118 @@ pub const DEFAULT_STYLE: [Paint<&'static str>; 4] = [
     Paint::blue("◥"),
 ];
 
+/// The look of a spinner's animation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpinnerStyle {
+    /// The animation frames, shown in turn. If there are none, the default frames are used.
+    pub frames: Vec<Paint<&'static str>>,
+    /// How much time to wait between frames.
+    pub tick: time::Duration,
+}
+
+impl Default for SpinnerStyle {
+    fn default() -> Self {
+        Self {
+            frames: DEFAULT_STYLE.to_vec(),
+            tick: DEFAULT_TICK,
+        }
+    }
+}
+
+impl SpinnerStyle {
+    /// A plain ASCII style, for terminals that don't render the default frames well.
+    pub fn dots() -> Self {
+        Self {
+            frames: [".  ", ".. ", "...", " ..", "  .", "   "]
+                .into_iter()
+                .map(Paint::new)
+                .collect(),
+            tick: time::Duration::from_millis(150),
+        }
+    }
+}
+
+/// Width of a progress bar, not including its brackets. See [`progress`].
+pub const PROGRESS_BAR_WIDTH: usize = 20;
+
+/// Maximum number of message characters included in a spinner's thread name.
+const THREAD_NAME_MESSAGE_LEN: usize = 32;
+
+/// Sequence number of the next spinner thread.
+static SPINNER_SEQ: AtomicUsize = AtomicUsize::new(0);
+
 struct Progress {
     state: State,
     message: Paint<String>,
+    /// Completed steps, with the prefix to print them with.
+    steps: Vec<(Paint<&'static str>, Paint<String>)>,
+    /// Total amount of work, if known. When set, a progress bar is shown instead of the
+    /// animation frames.
+    total: Option<usize>,
+    /// Amount of work done, out of the total.
+    current: usize,
+    /// When the spinner was created.
+    started: time::Instant,
+    /// Whether the elapsed time is shown in the completion message.
+    timed: bool,
+    /// Number of times the spinner thread went over the progress, eg. to draw a frame.
+    ticks: usize,
 }
 
 impl Progress {
-    fn new(message: Paint<String>) -> Self {
+    fn new(message: Paint<String>, total: Option<usize>, timed: bool) -> Self {
         Self {
             state: State::Running { cursor: 0 },
             message,
+            steps: Vec::new(),
+            total,
+            current: 0,
+            started: time::Instant::now(),
+            timed,
+            ticks: 0,
+        }
+    }
+
+    /// The elapsed time to append to the completion message, if any, eg. ` (4.2s)`.
+    fn elapsed(&self) -> String {
+        if self.timed {
+            let elapsed = Paint::new(format!("({})", format_elapsed(self.started.elapsed())));
+            format!(" {}", elapsed.dim())
+        } else {
+            String::new()
+        }
+    }
+
+    /// The line printed once the work is over, or `None` if the work is still running or
+    /// the line was cleared.
+    fn outcome(&self) -> Option<String> {
+        let message = &self.message;
+
+        match self.state {
+            State::Running { .. } | State::Paused { .. } | State::Cleared => None,
+            State::Done => Some(format!("{} {message}{}", Paint::green("✓"), self.elapsed())),
+            State::Canceled => Some(format!(
+                "{ERROR_PREFIX} {message} {}",
+                Paint::red("<canceled>")
+            )),
+            State::Warn => Some(format!("{WARNING_PREFIX} {message}")),
+            State::Error => Some(format!("{ERROR_PREFIX} {message}{}", self.elapsed())),
         }
     }
 }
 
 enum State {
-    Running { cursor: usize },
+    Running {
+        cursor: usize,
+    },
+    /// The animation is hidden, until the spinner is resumed. Set once the animation was
+    /// cleared by the spinner thread.
+    Paused {
+        cursor: usize,
+        cleared: bool,
+    },
     Canceled,
+    Cleared,
     Done,
     Warn,
     Error,
@@ -41,16 +137,22 @@ enum State {
 /// A progress spinner.
 pub struct Spinner {
     progress: Arc<Mutex<Progress>>,
+    /// Notified by the spinner thread each time it went over the progress, eg. once it
+    /// cleared the animation of a paused spinner.
+    ticked: Arc<Condvar>,
     handle: ManuallyDrop<thread::JoinHandle<()>>,
 }
 
 impl Drop for Spinner {
     fn drop(&mut self) {
         if let Ok(mut progress) = self.progress.lock() {
-            if let State::Running { .. } = progress.state {
+            if let State::Running { .. } | State::Paused { .. } = progress.state {
                 progress.state = State::Canceled;
             }
         }
+        // Wake the thread up, so that it prints the outcome without waiting for the next tick.
+        self.handle.thread().unpark();
+
         unsafe { ManuallyDrop::take(&mut self.handle) }
             .join()
             .unwrap();
@@ -65,6 +167,14 @@ impl Spinner {
         }
     }
 
+    /// Remove the spinner without printing a completion message. Steps that were already
+    /// marked as completed are kept.
+    pub fn clear(self) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.state = State::Cleared;
+        }
+    }
+
     /// Mark the spinner as failed. This cancels the spinner.
     pub fn failed(self) {
         if let Ok(mut progress) = self.progress.lock() {
@@ -92,6 +202,61 @@ impl Spinner {
         }
     }
 
+    /// Mark an intermediate step as completed. The step is printed as a persistent line above
+    /// the animation, which keeps running with the current message.
+    pub fn step_done(&mut self, msg: impl fmt::Display) {
+        let msg = msg.to_string();
+
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.steps.push((Paint::green("✓"), Paint::new(msg)));
+        }
+    }
+
+    /// Mark an intermediate step as failed. Like [`Spinner::step_done`], the step is printed
+    /// as a persistent line above the animation, which keeps running.
+    pub fn step_failed(&mut self, msg: impl fmt::Display) {
+        let msg = msg.to_string();
+
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.steps.push((ERROR_PREFIX, Paint::new(msg)));
+        }
+    }
+
+    /// Hide the spinner, eg. to prompt the user, until [`Spinner::resume`] is called. The
+    /// animation is cleared by the time this returns, so that it doesn't draw over what is
+    /// printed next. Completed steps are printed once the spinner is resumed.
+    pub fn pause(&mut self) {
+        let Ok(mut progress) = self.progress.lock() else {
+            return;
+        };
+        let State::Running { cursor } = progress.state else {
+            return;
+        };
+        progress.state = State::Paused {
+            cursor,
+            cleared: false,
+        };
+        self.handle.thread().unpark();
+
+        // Wait for the spinner thread to clear the animation.
+        self.ticked
+            .wait_while(progress, |p| {
+                matches!(p.state, State::Paused { cleared: false, .. })
+            })
+            .ok();
+    }
+
+    /// Show the spinner again after [`Spinner::pause`], with its current message.
+    pub fn resume(&mut self) {
+        if let Ok(mut progress) = self.progress.lock() {
+            if let State::Paused { cursor, .. } = progress.state {
+                progress.state = State::Running { cursor };
+            }
+        }
+        // Redraw the animation right away.
+        self.handle.thread().unpark();
+    }
+
     /// Set the spinner's message.
     pub fn message(&mut self, msg: impl fmt::Display) {
         let msg = msg.to_string();
@@ -100,32 +265,165 @@ impl Spinner {
             progress.message = Paint::new(msg);
         }
     }
+
+    /// Set the amount of work done, out of the total. See [`progress`].
+    pub fn set_progress(&mut self, current: usize) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.current = current;
+        }
+    }
+
+    /// Set the total amount of work, once it is known. Setting it to `None` shows the
+    /// animation frames again.
+    pub fn set_total(&mut self, total: Option<usize>) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.total = total;
+        }
+    }
+
+    /// Show the elapsed time when the spinner completes.
+    pub fn show_elapsed(&mut self) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.timed = true;
+        }
+    }
+
+    /// Don't show the elapsed time when the spinner completes.
+    pub fn hide_elapsed(&mut self) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.timed = false;
+        }
+    }
 }
 
 /// Create a new spinner with the given message. Sends animation output to `stderr` and success or
 /// failure messages to `stdout`.
+///
+/// When the spinner finishes or fails, the time elapsed since it was created is appended to the
+/// completion message, unless [`Spinner::hide_elapsed`] is used. If `stderr` isn't a terminal,
+/// the elapsed time isn't shown, so that the output stays the same from one run to the next.
 pub fn spinner(message: impl ToString) -> Spinner {
+    spinner_with_style(message, SpinnerStyle::default())
+}
+
+/// Create a new spinner with the given message and animation style. Output is sent as with
+/// [`spinner`].
+pub fn spinner_with_style(message: impl ToString, style: SpinnerStyle) -> Spinner {
     let (stdout, stderr) = (io::stdout(), io::stderr());
 
     if stderr.is_terminal() {
-        spinner_to(message, stdout, stderr)
+        let mut spinner = spinner_to_with_style(message, style, stdout, stderr);
+        spinner.show_elapsed();
+        spinner
     } else {
-        spinner_to(message, stdout, io::sink())
+        spinner_to_with_style(message, style, stdout, io::sink())
     }
 }
 
 /// Create a new spinner with the given message, and send output to the given writers.
+///
+/// Since the writers usually aren't terminals, the elapsed time isn't shown when the spinner
+/// completes, unless [`Spinner::show_elapsed`] is used.
 pub fn spinner_to(
     message: impl ToString,
+    completion: impl io::Write + Send + 'static,
+    animation: impl io::Write + Send + 'static,
+) -> Spinner {
+    spinner_to_with_style(message, SpinnerStyle::default(), completion, animation)
+}
+
+/// Create a new spinner with the given message and animation style, and send output to the
+/// given writers. See [`spinner_to`].
+pub fn spinner_to_with_style(
+    message: impl ToString,
+    style: SpinnerStyle,
+    completion: impl io::Write + Send + 'static,
+    animation: impl io::Write + Send + 'static,
+) -> Spinner {
+    spawn(message, style, None, completion, animation, None)
+}
+
+/// Create a new spinner with the given message, for commands asked to be quiet. The animation
+/// isn't drawn, and success messages and completed steps are sent to `completion`, usually
+/// [`io::sink`]. Errors, warnings and cancelations are still sent to `stderr`, so that failures
+/// aren't hidden.
+pub fn quiet_spinner_to(
+    message: impl ToString,
+    completion: impl io::Write + Send + 'static,
+) -> Spinner {
+    let mut spinner = spawn(
+        message,
+        SpinnerStyle::default(),
+        None,
+        completion,
+        io::sink(),
+        Some(Box::new(io::stderr())),
+    );
+    if io::stderr().is_terminal() {
+        spinner.show_elapsed();
+    }
+    spinner
+}
+
+/// Create a new progress bar with the given message, for work whose total amount is known,
+/// eg. `[####----] 4/8 Fetching..`. Progress is updated with [`Spinner::set_progress`].
+/// If the total isn't known, the spinner animation is shown until it is set with
+/// [`Spinner::set_total`]. Output is sent as with [`spinner`].
+pub fn progress(message: impl ToString, total: Option<usize>) -> Spinner {
+    let (stdout, stderr) = (io::stdout(), io::stderr());
+
+    if stderr.is_terminal() {
+        let mut spinner = progress_to(message, total, stdout, stderr);
+        spinner.show_elapsed();
+        spinner
+    } else {
+        progress_to(message, total, stdout, io::sink())
+    }
+}
+
+/// Create a new progress bar with the given message, and send output to the given writers.
+/// See [`progress`] and [`spinner_to`].
+pub fn progress_to(
+    message: impl ToString,
+    total: Option<usize>,
+    completion: impl io::Write + Send + 'static,
+    animation: impl io::Write + Send + 'static,
+) -> Spinner {
+    spawn(
+        message,
+        SpinnerStyle::default(),
+        total,
+        completion,
+        animation,
+        None,
+    )
+}
+
+/// Spawn the thread drawing a spinner or progress bar. Errors, warnings and cancelations are
+/// sent to `failure` if given, and to `completion` otherwise.
+fn spawn(
+    message: impl ToString,
+    style: SpinnerStyle,
+    total: Option<usize>,
     mut completion: impl io::Write + Send + 'static,
     animation: impl io::Write + Send + 'static,
+    mut failure: Option<Box<dyn io::Write + Send>>,
 ) -> Spinner {
+    let SpinnerStyle { frames, tick } = style;
+    let frames = if frames.is_empty() {
+        DEFAULT_STYLE.to_vec()
+    } else {
+        frames
+    };
     let message = message.to_string();
-    let progress = Arc::new(Mutex::new(Progress::new(Paint::new(message))));
+    let name = thread_name(SPINNER_SEQ.fetch_add(1, Ordering::Relaxed), &message);
+    let progress = Arc::new(Mutex::new(Progress::new(Paint::new(message), total, false)));
+    let ticked = Arc::new(Condvar::new());
     let handle = thread::Builder::new()
-        .name(String::from("spinner"))
+        .name(name)
         .spawn({
             let progress = progress.clone();
+            let ticked = ticked.clone();
 
             move || {
                 let mut animation = termion::cursor::HideCursor::from(animation);
@@ -134,12 +432,53 @@ pub fn spinner_to(
                     let Ok(mut progress) = progress.lock() else {
                         break;
                     };
+                    if let State::Paused { cleared, .. } = &mut progress.state {
+                        if !*cleared {
+                            write!(animation, "{}", termion::clear::AfterCursor).ok();
+                            animation.flush().ok();
+                            *cleared = true;
+                        }
+                        progress.ticks += 1;
+                        drop(progress);
+                        ticked.notify_all();
+                        thread::park_timeout(tick);
+
+                        continue;
+                    }
+                    for (prefix, step) in progress.steps.drain(..) {
+                        write!(animation, "{}", termion::clear::AfterCursor).ok();
+                        writeln!(completion, "{prefix} {step}").ok();
+                    }
+                    let elapsed = match progress.state {
+                        State::Done | State::Error => progress.elapsed(),
+                        _ => String::new(),
+                    };
+                    let failure: &mut dyn io::Write = match &mut failure {
+                        Some(failure) => failure,
+                        None => &mut completion,
+                    };
                     match &mut *progress {
+                        Progress {
+                            state: State::Running { .. },
+                            message,
+                            total: Some(total),
+                            current,
+                            ..
+                        } => {
+                            write!(
+                                animation,
+                                "{}{} {message}\r",
+                                termion::clear::AfterCursor,
+                                bar(*current, *total),
+                            )
+                            .ok();
+                        }
                         Progress {
                             state: State::Running { cursor },
                             message,
+                            ..
                         } => {
-                            let spinner = DEFAULT_STYLE[*cursor];
+                            let spinner = frames[*cursor];
 
                             write!(
                                 animation,
@@ -149,23 +488,38 @@ pub fn spinner_to(
                             .ok();
 
                             *cursor += 1;
-                            *cursor %= DEFAULT_STYLE.len();
+                            *cursor %= frames.len();
+                        }
+                        Progress {
+                            state: State::Paused { .. },
+                            ..
+                        } => {
+                            // Handled above.
+                        }
+                        Progress {
+                            state: State::Cleared,
+                            ..
+                        } => {
+                            write!(animation, "{}", termion::clear::AfterCursor).ok();
+                            break;
                         }
                         Progress {
                             state: State::Done,
                             message,
+                            ..
                         } => {
                             write!(animation, "{}", termion::clear::AfterCursor).ok();
-                            writeln!(completion, "{} {message}", Paint::green("✓")).ok();
+                            writeln!(completion, "{} {message}{elapsed}", Paint::green("✓")).ok();
                             break;
                         }
                         Progress {
                             state: State::Canceled,
                             message,
+                            ..
                         } => {
                             write!(animation, "{}", termion::clear::AfterCursor).ok();
                             writeln!(
-                                completion,
+                                failure,
                                 "{ERROR_PREFIX} {message} {}",
                                 Paint::red("<canceled>")
                             )
@@ -175,28 +529,512 @@ pub fn spinner_to(
                         Progress {
                             state: State::Warn,
                             message,
+                            ..
                         } => {
-                            writeln!(completion, "{WARNING_PREFIX} {message}").ok();
+                            writeln!(failure, "{WARNING_PREFIX} {message}").ok();
                             break;
                         }
                         Progress {
                             state: State::Error,
                             message,
+                            ..
                         } => {
-                            writeln!(completion, "{ERROR_PREFIX} {message}").ok();
+                            writeln!(failure, "{ERROR_PREFIX} {message}{elapsed}").ok();
                             break;
                         }
                     }
+                    progress.ticks += 1;
                     drop(progress);
-                    thread::sleep(DEFAULT_TICK);
+                    ticked.notify_all();
+                    // Wait for the next frame, unless woken up by the spinner completing.
+                    thread::park_timeout(tick);
                 }
             }
         })
-        // SAFETY: Only panics if the thread name contains `null` bytes, which isn't the case here.
+        // SAFETY: Only panics if the thread name contains `null` bytes, which are stripped
+        // by `thread_name`.
         .unwrap();
 
     Spinner {
         progress,
+        ticked,
         handle: ManuallyDrop::new(handle),
     }
 }
+
+/// Render a progress bar, eg. `[##########----------] 4/8`.
+fn bar(current: usize, total: usize) -> String {
+    let filled = if total == 0 {
+        PROGRESS_BAR_WIDTH
+    } else {
+        current.min(total) * PROGRESS_BAR_WIDTH / total
+    };
+    format!(
+        "[{}{}] {current}/{total}",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_BAR_WIDTH - filled)
+    )
+}
+
+/// The rows of a [`MultiSpinner`].
+struct Rows {
+    rows: Vec<Progress>,
+    /// Number of leading rows whose completion line was printed.
+    flushed: usize,
+    /// Whether the elapsed time is shown for new rows.
+    timed: bool,
+    /// Set when the spinner is dropped. No rows can be added anymore.
+    closed: bool,
+}
+
+/// Several spinners drawn together as a block, one row each, eg. for work done in parallel.
+/// All rows are animated by a single thread.
+///
+/// Completion lines are printed in the order the rows were added: a row that completes
+/// before the rows above it keeps its place in the block until they have completed too.
+pub struct MultiSpinner {
+    rows: Arc<Mutex<Rows>>,
+    handle: ManuallyDrop<thread::JoinHandle<()>>,
+}
+
+impl Drop for MultiSpinner {
+    fn drop(&mut self) {
+        if let Ok(mut rows) = self.rows.lock() {
+            for row in rows.rows.iter_mut() {
+                if let State::Running { .. } = row.state {
+                    row.state = State::Canceled;
+                }
+            }
+            rows.closed = true;
+        }
+        self.handle.thread().unpark();
+
+        unsafe { ManuallyDrop::take(&mut self.handle) }
+            .join()
+            .unwrap();
+    }
+}
+
+impl MultiSpinner {
+    /// Add a row with the given message, below the existing rows.
+    pub fn add(&self, message: impl ToString) -> SpinnerRow {
+        let mut rows = self.rows.lock().unwrap_or_else(|e| e.into_inner());
+        let row = Progress::new(Paint::new(message.to_string()), None, rows.timed);
+        rows.rows.push(row);
+
+        SpinnerRow {
+            rows: self.rows.clone(),
+            index: rows.rows.len() - 1,
+        }
+    }
+
+    /// Show the elapsed time when rows complete. See [`Spinner::show_elapsed`].
+    pub fn show_elapsed(&mut self) {
+        if let Ok(mut rows) = self.rows.lock() {
+            rows.timed = true;
+            rows.rows.iter_mut().for_each(|row| row.timed = true);
+        }
+    }
+
+    /// Don't show the elapsed time when rows complete. See [`Spinner::hide_elapsed`].
+    pub fn hide_elapsed(&mut self) {
+        if let Ok(mut rows) = self.rows.lock() {
+            rows.timed = false;
+            rows.rows.iter_mut().for_each(|row| row.timed = false);
+        }
+    }
+}
+
+/// A row of a [`MultiSpinner`]. Like a [`Spinner`], a row that is dropped while running is
+/// canceled.
+pub struct SpinnerRow {
+    rows: Arc<Mutex<Rows>>,
+    index: usize,
+}
+
+impl Drop for SpinnerRow {
+    fn drop(&mut self) {
+        self.update(|row| {
+            if let State::Running { .. } = row.state {
+                row.state = State::Canceled;
+            }
+        });
+    }
+}
+
+impl SpinnerRow {
+    /// Mark the row as successfully completed.
+    pub fn finish(self) {
+        self.update(|row| row.state = State::Done);
+    }
+
+    /// Mark the row as failed.
+    pub fn failed(self) {
+        self.update(|row| row.state = State::Error);
+    }
+
+    /// Mark the row as failed with an error.
+    pub fn error(self, msg: impl fmt::Display) {
+        self.update(|row| {
+            row.state = State::Error;
+            row.message = Paint::new(format!("{} {} {}", row.message, Paint::red("error:"), msg));
+        });
+    }
+
+    /// Mark the row with a warning sign.
+    pub fn warn(self) {
+        self.update(|row| row.state = State::Warn);
+    }
+
+    /// Set the row's message.
+    pub fn message(&mut self, msg: impl fmt::Display) {
+        let msg = msg.to_string();
+
+        self.update(|row| row.message = Paint::new(msg));
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Progress)) {
+        if let Ok(mut rows) = self.rows.lock() {
+            if let Some(row) = rows.rows.get_mut(self.index) {
+                f(row);
+            }
+        }
+    }
+}
+
+/// Create a new spinner with several rows, added with [`MultiSpinner::add`]. Output is sent
+/// as with [`spinner`].
+pub fn multi_spinner() -> MultiSpinner {
+    let (stdout, stderr) = (io::stdout(), io::stderr());
+
+    if stderr.is_terminal() {
+        let mut spinner = multi_spinner_to(stdout, stderr);
+        spinner.show_elapsed();
+        spinner
+    } else {
+        multi_spinner_to(stdout, io::sink())
+    }
+}
+
+/// Create a new spinner with several rows, and send output to the given writers. If the
+/// animation isn't drawn, eg. when sent to [`io::sink`], only the completion lines are
+/// printed, in order. As with [`spinner_to`], the elapsed time isn't shown by default.
+pub fn multi_spinner_to(
+    mut completion: impl io::Write + Send + 'static,
+    animation: impl io::Write + Send + 'static,
+) -> MultiSpinner {
+    let rows = Arc::new(Mutex::new(Rows {
+        rows: Vec::new(),
+        flushed: 0,
+        timed: false,
+        closed: false,
+    }));
+    let handle = thread::Builder::new()
+        .name(format!(
+            "spinner-{}",
+            SPINNER_SEQ.fetch_add(1, Ordering::Relaxed)
+        ))
+        .spawn({
+            let rows = rows.clone();
+            let frames = DEFAULT_STYLE;
+
+            move || {
+                let mut animation = termion::cursor::HideCursor::from(animation);
+                // Number of lines of the block drawn on the last tick.
+                let mut drawn: u16 = 0;
+                let mut cursor = 0;
+
+                loop {
+                    let Ok(mut guard) = rows.lock() else {
+                        break;
+                    };
+                    let Rows {
+                        rows,
+                        flushed,
+                        closed,
+                        ..
+                    } = &mut *guard;
+
+                    // Go back to the top of the block, to redraw it.
+                    if drawn > 0 {
+                        write!(animation, "{}\r", termion::cursor::Up(drawn)).ok();
+                    }
+                    write!(animation, "{}", termion::clear::AfterCursor).ok();
+
+                    // Print the completed rows at the top of the block, which then moves down.
+                    for row in &rows[*flushed..] {
+                        if let State::Running { .. } = row.state {
+                            break;
+                        }
+                        if let Some(line) = row.outcome() {
+                            writeln!(completion, "{line}").ok();
+                        }
+                        *flushed += 1;
+                    }
+                    if *closed && *flushed == rows.len() {
+                        break;
+                    }
+
+                    drawn = 0;
+                    for row in &rows[*flushed..] {
+                        let line = match row.state {
+                            State::Running { .. } => {
+                                format!("{} {}", frames[cursor], row.message)
+                            }
+                            _ => match row.outcome() {
+                                Some(line) => line,
+                                None => continue,
+                            },
+                        };
+                        writeln!(animation, "{line}").ok();
+                        drawn += 1;
+                    }
+                    cursor = (cursor + 1) % frames.len();
+
+                    drop(guard);
+                    thread::park_timeout(DEFAULT_TICK);
+                }
+            }
+        })
+        // SAFETY: Only panics if the thread name contains `null` bytes.
+        .unwrap();
+
+    MultiSpinner {
+        rows,
+        handle: ManuallyDrop::new(handle),
+    }
+}
+
+/// Format an elapsed duration for humans, eg. `350ms` or `4.2s`.
+fn format_elapsed(elapsed: time::Duration) -> String {
+    if elapsed < time::Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
+/// Build the name of a spinner thread, eg. `spinner-3: Fetching rad:z3gqc..`.
+///
+/// The sequence number comes first, since some platforms truncate thread names, eg. Linux
+/// keeps only the first 15 bytes. Control characters, including `null` bytes, are removed
+/// from the message.
+fn thread_name(seq: usize, message: &str) -> String {
+    let mut name = format!("spinner-{seq}");
+    let mut chars = message.chars().filter(|c| !c.is_control());
+    let truncated = chars
+        .by_ref()
+        .take(THREAD_NAME_MESSAGE_LEN)
+        .collect::<String>();
+
+    if !truncated.is_empty() {
+        name.push_str(": ");
+        name.push_str(truncated.trim_end());
+
+        if chars.next().is_some() {
+            name.push_str("..");
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(0, 8), format!("[{}] 0/8", "-".repeat(20)));
+        assert_eq!(
+            bar(4, 8),
+            format!("[{}{}] 4/8", "#".repeat(10), "-".repeat(10))
+        );
+        assert_eq!(bar(8, 8), format!("[{}] 8/8", "#".repeat(20)));
+        // Progress past the total doesn't overflow the bar.
+        assert_eq!(bar(9, 8), format!("[{}] 9/8", "#".repeat(20)));
+        assert_eq!(bar(0, 0), format!("[{}] 0/0", "#".repeat(20)));
+    }
+
+    /// A writer whose output can be inspected while it is used by a spinner.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Buffer {
+        fn len(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A style whose frames are only drawn when the spinner thread is woken up, eg. by [`tick`].
+    fn manual() -> SpinnerStyle {
+        SpinnerStyle {
+            tick: time::Duration::from_secs(60 * 60),
+            ..SpinnerStyle::default()
+        }
+    }
+
+    /// Wake the spinner thread up, and wait for it to go over the progress once.
+    fn tick(spinner: &Spinner) {
+        let progress = spinner.progress.lock().unwrap();
+        let ticks = progress.ticks;
+
+        spinner.handle.thread().unpark();
+        drop(
+            spinner
+                .ticked
+                .wait_while(progress, |p| p.ticks == ticks)
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_pause() {
+        let animation = Buffer::default();
+        let mut spinner =
+            spinner_to_with_style("Fetching..", manual(), io::sink(), animation.clone());
+        tick(&spinner);
+        assert!(animation.contents().contains("Fetching.."));
+
+        spinner.pause();
+        let len = animation.len();
+        // Nothing is drawn while paused, but the message can be changed.
+        spinner.message("Fetching again..");
+        tick(&spinner);
+        tick(&spinner);
+        assert_eq!(animation.len(), len);
+
+        spinner.resume();
+        tick(&spinner);
+        assert!(animation.contents()[len..].contains("Fetching again.."));
+
+        spinner.finish();
+    }
+
+    #[test]
+    fn test_failure_writer() {
+        let completion = Buffer::default();
+        let failure = Buffer::default();
+        let spinner = |message: &str| {
+            spawn(
+                message,
+                SpinnerStyle::default(),
+                None,
+                completion.clone(),
+                io::sink(),
+                Some(Box::new(failure.clone())),
+            )
+        };
+        spinner("Fetched").finish();
+        spinner("Announced").error("timed out");
+
+        // Only errors are sent to the failure writer.
+        assert!(completion.contents().contains("Fetched"));
+        assert!(!completion.contents().contains("Announced"));
+        assert!(failure.contents().contains("Announced error: timed out"));
+        assert!(!failure.contents().contains("Fetched"));
+    }
+
+    #[test]
+    fn test_color_never() {
+        let _guard = crate::ansi::tests::SERIAL.lock();
+        let completion = Buffer::default();
+        let spinner = |message: &str| spinner_to(message, completion.clone(), io::sink());
+
+        let mode = Paint::color_mode();
+        Paint::set_color(crate::ColorMode::Never);
+        spinner("Fetched").finish();
+        spinner("Announced").error("timed out");
+        spinner("Skipped").warn();
+        drop(spinner("Interrupted"));
+        Paint::set_color(mode);
+
+        let output = completion.contents();
+        assert_eq!(output.lines().count(), 4);
+        assert!(!output.contains('\x1b'), "{output:?}");
+    }
+
+    #[test]
+    fn test_finish_wakes_up() {
+        let completion = Buffer::default();
+        let spinner = spinner_to_with_style("Fetching..", manual(), completion.clone(), io::sink());
+        // The thread is waiting for the next frame, which is an hour away.
+        tick(&spinner);
+
+        // The thread is woken up, rather than finishing on the next frame.
+        spinner.finish();
+        assert!(completion.contents().contains("Fetching.."));
+    }
+
+    #[test]
+    fn test_multi_spinner() {
+        let completion = Buffer::default();
+        let spinner = multi_spinner_to(completion.clone(), io::sink());
+
+        let first = spinner.add("First");
+        let mut second = spinner.add("Second");
+        let third = spinner.add("Third");
+        let fourth = spinner.add("Fourth");
+
+        second.message("Second!");
+        second.finish();
+        first.error("oops");
+        fourth.warn();
+        drop(spinner);
+        drop(third);
+
+        let output = completion.contents();
+        // Styling may be forced by other tests, so escape sequences are removed.
+        let mut output = output.as_str();
+        let mut plain = String::new();
+        while let Some((text, rest)) = output.split_once('\x1b') {
+            plain.push_str(text);
+            output = rest.split_once('m').map_or("", |(_, rest)| rest);
+        }
+        plain.push_str(output);
+
+        // Rows are printed in order, and the rows still running are canceled.
+        assert_eq!(
+            plain.lines().collect::<Vec<_>>(),
+            vec![
+                "✗ First error: oops",
+                "✓ Second!",
+                "✗ Third <canceled>",
+                "! Fourth",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_elapsed() {
+        let completion = Buffer::default();
+        spinner_to("Fetched", completion.clone(), io::sink()).finish();
+        assert!(!completion.contents().contains('('));
+
+        let mut spinner = spinner_to("Fetched", completion.clone(), io::sink());
+        spinner.show_elapsed();
+        spinner.finish();
+        assert!(completion.contents().contains('('));
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(time::Duration::from_millis(0)), "0ms");
+        assert_eq!(format_elapsed(time::Duration::from_millis(999)), "999ms");
+        assert_eq!(format_elapsed(time::Duration::from_millis(1000)), "1.0s");
+        assert_eq!(format_elapsed(time::Duration::from_millis(4230)), "4.2s");
+    }
+}
@@ -17,6 +17,7 @@
 //! spider mite persimilis
 //! ```
 use std::fmt;
+use std::str::FromStr;
 
 use crate::cell::Cell;
 use crate::{self as term, Style};
@@ -54,6 +55,31 @@ impl TableOptions {
     }
 }
 
+/// Table output format.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Column-aligned, for humans.
+    #[default]
+    Table,
+    /// Comma-separated values, see [`Table::render_csv`].
+    Csv,
+    /// Tab-separated values, see [`Table::render_tsv`].
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            _ => Err("invalid format, expected one of: table, csv, tsv"),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum Row<const W: usize, T> {
     Header([T; W]),
@@ -196,6 +222,78 @@ impl<const W: usize, T: Cell> Table<W, T> {
         !self.rows.iter().any(|r| matches!(r, Row::Data { .. }))
     }
 
+    /// Render the table as comma-separated values, as per RFC 4180.
+    /// Styling and dividers are left out.
+    pub fn render_csv(&self) -> String {
+        self.render_separated(",", "\r\n", |field| {
+            if field.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+    }
+
+    /// Render the table as tab-separated values. Tabs, newlines and backslashes in fields
+    /// are escaped with a backslash. Styling and dividers are left out.
+    pub fn render_tsv(&self) -> String {
+        self.render_separated("\t", "\n", |field| {
+            field
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\r', "\\r")
+                .replace('\n', "\\n")
+        })
+    }
+
+    /// Render the table in the given format.
+    pub fn render_as(&self, format: Format) -> String
+    where
+        T: fmt::Debug,
+        T::Padded: Into<Line>,
+    {
+        match format {
+            Format::Table => self.display(Constraint::from_env().unwrap_or_default()),
+            Format::Csv => self.render_csv(),
+            Format::Tsv => self.render_tsv(),
+        }
+    }
+
+    /// Print the table to `stdout` in the given format.
+    pub fn print_as(&self, format: Format)
+    where
+        T: fmt::Debug,
+        T::Padded: Into<Line>,
+    {
+        match format {
+            Format::Table => self.print(),
+            Format::Csv | Format::Tsv => print!("{}", self.render_as(format)),
+        }
+    }
+
+    fn render_separated(
+        &self,
+        separator: &str,
+        terminator: &str,
+        escape: impl Fn(String) -> String,
+    ) -> String {
+        let mut out = String::new();
+
+        for row in &self.rows {
+            let (Row::Header(cells) | Row::Data(cells)) = row else {
+                continue;
+            };
+            let fields = cells
+                .iter()
+                .map(|c| escape(strip_ansi(&c.to_string())))
+                .collect::<Vec<_>>();
+
+            out.push_str(&fields.join(separator));
+            out.push_str(terminator);
+        }
+        out
+    }
+
     fn inner(&self, c: Constraint) -> Size {
         let mut outer = self.outer(c);
 
@@ -220,6 +318,28 @@ impl<const W: usize, T: Cell> Table<W, T> {
     }
 }
 
+/// Remove ANSI escape sequences from a string.
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a "Control Sequence Introducer" up to and including its final byte.
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod test {
     use crate::Element;
@@ -257,6 +377,60 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_table_csv() {
+        let mut t = Table::new(TableOptions::bordered());
+
+        t.header(["Name", "Note"]);
+        t.divider();
+        t.push(["pineapple", "sweet, sour"]);
+        t.push(["apple", "the \"forbidden\" fruit"]);
+        t.push(["pear", "line\nbreak"]);
+
+        assert_eq!(
+            t.render_csv(),
+            [
+                "Name,Note\r\n",
+                "pineapple,\"sweet, sour\"\r\n",
+                "apple,\"the \"\"forbidden\"\" fruit\"\r\n",
+                "pear,\"line\nbreak\"\r\n",
+            ]
+            .join("")
+        );
+    }
+
+    #[test]
+    fn test_table_tsv() {
+        let mut t = Table::new(TableOptions::default());
+
+        t.push(["pineapple", "sweet\tsour"]);
+        t.push(["pear", "line\nbreak"]);
+        t.push(["apple", "back\\slash"]);
+
+        assert_eq!(
+            t.render_tsv(),
+            [
+                "pineapple\tsweet\\tsour\n",
+                "pear\tline\\nbreak\n",
+                "apple\tback\\\\slash\n",
+            ]
+            .join("")
+        );
+    }
+
+    #[test]
+    fn test_table_csv_styled() {
+        let mut t = Table::new(TableOptions::default());
+
+        t.push([
+            Paint::new(String::from("red")).fg(Color::Red),
+            Paint::new(String::from("plain")),
+        ]);
+
+        assert_eq!(strip_ansi("\x1b[31mred\x1b[0m"), "red");
+        assert_eq!(t.render_csv(), "red,plain\r\n");
+    }
+
     #[test]
     fn test_table_border() {
         let mut t = Table::new(TableOptions {
@@ -12,3 +12,16 @@ impl Default for Config {
         Self { hints: true }
     }
 }
+
+/// Sync configuration, used by `rad sync` and commands that sync.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfig {
+    /// Default number of seconds to wait while syncing, when no timeout is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Number of seconds a failed fetch from a seed is remembered, during which the seed is
+    /// tried after the others.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_health_expiry: Option<u64>,
+}
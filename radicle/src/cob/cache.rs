@@ -23,7 +23,14 @@ const DB_WRITE_TIMEOUT: time::Duration = time::Duration::from_secs(6);
 
 /// Database migrations.
 /// The first migration is the creation of the initial tables.
-const MIGRATIONS: &[&str] = &[include_str!("cache/migrations/1.sql")];
+const MIGRATIONS: &[&str] = &[
+    include_str!("cache/migrations/1.sql"),
+    include_str!("cache/migrations/2.sql"),
+];
+
+/// Current version of the database schema, ie. the number of migrations applied.
+/// Version `2` adds the summary index of [`crate::cob::store::IndexedStore`].
+pub const VERSION: usize = 2;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -312,3 +319,47 @@ impl WriteAllProgress {
         (self.seen as f32 / self.total as f32) * 100.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_version() {
+        assert_eq!(MIGRATIONS.len(), VERSION);
+
+        let db = StoreWriter::memory().unwrap();
+        assert_eq!(db.version().unwrap(), VERSION);
+    }
+
+    #[test]
+    fn test_migrate_summaries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(COBS_DB_FILE);
+
+        // A database created before the summary index existed.
+        {
+            let db = sql::Connection::open(&path).unwrap();
+            db.execute(MIGRATIONS[0]).unwrap();
+            bump(&db).unwrap();
+            db.execute("INSERT INTO issues (id, repo, issue) VALUES ('a', 'b', '{}')")
+                .unwrap();
+        }
+        let db = StoreWriter::open(&path).unwrap();
+        assert_eq!(db.version().unwrap(), 2);
+
+        db.raw_query(|db| {
+            // Existing entries are kept, and the index is empty.
+            let issues = db.prepare("SELECT id FROM issues")?.into_iter().count();
+            let summaries = db.prepare("SELECT id FROM summaries")?.into_iter().count();
+            assert_eq!((issues, summaries), (1, 0));
+
+            Ok::<_, Error>(())
+        })
+        .unwrap();
+
+        // Opening it again doesn't migrate it again.
+        drop(db);
+        assert_eq!(StoreWriter::open(&path).unwrap().version().unwrap(), 2);
+    }
+}
@@ -0,0 +1,14 @@
+-- Index of collaborative object summaries
+create table if not exists "summaries" (
+  -- Repository ID
+  "repo"          text      not null,
+  -- Object type name
+  "type"          text      not null,
+  -- Object ID
+  "id"            text      not null,
+  -- Tips of the object history across all remotes, sorted and comma-separated
+  "heads"         text      not null,
+  -- Object summary in JSON format
+  "summary"       text      not null,
+  primary key ("repo", "type", "id")
+) strict;
@@ -14,7 +14,7 @@ use crate::{
     cob,
     cob::{
         op, store,
-        store::{Cob, CobAction, Transaction},
+        store::{Cob, CobAction, Effect, Transaction},
         ActorId, Timestamp,
     },
     identity::{
@@ -360,13 +360,14 @@ impl store::Cob for Identity {
         op: Op,
         concurrent: I,
         repo: &R,
-    ) -> Result<(), ApplyError> {
+    ) -> Result<Vec<Effect>, ApplyError> {
         let id = op.id;
         let concurrent = concurrent.into_iter().collect::<Vec<_>>();
+        let mut effects = Vec::with_capacity(op.actions.len());
 
         for action in op.actions {
-            match self.action(action, id, op.author, op.timestamp, &concurrent, repo) {
-                Ok(()) => {}
+            let effect = match self.action(action, id, op.author, op.timestamp, &concurrent, repo) {
+                Ok(()) => Effect::Applied,
                 // This particular error is returned when there is a mismatch between the expected
                 // and the actual state of a revision, which can happen concurrently. Therefore
                 // if there are other concurrent ops, it is not fatal and we simply ignore it.
@@ -374,16 +375,19 @@ impl store::Cob for Identity {
                     if concurrent.is_empty() {
                         return Err(ApplyError::UnexpectedState);
                     }
+                    Effect::Ignored
                 }
                 // It's not a user error if the revision happens to be redacted by
                 // the time this action is processed.
-                Err(ApplyError::Redacted) => {}
+                Err(ApplyError::Redacted) => Effect::Ignored,
                 Err(other) => return Err(other),
-            }
+            };
+            effects.push(effect);
+
             debug_assert!(!self.timeline.contains(&id));
             self.timeline.push(id);
         }
-        Ok(())
+        Ok(effects)
     }
 }
 
@@ -608,7 +612,9 @@ impl<R: ReadRepository> cob::Evaluate<R> for Identity {
         let op = Op::try_from(entry)?;
 
         self.op(op, concurrent.map(|(_, e)| e), repo)
-            .map_err(Error::Apply)
+            .map_err(Error::Apply)?;
+
+        Ok(())
     }
 }
 
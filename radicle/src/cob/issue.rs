@@ -11,11 +11,11 @@ use thiserror::Error;
 use crate::cob;
 use crate::cob::common::{Author, Authorization, Label, Reaction, Timestamp, Uri};
 use crate::cob::store::Transaction;
-use crate::cob::store::{Cob, CobAction};
+use crate::cob::store::{Cob, CobAction, Effect};
 use crate::cob::thread;
 use crate::cob::thread::{Comment, CommentId, Thread};
 use crate::cob::{op, store, ActorId, Embed, EntryId, ObjectId, TypeName};
-use crate::crypto::Signer;
+use crate::crypto::{PublicKey, Signer};
 use crate::identity::doc::{Doc, DocError};
 use crate::prelude::{Did, ReadRepository, RepoId, Verified};
 use crate::storage::{HasRepoId, RepositoryError, WriteRepository};
@@ -135,6 +135,30 @@ pub struct Issue {
     pub(super) labels: BTreeSet<Label>,
     /// Discussion around this issue.
     pub(super) thread: Thread,
+    /// Whether the issue was removed. See [`Action::Remove`].
+    #[serde(default, skip_serializing_if = "crate::serde_ext::is_default")]
+    pub(super) removed: bool,
+}
+
+/// Summary of an issue, as stored in the COB index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueSummary {
+    /// Title of the issue.
+    pub title: String,
+    /// Current state of the issue.
+    pub state: State,
+}
+
+impl store::Summarize for Issue {
+    type Summary = IssueSummary;
+
+    fn summary(&self) -> Self::Summary {
+        IssueSummary {
+            title: self.title.clone(),
+            state: self.state,
+        }
+    }
 }
 
 impl store::Cob for Issue {
@@ -145,6 +169,29 @@ impl store::Cob for Issue {
         &TYPENAME
     }
 
+    fn resolved_fields(&self) -> Vec<(&'static str, String)> {
+        let state = match self.state {
+            State::Open => self.state.to_string(),
+            State::Closed { reason } => format!("{} ({reason})", self.state),
+        };
+        vec![("title", self.title.clone()), ("state", state)]
+    }
+
+    fn tombstone() -> Option<Self::Action> {
+        Some(Action::Remove)
+    }
+
+    fn is_tombstoned(&self) -> bool {
+        self.removed
+    }
+
+    fn similarity_key(&self) -> Option<String> {
+        match self.state {
+            State::Open => Some(self.title.clone()),
+            State::Closed { .. } => None,
+        }
+    }
+
     fn from_root<R: ReadRepository>(op: Op, repo: &R) -> Result<Self, Self::Error> {
         let doc = op.identity_doc(repo)?.ok_or(Error::MissingIdentity)?;
         let mut actions = op.actions.into_iter();
@@ -183,13 +230,24 @@ impl store::Cob for Issue {
         op: Op,
         concurrent: I,
         repo: &R,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<Effect>, Error> {
         let doc = op.identity_doc(repo)?.ok_or(Error::MissingIdentity)?;
         let concurrent = concurrent.into_iter().collect::<Vec<_>>();
-        for action in op.actions {
+        // A title edit is overwritten by any title edit following it in the same change.
+        let last_edit = op
+            .actions
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| matches!(a, Action::Edit { .. }))
+            .map(|(i, _)| i)
+            .last();
+        let mut effects = Vec::with_capacity(op.actions.len());
+
+        for (i, action) in op.actions.into_iter().enumerate() {
             match self.authorization(&action, &op.author, &doc)? {
                 Authorization::Allow => {
-                    self.action(
+                    let overwritten = matches!(action, Action::Edit { .. }) && Some(i) != last_edit;
+                    let effect = self.action(
                         action,
                         op.id,
                         op.author,
@@ -198,16 +256,19 @@ impl store::Cob for Issue {
                         &doc,
                         repo,
                     )?;
+                    effects.push(if overwritten { Effect::Ignored } else { effect });
                 }
                 Authorization::Deny => {
                     return Err(Error::NotAuthorized(op.author, action));
                 }
                 Authorization::Unknown => {
-                    continue;
+                    effects.push(Effect::Ignored);
                 }
             }
         }
-        Ok(())
+        self.thread.reorder(op.id, &concurrent);
+
+        Ok(effects)
     }
 }
 
@@ -229,7 +290,9 @@ impl<R: ReadRepository> cob::Evaluate<R> for Issue {
     ) -> Result<(), Self::Error> {
         let op = Op::try_from(entry)?;
 
-        self.op(op, concurrent.map(|(_, e)| e), repo)
+        self.op(op, concurrent.map(|(_, e)| e), repo)?;
+
+        Ok(())
     }
 }
 
@@ -242,6 +305,7 @@ impl Issue {
             state: State::default(),
             labels: BTreeSet::default(),
             thread,
+            removed: false,
         }
     }
 
@@ -369,38 +433,55 @@ impl Issue {
             }
             // All roles can react to a comment on an issue.
             Action::CommentReact { .. } => Authorization::Allow,
+            // Issue authors can remove their own issue.
+            Action::Remove => Authorization::from(*actor == author),
         };
         Ok(outcome)
     }
 }
 
 impl Issue {
-    /// Apply a single action to the issue.
+    /// Apply a single action to the issue, and return whether it had any effect.
     fn action<R: ReadRepository>(
         &mut self,
         action: Action,
         entry: EntryId,
         author: ActorId,
         timestamp: Timestamp,
-        _concurrent: &[&cob::Entry],
-        _doc: &Doc<Verified>,
+        concurrent: &[&cob::Entry],
+        doc: &Doc<Verified>,
         _repo: &R,
-    ) -> Result<(), Error> {
+    ) -> Result<Effect, Error> {
         match action {
             Action::Assign { assignees } => {
-                self.assignees = BTreeSet::from_iter(assignees);
+                let assignees = BTreeSet::from_iter(assignees);
+                if self.assignees == assignees {
+                    return Ok(Effect::Ignored);
+                }
+                self.assignees = assignees;
             }
             Action::Edit { title } => {
                 if title.contains('\n') || title.contains('\r') {
                     return Err(Error::InvalidTitle(title));
                 }
+                if self.title == title || self.is_edit_superseded(entry, timestamp, concurrent, doc)
+                {
+                    return Ok(Effect::Ignored);
+                }
                 self.title = title;
             }
             Action::Lifecycle { state } => {
+                if self.state == state {
+                    return Ok(Effect::Ignored);
+                }
                 self.state = state;
             }
             Action::Label { labels } => {
-                self.labels = BTreeSet::from_iter(labels);
+                let labels = BTreeSet::from_iter(labels);
+                if self.labels == labels {
+                    return Ok(Effect::Ignored);
+                }
+                self.labels = labels;
             }
             Action::Comment {
                 body,
@@ -435,8 +516,42 @@ impl Issue {
             } => {
                 thread::react(&mut self.thread, entry, author, id, reaction, active)?;
             }
+            Action::Remove => {
+                // Removal is final: actions applied after it, or concurrently to it, don't
+                // bring the issue back.
+                if self.removed {
+                    return Ok(Effect::Ignored);
+                }
+                self.removed = true;
+            }
         }
-        Ok(())
+        Ok(Effect::Applied)
+    }
+
+    /// Whether a title edit loses against an authorized title edit in one of the concurrent
+    /// changes. The last writer wins: the edit with the latest timestamp, or with the greatest
+    /// change id if the timestamps are equal. This way the title doesn't depend on the order
+    /// in which concurrent changes are applied.
+    fn is_edit_superseded(
+        &self,
+        entry: EntryId,
+        timestamp: Timestamp,
+        concurrent: &[&cob::Entry],
+        doc: &Doc<Verified>,
+    ) -> bool {
+        concurrent
+            .iter()
+            .filter_map(|e| Op::try_from(*e).ok())
+            .filter(|op| (op.timestamp, op.id) > (timestamp, entry))
+            .any(|op| {
+                op.actions.iter().any(|a| {
+                    matches!(a, Action::Edit { .. })
+                        && matches!(
+                            self.authorization(a, &op.author, doc),
+                            Ok(Authorization::Allow)
+                        )
+                })
+            })
     }
 }
 
@@ -759,6 +874,14 @@ where
 
         Ok(Self { raw })
     }
+
+    /// Return a new issues store that hides the contributions of the given authors.
+    /// See [`store::Store::block`].
+    pub fn block(self, authors: impl IntoIterator<Item = PublicKey>) -> Self {
+        Self {
+            raw: self.raw.block(authors),
+        }
+    }
 }
 
 impl<'a, R> Issues<'a, R>
@@ -916,6 +1039,10 @@ pub enum Action {
         reaction: Reaction,
         active: bool,
     },
+
+    /// Remove the issue. See [`store::Store::tombstone`].
+    #[serde(rename = "remove")]
+    Remove,
 }
 
 impl CobAction for Action {}
@@ -1715,6 +1842,8 @@ mod test {
                     contents: contents.clone(),
                     type_name: type_name.clone(),
                     message: String::from("Add invalid operation"),
+                    timestamp: None,
+                    version: cob::Version::default(),
                 },
             )
             .unwrap();
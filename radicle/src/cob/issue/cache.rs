@@ -8,7 +8,7 @@ use crate::cob;
 use crate::cob::cache;
 use crate::cob::cache::{Remove, StoreReader, StoreWriter, Update};
 use crate::cob::store;
-use crate::cob::{Embed, Label, ObjectId, TypeName};
+use crate::cob::{Embed, EntryId, Label, ObjectId, TypeName};
 use crate::crypto::Signer;
 use crate::prelude::{Did, RepoId};
 use crate::sql::transaction;
@@ -117,6 +117,24 @@ impl<'a, R, C> Cache<super::Issues<'a, R>, C> {
         Ok(())
     }
 
+    /// Remove the given `id` by tombstoning it in the [`super::Issues`] storage,
+    /// and removing the entry from the `cache`. See [`store::Store::tombstone`].
+    pub fn tombstone<G>(&mut self, id: &IssueId, signer: &G) -> Result<EntryId, super::Error>
+    where
+        G: Signer,
+        R: ReadRepository + SignRepository + cob::Store,
+        C: Remove<Issue>,
+    {
+        let updated = self.store.tombstone(id, signer)?;
+        self.cache
+            .remove(id)
+            .map_err(|e| super::Error::CacheRemove {
+                id: *id,
+                err: e.into(),
+            })?;
+        Ok(updated.head)
+    }
+
     /// Read the given `id` from the [`super::Issues`] store and
     /// writing it to the `cache`.
     pub fn write(&mut self, id: &IssueId) -> Result<(), super::Error>
@@ -153,10 +171,17 @@ impl<'a, R, C> Cache<super::Issues<'a, R>, C> {
         R: ReadRepository + cob::Store,
         C: Update<Issue>,
     {
-        let issues = self.store.all()?;
-        let mut progress = cache::WriteAllProgress::new(issues.len());
-        for issue in self.store.all()? {
+        // Collect the ids first, so that the total is known upfront.
+        let ids = self.store.ids()?;
+        let mut progress = cache::WriteAllProgress::new(ids.len());
+        for id in ids {
             progress.inc();
+            let issue = match self.store.get(&id) {
+                Ok(Some(issue)) => Ok((id, issue)),
+                // Tombstoned issues aren't cached, and issues can be removed in the meantime.
+                Ok(None) => continue,
+                Err(e) => Err(e),
+            };
             match on_issue(&issue, &progress) {
                 ControlFlow::Continue(()) => match issue {
                     Ok((id, issue)) => {
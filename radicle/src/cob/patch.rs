@@ -15,7 +15,7 @@ use thiserror::Error;
 use crate::cob;
 use crate::cob::common::{Author, Authorization, CodeLocation, Label, Reaction, Timestamp};
 use crate::cob::store::Transaction;
-use crate::cob::store::{Cob, CobAction};
+use crate::cob::store::{Cob, CobAction, Effect};
 use crate::cob::thread;
 use crate::cob::thread::Thread;
 use crate::cob::thread::{Comment, CommentId, Edit, Reactions};
@@ -1120,6 +1120,27 @@ impl Patch {
     }
 }
 
+/// Summary of a patch, as stored in the COB index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchSummary {
+    /// Title of the patch.
+    pub title: String,
+    /// Current state of the patch.
+    pub state: State,
+}
+
+impl store::Summarize for Patch {
+    type Summary = PatchSummary;
+
+    fn summary(&self) -> Self::Summary {
+        PatchSummary {
+            title: self.title().to_owned(),
+            state: self.state().clone(),
+        }
+    }
+}
+
 impl store::Cob for Patch {
     type Action = Action;
     type Error = Error;
@@ -1176,13 +1197,14 @@ impl store::Cob for Patch {
         op: Op,
         concurrent: I,
         repo: &R,
-    ) -> Result<(), Error> {
-        debug_assert!(!self.timeline.contains(&op.id));
-        self.timeline.push(op.id);
-
+    ) -> Result<Vec<Effect>, Error> {
         let doc = op.identity_doc(repo)?.ok_or(Error::MissingIdentity)?;
         let concurrent = concurrent.into_iter().collect::<Vec<_>>();
 
+        debug_assert!(!self.timeline.contains(&op.id));
+        thread::insert(&mut self.timeline, op.id, &concurrent);
+        let mut effects = Vec::with_capacity(op.actions.len());
+
         for action in op.actions {
             match self.authorization(&action, &op.author, &doc)? {
                 Authorization::Allow => {
@@ -1195,6 +1217,7 @@ impl store::Cob for Patch {
                         &doc,
                         repo,
                     )?;
+                    effects.push(Effect::Applied);
                 }
                 Authorization::Deny => {
                     return Err(Error::NotAuthorized(op.author, action));
@@ -1204,11 +1227,11 @@ impl store::Cob for Patch {
                     // whether the action is authorized or not, we simply ignore it.
                     // It's likely that the target object was redacted, and we can't
                     // verify whether the action would have been allowed or not.
-                    continue;
+                    effects.push(Effect::Ignored);
                 }
             }
         }
-        Ok(())
+        Ok(effects)
     }
 }
 
@@ -1230,7 +1253,9 @@ impl<R: ReadRepository> cob::Evaluate<R> for Patch {
     ) -> Result<(), Self::Error> {
         let op = Op::try_from(entry)?;
 
-        self.op(op, concurrent.map(|(_, e)| e), repo)
+        self.op(op, concurrent.map(|(_, e)| e), repo)?;
+
+        Ok(())
     }
 }
 
@@ -2340,6 +2365,14 @@ where
         Ok(Self { raw })
     }
 
+    /// Return a new patches store that hides the contributions of the given authors.
+    /// See [`store::Store::block`].
+    pub fn block(self, authors: impl IntoIterator<Item = PublicKey>) -> Self {
+        Self {
+            raw: self.raw.block(authors),
+        }
+    }
+
     /// Patches count by state.
     pub fn counts(&self) -> Result<PatchCounts, store::Error> {
         let all = self.all()?;
@@ -220,10 +220,17 @@ impl<'a, R, C> Cache<super::Patches<'a, R>, C> {
         R: ReadRepository + cob::Store,
         C: Update<Patch>,
     {
-        let patches = self.store.all()?;
-        let mut progress = cache::WriteAllProgress::new(patches.len());
-        for patch in self.store.all()? {
+        // Collect the ids first, so that the total is known upfront.
+        let ids = self.store.ids()?;
+        let mut progress = cache::WriteAllProgress::new(ids.len());
+        for id in ids {
             progress.inc();
+            let patch = match self.store.get(&id) {
+                Ok(Some(patch)) => Ok((id, patch)),
+                // Patches can be removed in the meantime.
+                Ok(None) => continue,
+                Err(e) => Err(e),
+            };
             match callback(&patch, &progress) {
                 ControlFlow::Continue(()) => match patch {
                     Ok((id, patch)) => {
@@ -1,19 +1,28 @@
 //! Generic COB storage.
 #![allow(clippy::large_enum_variant)]
 #![allow(clippy::type_complexity)]
+use std::cell::{RefCell, RefMut};
+use std::cmp;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::ops::Deref;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 use nonempty::NonEmpty;
 use radicle_cob::CollaborativeObject;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use sqlite as sql;
 
 use crate::cob::op::Op;
 use crate::cob::{Create, Embed, EntryId, ObjectId, TypeName, Update, Updated, Version};
 use crate::git;
 use crate::prelude::*;
 use crate::storage::git as storage;
-use crate::storage::SignRepository;
+use crate::storage::git::cob::DraftStore;
+use crate::storage::{Generation, SignRepository};
 use crate::{cob, identity};
 
 pub trait CobAction: Debug {
@@ -24,6 +33,26 @@ pub trait CobAction: Debug {
     }
 }
 
+/// A field on which remotes disagree, before their histories are merged.
+/// See [`Store::conflicts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The field, as reported by [`Cob::resolved_fields`].
+    pub field: &'static str,
+    /// The value of the field as seen by each remote that changed it.
+    pub values: Vec<(PublicKey, String)>,
+}
+
+/// A collaborative object with a compact summary, eg. for listings.
+/// See [`IndexedStore`].
+pub trait Summarize {
+    /// The object summary.
+    type Summary: Serialize + DeserializeOwned;
+
+    /// Summarize the object.
+    fn summary(&self) -> Self::Summary;
+}
+
 /// A collaborative object. Can be materialized from an operation history.
 pub trait Cob: Sized + PartialEq + Debug {
     /// The underlying action composing each operation.
@@ -37,13 +66,69 @@ pub trait Cob: Sized + PartialEq + Debug {
     /// Initialize a collarorative object from a root operation.
     fn from_root<R: ReadRepository>(op: Op<Self::Action>, repo: &R) -> Result<Self, Self::Error>;
 
-    /// Apply an operation to the state.
+    /// Apply an operation to the state, and return the effect of each of its actions, in order.
     fn op<'a, R: ReadRepository, I: IntoIterator<Item = &'a cob::Entry>>(
         &mut self,
         op: Op<Self::Action>,
         concurrent: I,
         repo: &R,
-    ) -> Result<(), <Self as Cob>::Error>;
+    ) -> Result<Vec<Effect>, <Self as Cob>::Error>;
+
+    /// The schema version of this object type.
+    ///
+    /// It is recorded in the manifest of every change written through a [`Store`]. Objects
+    /// whose changes were all written with an older version are migrated when loaded,
+    /// see [`Cob::migrate`].
+    fn version() -> Version {
+        Version::default()
+    }
+
+    /// Migrate an object from an older schema version, by returning the actions that
+    /// bring it up to date.
+    ///
+    /// When the object is loaded through a [`Store`], these actions are applied on top of
+    /// its state. They are written back along with the next update made through the
+    /// [`Store`], after which the object is up to date and no longer migrated when loaded.
+    /// Until then, the store keeps the actions for as long as the object doesn't change, so
+    /// this is called once per object and store.
+    fn migrate(&self, from: Version) -> Vec<Self::Action> {
+        let _ = from;
+
+        Vec::new()
+    }
+
+    /// The action that marks an object as removed, written by [`Store::tombstone`].
+    /// Types that can't be tombstoned return `None`.
+    fn tombstone() -> Option<Self::Action> {
+        None
+    }
+
+    /// Whether the [`Cob::tombstone`] action was applied to this object.
+    ///
+    /// Once tombstoned, an object must stay tombstoned whatever actions are applied after or
+    /// concurrently to the tombstone, so that merging an older copy of the object doesn't
+    /// resurrect it.
+    fn is_tombstoned(&self) -> bool {
+        false
+    }
+
+    /// The fields of the object whose value is decided by the reducer when histories are
+    /// merged, eg. by the last writer winning, along with a rendering of their value.
+    ///
+    /// Remotes that disagree on the value of these fields are reported by
+    /// [`Store::conflicts`]. Types that don't report conflicts return nothing.
+    fn resolved_fields(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// A short text describing the object, eg. an issue's title, compared by
+    /// [`Store::similar`] to find objects similar to a new one.
+    ///
+    /// Objects that are no longer relevant, eg. closed issues, return `None`, as do types
+    /// that aren't compared.
+    fn similarity_key(&self) -> Option<String> {
+        None
+    }
 
     #[cfg(test)]
     /// Create an object from a history.
@@ -98,12 +183,390 @@ pub enum Error {
         #[source]
         err: git::raw::Error,
     },
+    #[error("git: {0}")]
+    Git(#[from] git::ext::Error),
+    #[error("index: {0}")]
+    Index(#[from] cob::cache::Error),
+    #[error("object `{1}` of type `{0}` was modified concurrently")]
+    Conflict(TypeName, ObjectId),
+    #[error("op: {0}")]
+    Op(#[from] cob::op::OpEncodingError),
+    #[error("timestamp {0} is too far in the future")]
+    FutureTimestamp(u64),
+    #[error("objects of type `{0}` can't be tombstoned")]
+    Tombstone(TypeName),
+    #[error("change {2} of object `{1}` of type `{0}` was made by untrusted author {3}")]
+    Untrusted(TypeName, ObjectId, EntryId, PublicKey),
+    #[error("change {2} of object `{1}` of type `{0}` was rejected")]
+    Rejected(TypeName, ObjectId, git::Oid),
+    #[error("repository: {0}")]
+    Repository(#[source] Box<crate::storage::RepositoryError>),
+    #[error("failed to migrate object `{1}` of type `{0}`: {2}")]
+    Migrate(
+        TypeName,
+        ObjectId,
+        #[source] Box<dyn std::error::Error + Send + Sync + 'static>,
+    ),
+    #[error("failed to evaluate object `{1}` of type `{0}`: {2}")]
+    Evaluate(
+        TypeName,
+        ObjectId,
+        #[source] Box<dyn std::error::Error + Send + Sync + 'static>,
+    ),
+}
+
+/// The effect of an action on an object's state, once applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// The action changed the object.
+    Applied,
+    /// The action was ignored, and the object is as it was before. For example, the action
+    /// was superseded by a newer one, or had no effect to begin with.
+    Ignored,
+}
+
+/// How far in the future an explicit change timestamp may be, to allow for clock skew.
+pub const MAX_TIMESTAMP_SKEW: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Check that an explicit change timestamp isn't in the future, give or take
+/// [`MAX_TIMESTAMP_SKEW`]. Returns the timestamp in seconds.
+fn check_timestamp(timestamp: cob::Timestamp) -> Result<u64, Error> {
+    let secs = timestamp.as_secs();
+
+    if secs > cob::Timestamp::now().as_secs() + MAX_TIMESTAMP_SKEW.as_secs() {
+        return Err(Error::FutureTimestamp(secs));
+    }
+    Ok(secs)
+}
+
+/// Whether an update failed because the object's reference was concurrently modified,
+/// either because it was locked by another writer, or moved since it was read.
+fn is_conflict(err: &cob::error::Update) -> bool {
+    let cob::error::Update::Refs { err } = err else {
+        return false;
+    };
+    err.downcast_ref::<git::raw::Error>().map_or(false, |e| {
+        matches!(
+            e.code(),
+            git::raw::ErrorCode::Locked
+                | git::raw::ErrorCode::Modified
+                | git::raw::ErrorCode::Exists
+        )
+    })
+}
+
+/// The schema version of an object, ie. the latest version its changes were written with.
+fn version_of<T>(object: &CollaborativeObject<T>) -> Version {
+    object
+        .history()
+        .sorted(Ord::cmp)
+        .map(|entry| entry.manifest.version)
+        .max()
+        .unwrap_or_default()
+}
+
+/// The encoded migration actions of out-of-date objects, along with the tips of the history
+/// they were computed from, so that objects are migrated once until they change.
+type Migrations = Arc<Mutex<HashMap<ObjectId, (BTreeSet<EntryId>, Vec<Vec<u8>>)>>>;
+
+/// The operation migrating an object to the current schema version of its type, if the
+/// object is out of date. See [`Cob::migrate`].
+fn migration<T: Cob>(
+    object: &CollaborativeObject<T>,
+    migrations: &Migrations,
+) -> Result<Option<Op<T::Action>>, Error> {
+    let version = version_of(object);
+    if version >= T::version() {
+        return Ok(None);
+    }
+    let tips = object.history().tips();
+    let actions = {
+        let mut migrations = migrations.lock().unwrap_or_else(|e| e.into_inner());
+
+        match migrations.get(object.id()) {
+            Some((known, actions)) if *known == tips => actions.clone(),
+            _ => {
+                let actions = object
+                    .object
+                    .migrate(version)
+                    .iter()
+                    .map(encoding::encode)
+                    .collect::<Result<Vec<_>, _>>()?;
+                migrations.insert(*object.id(), (tips.clone(), actions.clone()));
+                actions
+            }
+        }
+    };
+    let Some(actions) = NonEmpty::from_vec(actions) else {
+        return Ok(None);
+    };
+    let actions = actions.try_map(|a| serde_json::from_slice::<T::Action>(&a))?;
+    // SAFETY: Histories always have at least a root entry.
+    #[allow(clippy::unwrap_used)]
+    let head = object.history().sorted(Ord::cmp).last().unwrap();
+    // The migration isn't a change, so it gets an id that is distinct from the ids of all
+    // changes, yet is the same every time the same history is migrated.
+    let id = git::raw::Oid::hash_object(
+        git::raw::ObjectType::Blob,
+        format!("migration {} {}", head.id(), T::version()).as_bytes(),
+    )
+    .map_err(cob::op::OpEncodingError::from)?;
+
+    Ok(Some(Op {
+        id: id.into(),
+        related: actions.iter().flat_map(T::Action::parents).collect(),
+        actions,
+        author: *head.author(),
+        timestamp: cob::Timestamp::from_secs(head.timestamp),
+        parents: object.history().tips().into_iter().collect(),
+        identity: head.resource().copied(),
+        manifest: cob::Manifest::new(T::type_name().clone(), T::version()),
+    }))
+}
+
+/// Get the state of a loaded object, migrated to the current schema version of its type.
+fn migrated<T: Cob, R: ReadRepository>(
+    object: CollaborativeObject<T>,
+    migrations: &Migrations,
+    repo: &R,
+) -> Result<T, Error> {
+    let Some(op) = migration(&object, migrations)? else {
+        return Ok(object.object);
+    };
+    let id = *object.id();
+    let mut state = object.object;
+
+    state
+        .op(op, [], repo)
+        .map_err(|e| Error::Migrate(T::type_name().clone(), id, Box::new(e)))?;
+
+    Ok(state)
+}
+
+/// Evaluate an object's history the same way it's evaluated when the object is loaded,
+/// keeping only the changes matching the filter, and calling `f` with the id of each change
+/// applied and the effects of its actions, as reported by [`Cob::op`].
+///
+/// The filter must keep the dependencies of the changes it keeps, eg. the root.
+fn evaluate<T: Cob, R: ReadRepository>(
+    id: &ObjectId,
+    history: &cob::History,
+    filter: impl Fn(&EntryId) -> bool,
+    mut f: impl FnMut(&EntryId, Vec<Effect>),
+    repo: &R,
+) -> Result<T, Error> {
+    use std::ops::ControlFlow;
+
+    let root = history.root();
+    let op = Op::<T::Action>::try_from(root)?;
+    // The object doesn't exist without its root, so every root action applies.
+    f(root.id(), vec![Effect::Applied; op.actions.len()]);
+
+    let mut object = T::from_root(op, repo)
+        .map_err(|e| Error::Evaluate(T::type_name().clone(), *id, Box::new(e)))?;
+    let mut graph = history.graph().clone();
+
+    graph.prune(&history.children_of(root.id()), |key, node, siblings| {
+        if !filter(key) {
+            return ControlFlow::Break(());
+        }
+        let Ok(op) = Op::<T::Action>::try_from(&node.value) else {
+            return ControlFlow::Break(());
+        };
+        let siblings = siblings.filter(|(k, _)| filter(k)).map(|(_, n)| &n.value);
+
+        match object.op(op, siblings, repo) {
+            Ok(effects) => {
+                f(key, effects);
+                ControlFlow::Continue(())
+            }
+            Err(_) => ControlFlow::Break(()),
+        }
+    });
+    Ok(object)
+}
+
+/// Get the effects of the actions of the given change, when evaluating an object's history.
+/// Returns `None` if the change isn't part of the history, eg. because it failed to apply.
+fn effects<T: Cob, R: ReadRepository>(
+    id: &ObjectId,
+    history: &cob::History,
+    change: &EntryId,
+    repo: &R,
+) -> Result<Option<Vec<Effect>>, Error> {
+    let mut found = None;
+
+    evaluate::<T, _>(
+        id,
+        history,
+        |_| true,
+        |key, effects| {
+            if key == change {
+                found = Some(effects);
+            }
+        },
+        repo,
+    )?;
+    Ok(found)
+}
+
+/// Load an object, ignoring the histories published by the given authors.
+/// Returns `None` if only blocked authors have a copy of the object.
+fn load<T, R>(
+    repo: &R,
+    id: &ObjectId,
+    blocked: &BTreeSet<PublicKey>,
+) -> Result<Option<CollaborativeObject<T>>, cob::error::Retrieve>
+where
+    T: cob::Evaluate<R> + Cob,
+    R: cob::Store,
+{
+    if blocked.is_empty() {
+        return cob::get::<T, _>(repo, T::type_name(), id);
+    }
+    cob::get_matching::<T, _>(repo, T::type_name(), id, |r| !is_blocked(r, blocked))
+}
+
+/// The authors whose changes are trusted by [`Store::get_verified`]: the delegates of the
+/// repository, and the nodes allowed to see it if it's private.
+fn trusted<R: ReadRepository>(repo: &R) -> Result<BTreeSet<PublicKey>, Error> {
+    let doc = repo
+        .identity_doc()
+        .map_err(|e| Error::Repository(Box::new(e)))?;
+    let mut trusted = doc
+        .delegates
+        .iter()
+        .map(|did| *did.as_key())
+        .collect::<BTreeSet<_>>();
+
+    if let identity::Visibility::Private { allow } = &doc.visibility {
+        trusted.extend(allow.iter().map(|did| *did.as_key()));
+    }
+    Ok(trusted)
+}
+
+/// Check that every change of a loaded object was made by a trusted author, and that no
+/// change was left out of it, by checking that the object's history contains the tip of
+/// every copy of the object that was loaded.
+fn verify<T, R>(
+    repo: &R,
+    object: &CollaborativeObject<T>,
+    trusted: &BTreeSet<PublicKey>,
+    blocked: &BTreeSet<PublicKey>,
+) -> Result<(), Error>
+where
+    T: Cob,
+    R: cob::Store,
+{
+    use cob::object::Storage as _;
+
+    let id = object.id();
+    let history = object.history();
+
+    for entry in history.sorted(Ord::cmp) {
+        if !trusted.contains(entry.author()) {
+            return Err(Error::Untrusted(
+                T::type_name().clone(),
+                *id,
+                *entry.id(),
+                *entry.author(),
+            ));
+        }
+    }
+    let refs = repo
+        .objects(T::type_name(), id)
+        .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+
+    for r in refs.iter().filter(|r| !is_blocked(r, blocked)) {
+        if !history.graph().contains(&r.target.id) {
+            return Err(Error::Rejected(T::type_name().clone(), *id, r.target.id));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the reference is in the namespace of a blocked author.
+fn is_blocked(r: &cob::object::Reference, blocked: &BTreeSet<PublicKey>) -> bool {
+    git::parse_ref_namespaced::<PublicKey>(r.name.as_str())
+        .map_or(false, |(namespace, _)| blocked.contains(&namespace))
+}
+
+/// Similarity of two texts, between `0.0` and `1.0`: the number of words they share, out of
+/// all the words in either. Case and punctuation are ignored.
+fn similarity(a: &str, b: &str) -> f64 {
+    let words = |s: &str| -> BTreeSet<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// An update to a single object, as part of a batch. See [`Store::update_batch`].
+#[derive(Debug)]
+pub struct BatchUpdate<A> {
+    /// The object to update.
+    pub object_id: ObjectId,
+    /// The change message.
+    pub message: String,
+    /// The actions to apply.
+    pub actions: NonEmpty<A>,
+    /// Embeds to include in the change.
+    pub embeds: Vec<Embed>,
+    /// Timestamp of the change, instead of the current time. See [`Store::update_at`].
+    pub timestamp: Option<cob::Timestamp>,
+}
+
+impl<A> BatchUpdate<A> {
+    /// Create a new update of the given object, without embeds.
+    pub fn new(
+        object_id: ObjectId,
+        message: impl ToString,
+        actions: impl Into<NonEmpty<A>>,
+    ) -> Self {
+        Self {
+            object_id,
+            message: message.to_string(),
+            actions: actions.into(),
+            embeds: Vec::new(),
+            timestamp: None,
+        }
+    }
+}
+
+/// A write made through a [`Store`], reported to its observer. See [`Store::observe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreEvent {
+    /// The object was created, or a draft of it was published. Its history ends at `head`.
+    Created { head: EntryId },
+    /// The object was updated. Its history now ends at `head`.
+    Updated { head: EntryId },
+    /// The signer's copy of the object was removed.
+    Removed,
 }
 
+/// Callback notified of the writes made through a [`Store`].
+type Observer<'a> = Box<dyn Fn(&ObjectId, StoreEvent) + Send + Sync + 'a>;
+
 /// Storage for collaborative objects of a specific type `T` in a single repository.
 pub struct Store<'a, T, R> {
     identity: Option<git::Oid>,
     repo: &'a R,
+    /// Whether tombstoned objects are returned. See [`Store::tombstone`].
+    tombstoned: bool,
+    /// Authors whose contributions are hidden. See [`Store::block`].
+    blocked: BTreeSet<PublicKey>,
+    /// Notified of successful writes. See [`Store::observe`].
+    observer: Option<Observer<'a>>,
+    /// Migrations of the out-of-date objects loaded so far. See [`Cob::migrate`].
+    migrations: Migrations,
     witness: PhantomData<T>,
 }
 
@@ -122,6 +585,10 @@ where
         Ok(Self {
             repo,
             identity: None,
+            tombstoned: false,
+            blocked: BTreeSet::new(),
+            observer: None,
+            migrations: Migrations::default(),
             witness: PhantomData,
         })
     }
@@ -131,9 +598,66 @@ where
         Self {
             repo: self.repo,
             witness: self.witness,
+            tombstoned: self.tombstoned,
+            blocked: self.blocked,
+            observer: self.observer,
+            migrations: self.migrations,
             identity: Some(identity),
         }
     }
+
+    /// Return a store that keeps the objects it materializes in memory. See [`CachedStore`].
+    pub fn with_cache(self) -> CachedStore<'a, T, R> {
+        CachedStore::new(self)
+    }
+
+    /// Return a new store that also returns tombstoned objects. See [`Store::tombstone`].
+    pub fn include_tombstoned(self) -> Self {
+        Self {
+            tombstoned: true,
+            ..self
+        }
+    }
+
+    /// Return a new store that hides the contributions of the given authors, eg. the nodes
+    /// blocked by the user's follow policy.
+    ///
+    /// The histories published by blocked authors are ignored when loading objects, so objects
+    /// only they have a copy of are not returned by [`Store::get`], [`Store::all`] and
+    /// [`Store::count`], and their changes are left out of objects with other authors. Note
+    /// that changes of blocked authors which other authors built upon are kept, since they are
+    /// part of those authors' histories.
+    pub fn block(mut self, authors: impl IntoIterator<Item = PublicKey>) -> Self {
+        self.blocked.extend(authors);
+        self
+    }
+
+    /// Return a new store that doesn't hide any author. See [`Store::block`].
+    pub fn include_blocked(self) -> Self {
+        Self {
+            blocked: BTreeSet::new(),
+            ..self
+        }
+    }
+
+    /// Return a new store that calls the given observer after every successful write made
+    /// through it, eg. to keep an external index up to date.
+    ///
+    /// The observer is called once the repository's refs are signed, and never for writes
+    /// that fail. Writes made through other stores, or by other processes, are not observed.
+    pub fn observe(self, observer: impl Fn(&ObjectId, StoreEvent) + Send + Sync + 'a) -> Self {
+        Self {
+            observer: Some(Box::new(observer)),
+            ..self
+        }
+    }
+
+    /// Notify the observer of a successful write, if any.
+    fn notify(&self, id: &ObjectId, event: StoreEvent) {
+        if let Some(observer) = &self.observer {
+            observer(id, event);
+        }
+    }
 }
 
 impl<'a, T, R> Store<'a, T, R>
@@ -151,28 +675,209 @@ where
         embeds: Vec<Embed>,
         signer: &G,
     ) -> Result<Updated<T>, Error> {
-        let actions = actions.into();
-        let related = actions.iter().flat_map(T::Action::parents).collect();
-        let changes = actions.try_map(encoding::encode)?;
-        let updated = cob::update(
-            self.repo,
+        let updated = self.write(
+            BatchUpdate {
+                object_id,
+                message: message.to_owned(),
+                actions: actions.into(),
+                embeds,
+                timestamp: None,
+            },
             signer,
-            self.identity,
-            related,
-            signer.public_key(),
-            Update {
+        )?;
+        self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+        self.notify(&object_id, StoreEvent::Updated { head: updated.head });
+
+        Ok(updated)
+    }
+
+    /// Update an object, with the given timestamp instead of the current time.
+    ///
+    /// This is useful to keep the original dates of imported objects. Returns
+    /// [`Error::FutureTimestamp`] if the timestamp is more than [`MAX_TIMESTAMP_SKEW`]
+    /// in the future.
+    pub fn update_at<G: Signer>(
+        &self,
+        object_id: ObjectId,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        timestamp: cob::Timestamp,
+        signer: &G,
+    ) -> Result<Updated<T>, Error> {
+        let updated = self.write(
+            BatchUpdate {
                 object_id,
-                type_name: T::type_name().clone(),
                 message: message.to_owned(),
+                actions: actions.into(),
                 embeds,
-                changes,
+                timestamp: Some(timestamp),
             },
+            signer,
         )?;
         self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+        self.notify(&object_id, StoreEvent::Updated { head: updated.head });
 
         Ok(updated)
     }
 
+    /// Update an object, and return the [`Effect`] of each action, in the order they were
+    /// given.
+    ///
+    /// The effects are the ones reported by the object's reducer when applying the update on
+    /// top of the known history. Changes that other remotes made concurrently and that aren't
+    /// known yet may still supersede the update's actions once fetched, see
+    /// [`Store::effects`].
+    pub fn update_with_effects<G: Signer>(
+        &self,
+        object_id: ObjectId,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<(Updated<T>, NonEmpty<Effect>), Error> {
+        let actions = actions.into();
+        let len = actions.len();
+        let updated = self.update(object_id, message, actions, embeds, signer)?;
+        let effects = effects::<T, _>(
+            &object_id,
+            updated.object.history(),
+            &updated.head,
+            self.repo,
+        )?
+        .unwrap_or_default();
+        // Migration actions may have been written ahead of the update's own actions.
+        let effects = effects[effects.len().saturating_sub(len)..].to_vec();
+        let effects = NonEmpty::from_vec(effects)
+            .ok_or_else(|| Error::NotFound(T::type_name().clone(), object_id))?;
+
+        Ok((updated, effects))
+    }
+
+    /// Update multiple objects, signing the repository's refs only once for the whole batch.
+    ///
+    /// Every change is still signed on its own, since a change's signature covers its
+    /// contents and a change belongs to exactly one object. A batch of `N` updates thus
+    /// requires `N + 1` signatures instead of the `2N` needed by separate calls to
+    /// [`Store::update`].
+    ///
+    /// If an update fails, the updates that preceded it are kept and their refs signed
+    /// before the error is returned.
+    pub fn update_batch<G: Signer>(
+        &self,
+        updates: impl IntoIterator<Item = BatchUpdate<T::Action>>,
+        signer: &G,
+    ) -> Result<Vec<Updated<T>>, Error> {
+        let mut results = Vec::new();
+
+        let mut ids = Vec::new();
+
+        for update in updates {
+            let id = update.object_id;
+
+            match self.write(update, signer) {
+                Ok(updated) => {
+                    ids.push(id);
+                    results.push(updated);
+                }
+                Err(e) => {
+                    if !results.is_empty() {
+                        self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+                        self.notify_updated(&ids, &results);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        if !results.is_empty() {
+            self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+            self.notify_updated(&ids, &results);
+        }
+        Ok(results)
+    }
+
+    /// Notify the observer of the updates of a batch, once their refs are signed.
+    fn notify_updated(&self, ids: &[ObjectId], results: &[Updated<T>]) {
+        for (id, updated) in ids.iter().zip(results) {
+            self.notify(id, StoreEvent::Updated { head: updated.head });
+        }
+    }
+
+    /// Write an update without signing refs.
+    ///
+    /// If the object's reference was moved by a concurrent writer, the object is loaded
+    /// again and the change re-applied on top of the new head. Each retry thus follows a
+    /// successful write by another writer. If the reference is locked, or didn't move,
+    /// [`Error::Conflict`] is returned right away, and it is up to the caller to try again.
+    fn write<G: Signer>(
+        &self,
+        update: BatchUpdate<T::Action>,
+        signer: &G,
+    ) -> Result<Updated<T>, Error> {
+        let related: Vec<_> = update.actions.iter().flat_map(T::Action::parents).collect();
+        let changes = update.actions.try_map(encoding::encode)?;
+        let timestamp = update.timestamp.map(check_timestamp).transpose()?;
+        let name = git::refs::storage::cob(signer.public_key(), T::type_name(), &update.object_id);
+        let head = || {
+            self.repo
+                .reference_oid(signer.public_key(), &name.strip_namespace())
+                .ok()
+        };
+        let mut last = head();
+
+        loop {
+            let mut related = related.clone();
+            let mut changes = changes.clone();
+
+            // Write back the migration of out-of-date objects, ahead of the update's actions.
+            // Objects can't be out of date if their type was never migrated.
+            if T::version() > Version::default() {
+                let object = cob::get::<T, _>(self.repo, T::type_name(), &update.object_id)?
+                    .ok_or_else(|| Error::NotFound(T::type_name().clone(), update.object_id))?;
+
+                if let Some(op) = migration(&object, &self.migrations)? {
+                    let mut migrated = op.actions.try_map(encoding::encode)?;
+                    migrated.tail.extend(changes);
+
+                    related.extend(op.related);
+                    changes = migrated;
+                }
+            }
+            let result = cob::update(
+                self.repo,
+                signer,
+                self.identity,
+                related,
+                signer.public_key(),
+                Update {
+                    object_id: update.object_id,
+                    type_name: T::type_name().clone(),
+                    message: update.message.clone(),
+                    embeds: update.embeds.clone(),
+                    changes,
+                    timestamp,
+                    version: T::version(),
+                },
+            );
+            match result {
+                Err(e) if is_conflict(&e) => {
+                    let current = head();
+                    if current == last {
+                        return Err(Error::Conflict(T::type_name().clone(), update.object_id));
+                    }
+                    log::debug!(
+                        target: "radicle",
+                        "Concurrent update of {} {}, retrying: {e}",
+                        T::type_name(),
+                        update.object_id
+                    );
+                    last = current;
+                }
+                result => return result.map_err(Error::from),
+            }
+        }
+    }
+
     /// Create an object.
     pub fn create<G: Signer>(
         &self,
@@ -180,6 +885,32 @@ where
         actions: impl Into<NonEmpty<T::Action>>,
         embeds: Vec<Embed>,
         signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        self.create_with(message, actions, embeds, None, signer)
+    }
+
+    /// Create an object, with the given timestamp instead of the current time.
+    /// See [`Store::update_at`].
+    pub fn create_at<G: Signer>(
+        &self,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        timestamp: cob::Timestamp,
+        signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        let timestamp = check_timestamp(timestamp)?;
+
+        self.create_with(message, actions, embeds, Some(timestamp), signer)
+    }
+
+    fn create_with<G: Signer>(
+        &self,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        timestamp: Option<u64>,
+        signer: &G,
     ) -> Result<(ObjectId, T), Error> {
         let actions = actions.into();
         let parents = actions.iter().flat_map(T::Action::parents).collect();
@@ -192,17 +923,36 @@ where
             signer.public_key(),
             Create {
                 type_name: T::type_name().clone(),
-                version: Version::default(),
+                version: T::version(),
                 message: message.to_owned(),
                 embeds,
                 contents,
+                timestamp,
             },
         )?;
         self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+        self.notify(
+            cob.id(),
+            StoreEvent::Created {
+                head: *cob.history().root().id(),
+            },
+        );
 
         Ok((*cob.id(), cob.object))
     }
 
+    /// Remove an object by writing a [`Cob::tombstone`] to its history.
+    ///
+    /// Unlike [`Store::remove`], which deletes the signer's reference to the object, the
+    /// tombstone is replicated and merged like any other change, so that peers holding an
+    /// older copy of the object don't resurrect it. Tombstoned objects are skipped by
+    /// [`Store::get`] and [`Store::all`], unless [`Store::include_tombstoned`] is used.
+    pub fn tombstone<G: Signer>(&self, id: &ObjectId, signer: &G) -> Result<Updated<T>, Error> {
+        let action = T::tombstone().ok_or_else(|| Error::Tombstone(T::type_name().clone()))?;
+
+        self.update(*id, "Remove", NonEmpty::new(action), vec![], signer)
+    }
+
     /// Remove an object.
     pub fn remove<G: Signer>(&self, id: &ObjectId, signer: &G) -> Result<(), Error> {
         let name = git::refs::storage::cob(signer.public_key(), T::type_name(), id);
@@ -213,6 +963,8 @@ where
             Ok(_) => {
                 cob::remove(self.repo, signer.public_key(), T::type_name(), id)?;
                 self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+                self.notify(id, StoreEvent::Removed);
+
                 Ok(())
             }
             Err(err) if err.code() == git::raw::ErrorCode::NotFound => Ok(()),
@@ -226,44 +978,1038 @@ where
 
 impl<'a, T, R> Store<'a, T, R>
 where
-    R: ReadRepository + cob::Store,
-    T: cob::Evaluate<R> + Cob,
+    R: WriteRepository + cob::Store,
+    T: Cob + cob::Evaluate<R>,
     T::Action: Serialize,
 {
-    /// Get an object.
-    pub fn get(&self, id: &ObjectId) -> Result<Option<T>, Error> {
-        cob::get::<T, _>(self.repo, T::type_name(), id)
-            .map(|r| r.map(|cob| cob.object))
-            .map_err(Error::from)
-    }
-
-    /// Return all objects.
-    pub fn all(
+    /// Create an object, identified by a caller-supplied change key.
+    ///
+    /// If the signer already created an object of this type with the same key, it is
+    /// returned instead of creating a new one. This makes creation idempotent, eg. when an
+    /// import is retried. The key is recorded in a local reference pointing to the object,
+    /// see [`git::refs::storage::keys::cob`], which is neither signed nor replicated.
+    pub fn create_keyed<G: Signer>(
         &self,
-    ) -> Result<impl ExactSizeIterator<Item = Result<(ObjectId, T), Error>> + 'a, Error> {
-        let raw = cob::list::<T, _>(self.repo, T::type_name())?;
-
-        Ok(raw.into_iter().map(|o| Ok((*o.id(), o.object))))
-    }
-
-    /// Return true if the list of issues is empty.
-    pub fn is_empty(&self) -> Result<bool, Error> {
-        Ok(self.count()? == 0)
-    }
+        key: &str,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        if let Some(id) = self.find_by_key(signer.public_key(), key)? {
+            if let Some(object) = self.get(&id)? {
+                return Ok((id, object));
+            }
+        }
+        let (id, object) = self.create(message, actions, embeds, signer)?;
+        let name = git::refs::storage::keys::cob(signer.public_key(), T::type_name(), key);
 
-    /// Return objects count.
-    pub fn count(&self) -> Result<usize, Error> {
-        let raw = cob::list::<T, _>(self.repo, T::type_name())?;
+        self.repo
+            .raw()
+            .reference(
+                name.as_str(),
+                **id,
+                true,
+                &format!(
+                    "Change key of collaborative object '{}/{id}'",
+                    T::type_name()
+                ),
+            )
+            .map_err(git::ext::Error::from)?;
 
-        Ok(raw.len())
+        Ok((id, object))
     }
 }
 
-/// Allows operations to be batched atomically.
-#[derive(Debug)]
-pub struct Transaction<T: Cob + cob::Evaluate<R>, R> {
-    actions: Vec<T::Action>,
-    embeds: Vec<Embed>,
+impl<'a, T, R> Store<'a, T, R>
+where
+    R: WriteRepository + cob::Store,
+    T: Cob + cob::Evaluate<R> + for<'b> cob::Evaluate<DraftStore<'b, R>>,
+    T::Action: Serialize,
+{
+    /// Create a draft object.
+    ///
+    /// Drafts are stored under the signer's local `refs/drafts` namespace, which isn't part of
+    /// the signed refs, and are therefore never announced or replicated. Use [`Store::promote`]
+    /// to publish a draft.
+    pub fn create_draft<G: Signer>(
+        &self,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        let drafts = DraftStore::new(*signer.public_key(), self.repo);
+
+        self.with_drafts(&drafts)
+            .create(message, actions, embeds, signer)
+    }
+
+    /// Publish a draft object created with [`Store::create_draft`], by moving it to the
+    /// signer's COB refs and signing them.
+    pub fn promote<G: Signer>(&self, id: &ObjectId, signer: &G) -> Result<(), Error> {
+        let raw = self.repo.raw();
+        let draft = git::refs::storage::draft::cob(signer.public_key(), T::type_name(), id);
+        let mut reference = raw
+            .find_reference(draft.as_str())
+            .map_err(|err| Error::RefLookup {
+                name: draft.to_ref_string(),
+                err,
+            })?;
+        let Some(oid) = reference.target() else {
+            return Err(Error::NotFound(T::type_name().clone(), *id));
+        };
+        let name = git::refs::storage::cob(signer.public_key(), T::type_name(), id);
+
+        raw.reference(
+            name.as_str(),
+            oid,
+            false,
+            &format!(
+                "Promoting draft collaborative object '{}/{id}'",
+                T::type_name()
+            ),
+        )
+        .map_err(git::ext::Error::from)?;
+        reference.delete().map_err(git::ext::Error::from)?;
+        self.repo.sign_refs(signer).map_err(Error::SignRefs)?;
+        self.notify(id, StoreEvent::Created { head: oid.into() });
+
+        Ok(())
+    }
+
+    /// Get an object, falling back to the drafts of the given remote if the object
+    /// isn't published.
+    pub fn get_including_drafts(
+        &self,
+        id: &ObjectId,
+        drafts: &PublicKey,
+    ) -> Result<Option<T>, Error> {
+        if let Some(object) = self.get(id)? {
+            return Ok(Some(object));
+        }
+        let drafts = DraftStore::new(*drafts, self.repo);
+
+        self.with_drafts(&drafts).get(id)
+    }
+
+    /// Return all objects, including the drafts of the given remote.
+    pub fn all_including_drafts(&self, drafts: &PublicKey) -> Result<Vec<(ObjectId, T)>, Error> {
+        let drafts = DraftStore::new(*drafts, self.repo);
+        let mut objects = self.all()?.collect::<Result<Vec<_>, _>>()?;
+
+        for draft in self.with_drafts(&drafts).all()? {
+            let (id, object) = draft?;
+
+            if !objects.iter().any(|(other, _)| *other == id) {
+                objects.push((id, object));
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Return a store of the same type, operating on the given drafts.
+    fn with_drafts<'b>(&self, drafts: &'b DraftStore<'b, R>) -> Store<'b, T, DraftStore<'b, R>> {
+        Store {
+            identity: self.identity,
+            repo: drafts,
+            tombstoned: self.tombstoned,
+            blocked: self.blocked.clone(),
+            // Drafts aren't published, so writing them isn't observed.
+            observer: None,
+            migrations: self.migrations.clone(),
+            witness: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, R> Store<'a, T, R>
+where
+    R: ReadRepository + cob::Store,
+    T: cob::Evaluate<R> + Cob,
+    T::Action: Serialize,
+{
+    /// Get an object.
+    ///
+    /// Objects written with an older schema version are migrated, see [`Cob::migrate`].
+    /// Tombstoned objects are not returned, see [`Store::tombstone`], nor are objects of
+    /// blocked authors, see [`Store::block`].
+    pub fn get(&self, id: &ObjectId) -> Result<Option<T>, Error> {
+        let object = load::<T, _>(self.repo, id, &self.blocked)?
+            .map(|cob| migrated(cob, &self.migrations, self.repo))
+            .transpose()?;
+
+        Ok(object.filter(|o| self.tombstoned || !o.is_tombstoned()))
+    }
+
+    /// Get the [`Effect`] of each action of a change, once merged with the changes of all
+    /// remotes. Unlike the effects returned by [`Store::update_with_effects`], these account
+    /// for changes made concurrently, eg. a title edit losing against a later one. Returns
+    /// `None` if the object or the change doesn't exist.
+    pub fn effects(&self, id: &ObjectId, change: &EntryId) -> Result<Option<Vec<Effect>>, Error> {
+        let Some(object) = load::<T, _>(self.repo, id, &self.blocked)? else {
+            return Ok(None);
+        };
+        effects::<T, _>(id, object.history(), change, self.repo)
+    }
+
+    /// Get several objects, in the order of the given ids. Ids of objects that don't exist,
+    /// or that aren't returned by [`Store::get`], eg. because they are tombstoned, come back
+    /// with `None`.
+    ///
+    /// This is faster than calling [`Store::get`] for each object, since the references of
+    /// all objects are looked up at once.
+    pub fn get_many(&self, ids: &[ObjectId]) -> Result<Vec<(ObjectId, Option<T>)>, Error> {
+        use cob::object::Storage as _;
+
+        let refs = self
+            .repo
+            .types(T::type_name())
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+
+        ids.iter()
+            .map(|id| {
+                let Some(tips) = refs.get(id) else {
+                    return Ok((*id, None));
+                };
+                let tips = tips.iter().filter(|r| !is_blocked(r, &self.blocked));
+                let object = cob::get_from_refs::<T, _>(self.repo, T::type_name(), id, tips)?
+                    .map(|cob| migrated(cob, &self.migrations, self.repo))
+                    .transpose()?
+                    .filter(|o| self.tombstoned || !o.is_tombstoned());
+
+                Ok((*id, object))
+            })
+            .collect()
+    }
+
+    /// Get an object, like [`Store::get`], but only if every change in its history was made
+    /// by a trusted author: a delegate of the repository, or a node allowed to see it if the
+    /// repository is private. Otherwise, [`Error::Untrusted`] is returned.
+    ///
+    /// Changes with an invalid signature, or that fail to apply, are left out of objects
+    /// returned by [`Store::get`], along with the changes that depend on them. Here, they
+    /// cause [`Error::Rejected`] to be returned instead.
+    pub fn get_verified(&self, id: &ObjectId) -> Result<Option<T>, Error> {
+        let trusted = trusted(self.repo)?;
+        let Some(object) = load::<T, _>(self.repo, id, &self.blocked)? else {
+            return Ok(None);
+        };
+        verify(self.repo, &object, &trusted, &self.blocked)?;
+
+        let object = migrated(object, &self.migrations, self.repo)?;
+
+        Ok(Some(object).filter(|o| self.tombstoned || !o.is_tombstoned()))
+    }
+
+    /// Get all objects, like [`Store::all`], verifying each one as with
+    /// [`Store::get_verified`]. Objects that fail verification are returned as errors, while
+    /// objects that fail to load are skipped.
+    pub fn all_verified(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<(ObjectId, T), Error>> + 'a, Error> {
+        self.load_all(Some(trusted(self.repo)?))
+    }
+
+    /// Get an object as seen by the given remote, ignoring the histories of all other remotes.
+    /// Returns [`Error::NotFound`] if the remote doesn't have a copy of the object, or is
+    /// blocked, see [`Store::block`].
+    ///
+    /// This is useful to diagnose divergence between what different peers believe about an
+    /// object, since [`Store::get`] merges all of them.
+    pub fn get_from(&self, id: &ObjectId, remote: &PublicKey) -> Result<T, Error> {
+        migrated(self.load_from(id, remote)?, &self.migrations, self.repo)
+    }
+
+    /// Load an object's history as published by the given remote.
+    fn load_from(
+        &self,
+        id: &ObjectId,
+        remote: &PublicKey,
+    ) -> Result<CollaborativeObject<T>, Error> {
+        if self.blocked.contains(remote) {
+            return Err(Error::NotFound(T::type_name().clone(), *id));
+        }
+        cob::get_matching::<T, _>(self.repo, T::type_name(), id, |r| {
+            git::parse_ref_namespaced::<PublicKey>(r.name.as_str())
+                .map_or(false, |(namespace, _)| namespace == *remote)
+        })?
+        .ok_or_else(|| Error::NotFound(T::type_name().clone(), *id))
+    }
+
+    /// Report the fields of an object that remotes changed concurrently, ie. whose value was
+    /// decided by the reducer when merging the remotes' histories. Each remote's copy of the
+    /// object is evaluated as with [`Store::get_from`], and compared using
+    /// [`Cob::resolved_fields`] with the object's state at the changes all remotes have in
+    /// common.
+    ///
+    /// A field is only reported if at least two remotes changed it since then, to different
+    /// values. A remote that merely hasn't seen the latest changes of another doesn't conflict
+    /// with it. Migrations are not applied, since they change all copies alike. Blocked remotes
+    /// are left out, see [`Store::block`].
+    pub fn conflicts(&self, id: &ObjectId) -> Result<Vec<Conflict>, Error> {
+        use cob::object::Storage as _;
+
+        let remotes = self
+            .repo
+            .objects(T::type_name(), id)
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?
+            .iter()
+            .filter_map(|r| git::parse_ref_namespaced::<PublicKey>(r.name.as_str()).ok())
+            .map(|(namespace, _)| namespace)
+            .filter(|remote| !self.blocked.contains(remote))
+            .collect::<BTreeSet<_>>();
+        let copies = remotes
+            .into_iter()
+            .map(|remote| Ok((remote, self.load_from(id, &remote)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let Some((_, first)) = copies.first() else {
+            return Ok(Vec::new());
+        };
+        // The changes every remote has, ie. the common ancestor of the remotes' copies.
+        let common = copies
+            .iter()
+            .map(|(_, copy)| {
+                copy.history()
+                    .sorted(Ord::cmp)
+                    .map(|entry| *entry.id())
+                    .collect::<BTreeSet<_>>()
+            })
+            .reduce(|a, b| a.intersection(&b).copied().collect())
+            .unwrap_or_default();
+        let base = evaluate::<T, _>(
+            id,
+            first.history(),
+            |entry| common.contains(entry),
+            |_, _| {},
+            self.repo,
+        )?
+        .resolved_fields();
+        let mut fields: Vec<Conflict> = Vec::new();
+
+        for (remote, copy) in copies {
+            for (field, value) in copy.object.resolved_fields() {
+                // Fields the remote didn't change since the common ancestor can't conflict.
+                if base.iter().any(|(f, v)| *f == field && *v == value) {
+                    continue;
+                }
+                match fields.iter_mut().find(|c| c.field == field) {
+                    Some(conflict) => conflict.values.push((remote, value)),
+                    None => fields.push(Conflict {
+                        field,
+                        values: vec![(remote, value)],
+                    }),
+                }
+            }
+        }
+        fields.retain(|c| c.values.iter().any(|(_, v)| *v != c.values[0].1));
+
+        Ok(fields)
+    }
+
+    /// Find the object that the given remote created with the given change key, if any.
+    /// See [`Store::create_keyed`].
+    pub fn find_by_key(&self, remote: &PublicKey, key: &str) -> Result<Option<ObjectId>, Error> {
+        let name = git::refs::storage::keys::cob(remote, T::type_name(), key);
+
+        match self.repo.reference_oid(remote, &name.strip_namespace()) {
+            Ok(oid) => Ok(Some(oid.into())),
+            Err(err) if err.code() == git::raw::ErrorCode::NotFound => Ok(None),
+            Err(err) => Err(Error::RefLookup {
+                name: name.to_ref_string(),
+                err,
+            }),
+        }
+    }
+
+    /// Return all objects, ordered by id.
+    ///
+    /// Objects are only loaded as the iterator is advanced, so that the caller can stop early,
+    /// eg. with [`Iterator::find`], without loading the remaining objects. As with
+    /// [`cob::list`], objects that fail to load are skipped. Tombstoned objects are skipped
+    /// too, while errors migrating an object are returned.
+    ///
+    /// The upper bound of the iterator's [`Iterator::size_hint`] is the number of objects,
+    /// including tombstoned ones.
+    pub fn all(&self) -> Result<impl Iterator<Item = Result<(ObjectId, T), Error>> + 'a, Error> {
+        self.load_all(None)
+    }
+
+    /// Load all objects as the iterator is advanced, skipping the ones that fail to load.
+    /// If trusted authors are given, each object is verified as with [`Store::get_verified`]
+    /// before it is migrated.
+    fn load_all(
+        &self,
+        trusted: Option<BTreeSet<PublicKey>>,
+    ) -> Result<impl Iterator<Item = Result<(ObjectId, T), Error>> + 'a, Error> {
+        use cob::object::Storage as _;
+
+        let types = self
+            .repo
+            .types(T::type_name())
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+        let repo = self.repo;
+        let tombstoned = self.tombstoned;
+        let blocked = self.blocked.clone();
+        let migrations = self.migrations.clone();
+
+        Ok(types
+            .into_keys()
+            .filter_map(move |id| {
+                // As with `cob::list`, objects that can't be found or loaded are skipped,
+                // including those of blocked authors.
+                let object = match load::<T, _>(repo, &id, &blocked) {
+                    Ok(Some(object)) => object,
+                    Ok(None) => {
+                        log::trace!(target: "cob", "Object '{id}' not found");
+                        return None;
+                    }
+                    Err(e) => {
+                        log::trace!(target: "cob", "Object '{id}' failed to load: {e}");
+                        return None;
+                    }
+                };
+                if let Some(trusted) = &trusted {
+                    if let Err(e) = verify(repo, &object, trusted, &blocked) {
+                        return Some(Err(e));
+                    }
+                }
+                Some(migrated(object, &migrations, repo).map(|object| (id, object)))
+            })
+            .filter(move |result| match result {
+                Ok((_, object)) => tombstoned || !object.is_tombstoned(),
+                Err(_) => true,
+            }))
+    }
+
+    /// Find the objects matching the predicate, in the order of [`Store::all`].
+    ///
+    /// Objects are loaded one at a time, and only the matching ones are kept. The first
+    /// error returned by [`Store::all`] stops the search.
+    pub fn find(
+        &self,
+        mut predicate: impl FnMut(&ObjectId, &T) -> bool,
+    ) -> Result<Vec<(ObjectId, T)>, Error> {
+        self.all()?
+            .filter(|result| match result {
+                Ok((id, object)) => predicate(id, object),
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Find the first object matching the predicate, in the order of [`Store::all`].
+    /// Objects after it are not loaded. As with [`Store::find`], the first error stops
+    /// the search.
+    pub fn find_first(
+        &self,
+        mut predicate: impl FnMut(&ObjectId, &T) -> bool,
+    ) -> Result<Option<(ObjectId, T)>, Error> {
+        for result in self.all()? {
+            let (id, object) = result?;
+
+            if predicate(&id, &object) {
+                return Ok(Some((id, object)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the existing objects that look like an object described by the given text, eg.
+    /// the title of an issue about to be opened, so that the user can be asked whether they
+    /// meant one of those before the object is created.
+    ///
+    /// Objects are compared by their [`Cob::similarity_key`], using the share of words they
+    /// have in common, ignoring case and punctuation. Objects at least `threshold` similar,
+    /// between `0.0` and `1.0`, are returned, most similar first. Objects that fail to load
+    /// are skipped.
+    pub fn similar(&self, text: &str, threshold: f64) -> Result<Vec<ObjectId>, Error> {
+        let mut similar = self
+            .all()?
+            .filter_map(Result::ok)
+            .filter_map(|(id, o)| Some((id, similarity(text, &o.similarity_key()?))))
+            .filter(|(_, score)| *score >= threshold)
+            .collect::<Vec<_>>();
+        similar.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        Ok(similar.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// Return all objects, most recently updated first.
+    ///
+    /// Objects are ordered by the commit time of their latest head, which only requires
+    /// looking up one commit per head instead of going through their full history.
+    /// Objects updated at the same time are ordered by id.
+    pub fn all_by_recent(&self) -> Result<Vec<(ObjectId, T)>, Error> {
+        let mut ids = self.latest_updates()?;
+        ids.sort_by_key(|(latest, id)| (cmp::Reverse(*latest), *id));
+
+        let mut objects = Vec::with_capacity(ids.len());
+        for (_, id) in ids {
+            // Objects can be removed in the meantime.
+            if let Some(object) = self.get(&id)? {
+                objects.push((id, object));
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Return the ids of objects updated after the given time, ordered by id.
+    ///
+    /// As with [`Store::all_by_recent`], only the commit time of each object's heads is read,
+    /// so objects are not loaded. Since tombstoning is an update, the ids of tombstoned
+    /// objects are also returned, and [`Store::get`] returns nothing for them.
+    pub fn modified_since(&self, since: cob::Timestamp) -> Result<Vec<ObjectId>, Error> {
+        let since = since.as_secs() as i64;
+
+        Ok(self
+            .latest_updates()?
+            .into_iter()
+            .filter_map(|(latest, id)| (latest > since).then_some(id))
+            .collect())
+    }
+
+    /// Return the commit time, in seconds, of the latest head of every object, ordered by id.
+    /// Heads of blocked authors are ignored, and objects that only they have are left out.
+    fn latest_updates(&self) -> Result<Vec<(i64, ObjectId)>, Error> {
+        use cob::object::Storage as _;
+
+        let types = self
+            .repo
+            .types(T::type_name())
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+        let mut ids = Vec::with_capacity(types.len());
+
+        for (id, heads) in types {
+            let mut latest = None;
+            for head in heads.iter().filter(|r| !is_blocked(r, &self.blocked)) {
+                let time = self.repo.commit(head.target.id)?.time().seconds();
+                latest = latest.max(Some(time));
+            }
+            if let Some(latest) = latest {
+                ids.push((latest, id));
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Return the ids of all objects, ordered by id, without loading them.
+    ///
+    /// Objects that only blocked authors have a copy of are left out, but unlike with
+    /// [`Store::all`], tombstoned objects and objects that fail to load are included.
+    pub fn ids(&self) -> Result<Vec<ObjectId>, Error> {
+        use cob::object::Storage as _;
+
+        let types = self
+            .repo
+            .types(T::type_name())
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+
+        Ok(types
+            .into_iter()
+            .filter(|(_, refs)| refs.iter().any(|r| !is_blocked(r, &self.blocked)))
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    /// Return true if the list of issues is empty.
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.count()? == 0)
+    }
+
+    /// Return objects count. Tombstoned objects and objects of blocked authors are not counted.
+    pub fn count(&self) -> Result<usize, Error> {
+        if !self.blocked.is_empty() {
+            // Objects that fail to load are not counted, as with [`cob::list`].
+            return Ok(self.all()?.filter(Result::is_ok).count());
+        }
+        let raw = cob::list::<T, _>(self.repo, T::type_name())?;
+
+        Ok(raw
+            .iter()
+            .filter(|o| self.tombstoned || !o.object.is_tombstoned())
+            .count())
+    }
+}
+
+/// A [`Store`] that keeps materialized objects, and their count, in memory.
+///
+/// Each object is cached along with the heads of its history, and only returned from the
+/// cache while its heads haven't changed, eg. because another process updated it. Checking
+/// the heads only requires looking up the object's references, while materializing it
+/// requires going through its whole history. The count is tied to the repository's storage
+/// [`Generation`] instead, and is computed again whenever the repository changes.
+///
+/// Cached objects are kept until they are updated or the store is dropped, so the memory
+/// used grows with the number of distinct objects read. This suits long-running processes
+/// that read the same objects again and again, rather than ones that go through all of them.
+///
+/// Writes must go through the cached store, so that the objects they change are dropped from
+/// the cache; the underlying store is only given back by [`CachedStore::into_inner`]. Since
+/// the cache is kept in a [`RefCell`], a cached store can't be shared between threads: it is
+/// `Send` if the underlying store is, but never `Sync`.
+pub struct CachedStore<'a, T, R> {
+    store: Store<'a, T, R>,
+    cache: RefCell<Materialized<T>>,
+}
+
+/// Materialized objects, and the count of objects at a given storage generation.
+struct Materialized<T> {
+    generation: Option<Generation>,
+    /// Objects, along with the heads of the history they were materialized from.
+    objects: HashMap<ObjectId, (BTreeSet<git::Oid>, T)>,
+    /// Number of objects of this type, if known.
+    count: Option<usize>,
+}
+
+impl<T> Default for Materialized<T> {
+    fn default() -> Self {
+        Self {
+            generation: None,
+            objects: HashMap::new(),
+            count: None,
+        }
+    }
+}
+
+impl<'a, T, R> CachedStore<'a, T, R> {
+    /// Create a new cached store on top of the given store.
+    pub fn new(store: Store<'a, T, R>) -> Self {
+        Self {
+            store,
+            cache: RefCell::new(Materialized::default()),
+        }
+    }
+
+    /// Drop the cache, and return the underlying store.
+    pub fn into_inner(self) -> Store<'a, T, R> {
+        self.store
+    }
+
+    /// Drop the cached copy of an object.
+    fn invalidate(&self, id: &ObjectId) {
+        self.cache.borrow_mut().objects.remove(id);
+    }
+
+    /// Drop the cached object count.
+    fn invalidate_count(&self) {
+        self.cache.borrow_mut().count = None;
+    }
+
+    /// Get the cache, dropping the count first if the repository changed since it was
+    /// computed. Objects are checked individually, see [`CachedStore::get`].
+    fn refresh(&self) -> Result<RefMut<'_, Materialized<T>>, Error>
+    where
+        R: ReadRepository,
+    {
+        let generation = self.store.repo.generation()?;
+        let mut cache = self.cache.borrow_mut();
+
+        if cache.generation != Some(generation) {
+            cache.count = None;
+            cache.generation = Some(generation);
+        }
+        Ok(cache)
+    }
+}
+
+impl<'a, T, R> CachedStore<'a, T, R>
+where
+    R: ReadRepository + cob::Store,
+    T: cob::Evaluate<R> + Cob + Clone,
+    T::Action: Serialize,
+{
+    /// Get an object, from the cache if its history hasn't changed since it was materialized.
+    pub fn get(&self, id: &ObjectId) -> Result<Option<T>, Error> {
+        use cob::object::Storage as _;
+
+        let heads = self
+            .store
+            .repo
+            .objects(T::type_name(), id)
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?
+            .iter()
+            .map(|r| r.target.id)
+            .collect::<BTreeSet<_>>();
+        let mut cache = self.cache.borrow_mut();
+
+        if let Some((cached, obj)) = cache.objects.get(id) {
+            if *cached == heads {
+                return Ok(Some(obj.clone()));
+            }
+        }
+        let Some(obj) = self.store.get(id)? else {
+            cache.objects.remove(id);
+            return Ok(None);
+        };
+        cache.objects.insert(*id, (heads, obj.clone()));
+
+        Ok(Some(obj))
+    }
+}
+
+impl<'a, T, R> CachedStore<'a, T, R>
+where
+    R: ReadRepository + cob::Store,
+    T: cob::Evaluate<R> + Cob,
+    T::Action: Serialize,
+{
+    /// Return the objects count. The count is only computed again if the repository
+    /// changed since it was last computed. See [`Store::count`].
+    pub fn count(&self) -> Result<usize, Error> {
+        let mut cache = self.refresh()?;
+
+        if let Some(count) = cache.count {
+            return Ok(count);
+        }
+        let count = self.store.count()?;
+        cache.count = Some(count);
+
+        Ok(count)
+    }
+
+    /// Return true if there are no objects. See [`CachedStore::count`].
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.count()? == 0)
+    }
+}
+
+impl<'a, T, R> CachedStore<'a, T, R>
+where
+    R: ReadRepository + SignRepository + cob::Store,
+    T: Cob + cob::Evaluate<R>,
+    T::Action: Serialize,
+{
+    /// Update an object. See [`Store::update`].
+    pub fn update<G: Signer>(
+        &self,
+        object_id: ObjectId,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<Updated<T>, Error> {
+        let updated = self
+            .store
+            .update(object_id, message, actions, embeds, signer)?;
+        self.invalidate(&object_id);
+
+        Ok(updated)
+    }
+
+    /// Update an object, with the given timestamp. See [`Store::update_at`].
+    pub fn update_at<G: Signer>(
+        &self,
+        object_id: ObjectId,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        timestamp: cob::Timestamp,
+        signer: &G,
+    ) -> Result<Updated<T>, Error> {
+        let updated = self
+            .store
+            .update_at(object_id, message, actions, embeds, timestamp, signer)?;
+        self.invalidate(&object_id);
+
+        Ok(updated)
+    }
+
+    /// Update an object, and return the effect of each action.
+    /// See [`Store::update_with_effects`].
+    pub fn update_with_effects<G: Signer>(
+        &self,
+        object_id: ObjectId,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<(Updated<T>, NonEmpty<Effect>), Error> {
+        let result = self
+            .store
+            .update_with_effects(object_id, message, actions, embeds, signer)?;
+        self.invalidate(&object_id);
+
+        Ok(result)
+    }
+
+    /// Update multiple objects at once. See [`Store::update_batch`].
+    pub fn update_batch<G: Signer>(
+        &self,
+        updates: impl IntoIterator<Item = BatchUpdate<T::Action>>,
+        signer: &G,
+    ) -> Result<Vec<Updated<T>>, Error> {
+        let updates = updates.into_iter().collect::<Vec<_>>();
+        let ids = updates.iter().map(|u| u.object_id).collect::<Vec<_>>();
+        let result = self.store.update_batch(updates, signer);
+
+        // Some of the objects may have been updated even if the batch failed.
+        for id in &ids {
+            self.invalidate(id);
+        }
+        result
+    }
+
+    /// Create an object. See [`Store::create`].
+    pub fn create<G: Signer>(
+        &self,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        let (id, obj) = self.store.create(message, actions, embeds, signer)?;
+        self.invalidate(&id);
+        self.invalidate_count();
+
+        Ok((id, obj))
+    }
+
+    /// Create an object, with the given timestamp. See [`Store::create_at`].
+    pub fn create_at<G: Signer>(
+        &self,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        timestamp: cob::Timestamp,
+        signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        let (id, obj) = self
+            .store
+            .create_at(message, actions, embeds, timestamp, signer)?;
+        self.invalidate(&id);
+        self.invalidate_count();
+
+        Ok((id, obj))
+    }
+
+    /// Tombstone an object. See [`Store::tombstone`].
+    pub fn tombstone<G: Signer>(&self, id: &ObjectId, signer: &G) -> Result<Updated<T>, Error> {
+        let updated = self.store.tombstone(id, signer)?;
+        self.invalidate(id);
+        self.invalidate_count();
+
+        Ok(updated)
+    }
+
+    /// Remove an object. See [`Store::remove`].
+    pub fn remove<G: Signer>(&self, id: &ObjectId, signer: &G) -> Result<(), Error> {
+        self.store.remove(id, signer)?;
+        self.invalidate(id);
+        self.invalidate_count();
+
+        Ok(())
+    }
+}
+
+impl<'a, T, R> CachedStore<'a, T, R>
+where
+    R: WriteRepository + cob::Store,
+    T: Cob + cob::Evaluate<R>,
+    T::Action: Serialize,
+{
+    /// Create an object identified by a change key. See [`Store::create_keyed`].
+    pub fn create_keyed<G: Signer>(
+        &self,
+        key: &str,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        embeds: Vec<Embed>,
+        signer: &G,
+    ) -> Result<(ObjectId, T), Error> {
+        let (id, obj) = self
+            .store
+            .create_keyed(key, message, actions, embeds, signer)?;
+        self.invalidate(&id);
+        self.invalidate_count();
+
+        Ok((id, obj))
+    }
+}
+
+/// A [`Store`] that maintains an on-disk index of object summaries.
+///
+/// Each object is indexed along with the tips of its history, so that objects only need
+/// to be materialized again when their history changes. This makes listing objects cheap
+/// for large repositories, since most objects don't change between listings.
+pub struct IndexedStore<'a, T, R> {
+    store: Store<'a, T, R>,
+    db: cob::cache::StoreWriter,
+}
+
+impl<'a, T, R> IndexedStore<'a, T, R> {
+    /// Create a new indexed store on top of the given store, using the given database
+    /// for the index.
+    pub fn new(store: Store<'a, T, R>, db: cob::cache::StoreWriter) -> Self {
+        Self { store, db }
+    }
+}
+
+impl<'a, T, R> Deref for IndexedStore<'a, T, R> {
+    type Target = Store<'a, T, R>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+impl<'a, T, R> IndexedStore<'a, T, R>
+where
+    R: ReadRepository + cob::Store,
+    T: cob::Evaluate<R> + Cob + Summarize,
+    T::Action: Serialize,
+{
+    /// Bring the index up to date with the repository.
+    ///
+    /// Only objects whose history changed since they were last indexed are materialized.
+    /// Returns the number of index entries that were added, updated or removed.
+    pub fn reindex(&self) -> Result<usize, Error> {
+        use cob::object::Storage as _;
+
+        let rid = self.store.repo.id();
+        let objects = self
+            .store
+            .repo
+            .types(T::type_name())
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+        let indexed = self.heads(&rid)?;
+        let mut changed = 0;
+
+        for (id, refs) in &objects {
+            let mut tips = refs
+                .iter()
+                .map(|r| r.target.id.to_string())
+                .collect::<Vec<_>>();
+            tips.sort();
+
+            let heads = tips.join(",");
+            if indexed.get(id) == Some(&heads) {
+                continue;
+            }
+            let Some(object) = self.store.get(id)? else {
+                continue;
+            };
+            let summary = serde_json::to_string(&object.summary())?;
+
+            self.db.raw_query(|db| {
+                let mut stmt = db.prepare(
+                    "INSERT INTO summaries (repo, type, id, heads, summary)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT DO UPDATE
+                     SET heads = ?4, summary = ?5",
+                )?;
+                stmt.bind((1, &rid))?;
+                stmt.bind((2, T::type_name().as_str()))?;
+                stmt.bind((3, sql::Value::String(id.to_string())))?;
+                stmt.bind((4, heads.as_str()))?;
+                stmt.bind((5, summary.as_str()))?;
+                stmt.next()?;
+
+                Ok::<_, cob::cache::Error>(())
+            })?;
+            changed += 1;
+        }
+
+        for id in indexed.keys().filter(|id| !objects.contains_key(id)) {
+            self.db.raw_query(|db| {
+                let mut stmt = db.prepare(
+                    "DELETE FROM summaries
+                     WHERE repo = ?1 AND type = ?2 AND id = ?3",
+                )?;
+                stmt.bind((1, &rid))?;
+                stmt.bind((2, T::type_name().as_str()))?;
+                stmt.bind((3, sql::Value::String(id.to_string())))?;
+                stmt.next()?;
+
+                Ok::<_, cob::cache::Error>(())
+            })?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Return the summaries of all objects, bringing the index up to date first.
+    ///
+    /// Unlike [`Store::all`], objects are read from the index, and only the ones that
+    /// changed since they were last indexed are materialized.
+    pub fn all(&self) -> Result<Vec<(ObjectId, T::Summary)>, Error> {
+        self.reindex()?;
+
+        self.rows(&self.store.repo.id(), Column::Summary)?
+            .into_iter()
+            .map(|(id, summary)| Ok((id, serde_json::from_str(&summary)?)))
+            .collect()
+    }
+
+    /// Get the indexed heads of all objects of this type.
+    fn heads(&self, rid: &RepoId) -> Result<HashMap<ObjectId, String>, Error> {
+        Ok(self.rows(rid, Column::Heads)?.into_iter().collect())
+    }
+
+    /// Get the given column of all indexed objects of this type.
+    fn rows(&self, rid: &RepoId, column: Column) -> Result<Vec<(ObjectId, String)>, Error> {
+        let rows = self.db.raw_query(|db| {
+            let mut stmt = db.prepare(column.select())?;
+            stmt.bind((1, rid))?;
+            stmt.bind((2, T::type_name().as_str()))?;
+
+            let mut rows = Vec::new();
+            for row in stmt.into_iter() {
+                let row = row?;
+                let id = row.read::<&str, _>("id");
+                let Ok(id) = ObjectId::from_str(id) else {
+                    log::warn!(target: "radicle", "Invalid object id `{id}` in COB index");
+                    continue;
+                };
+                rows.push((id, row.read::<&str, _>(column.name()).to_owned()));
+            }
+            Ok::<_, cob::cache::Error>(rows)
+        })?;
+
+        Ok(rows)
+    }
+}
+
+/// A column of the summary index read by [`IndexedStore`].
+#[derive(Debug, Clone, Copy)]
+enum Column {
+    /// The tips of the object history.
+    Heads,
+    /// The object summary.
+    Summary,
+}
+
+impl Column {
+    /// The column name.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Heads => "heads",
+            Self::Summary => "summary",
+        }
+    }
+
+    /// The statement selecting this column for all objects of a repository and type.
+    fn select(&self) -> &'static str {
+        match self {
+            Self::Heads => {
+                "SELECT id, heads
+                 FROM summaries
+                 WHERE repo = ?1 AND type = ?2"
+            }
+            Self::Summary => {
+                "SELECT id, summary
+                 FROM summaries
+                 WHERE repo = ?1 AND type = ?2"
+            }
+        }
+    }
+}
+
+/// Allows operations to be batched atomically.
+#[derive(Debug)]
+pub struct Transaction<T: Cob + cob::Evaluate<R>, R> {
+    actions: Vec<T::Action>,
+    embeds: Vec<Embed>,
     repo: PhantomData<R>,
 }
 
@@ -375,7 +2121,7 @@ pub mod encoding {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test"))]
 pub mod test {
     use super::*;
 
@@ -417,4 +2163,1433 @@ pub mod test {
 
         Ok(obj)
     }
+
+    /// Check that replaying a history in different causal orders always yields the same object.
+    ///
+    /// The history is replayed `orderings` times, each time in a different topological order of
+    /// its entries, passing each operation the entries concurrent with it, as when the object
+    /// is loaded. Since concurrent operations must commute, all replays should converge;
+    /// if they don't, `false` is returned. Operations that fail to apply are skipped.
+    pub fn verify_convergence<R: ReadRepository, T: Cob>(
+        history: &crate::cob::History,
+        repo: &R,
+        orderings: u64,
+    ) -> Result<bool, HistoryError<T>> {
+        use std::ops::ControlFlow;
+
+        let mut expected: Option<T> = None;
+        let mut concurrent = HashMap::new();
+
+        history.graph().clone().prune(
+            &history.children_of(history.root().id()),
+            |key, _, siblings| {
+                concurrent.insert(*key, siblings.map(|(k, _)| *k).collect::<Vec<_>>());
+                ControlFlow::Continue(())
+            },
+        );
+
+        for seed in 0..orderings {
+            let mut rng = fastrand::Rng::with_seed(seed);
+            let mut keys = HashMap::new();
+            let entries = history.sorted(|a, b| {
+                let a = *keys.entry(*a).or_insert_with(|| rng.u64(..));
+                let b = *keys.entry(*b).or_insert_with(|| rng.u64(..));
+
+                a.cmp(&b)
+            });
+            // The root is always applied first, since all other entries depend on it.
+            let root = history.root();
+            let mut obj = T::from_root(Op::try_from(root)?, repo).map_err(HistoryError::Apply)?;
+
+            for entry in entries.filter(|e| e.id() != root.id()) {
+                let concurrent = concurrent
+                    .get(entry.id())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|k| history.graph().get(k))
+                    .map(|n| &n.value);
+
+                if let Err(err) = obj.op(Op::try_from(entry)?, concurrent, repo) {
+                    log::warn!("Error applying op to `{}` state: {err}", T::type_name());
+                }
+            }
+            match &expected {
+                Some(expected) if *expected != obj => return Ok(false),
+                Some(_) => {}
+                None => expected = Some(obj),
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cob::issue::{self, Issue};
+    use crate::storage::refs::Refs;
+    use crate::storage::RemoteRepository as _;
+    use crate::test::setup::{Network, Node, NodeRepo, NodeWithRepo};
+
+    /// A node with a project repository.
+    fn setup() -> (Node, NodeRepo) {
+        let NodeWithRepo { node, repo } = NodeWithRepo::default();
+        (node, repo)
+    }
+
+    /// The issues of a repository, without a cache.
+    fn issues(
+        repo: &storage::Repository,
+    ) -> issue::Cache<issue::Issues<'_, storage::Repository>, cob::cache::NoCache> {
+        issue::Cache::no_cache(repo).unwrap()
+    }
+
+    /// The store of issues of a repository.
+    fn issue_store(repo: &storage::Repository) -> Store<'_, Issue, storage::Repository> {
+        Store::open(repo).unwrap()
+    }
+
+    /// An action changing the title of an issue.
+    fn edit(title: &str) -> issue::Action {
+        issue::Action::Edit {
+            title: title.to_owned(),
+        }
+    }
+
+    /// Alice opens an issue, and Bob replies to it from his copy. Returns the issue, and
+    /// Bob's reply, once Alice fetched it.
+    fn open_and_reply(t: &Network) -> (ObjectId, EntryId) {
+        let id = *issues(&t.alice.repo)
+            .create("First", "Blah blah blah.", &[], &[], [], &t.alice.signer)
+            .unwrap()
+            .id();
+
+        t.bob.repo.fetch(&t.alice);
+        let reply = issues(&t.bob.repo)
+            .get_mut(&id)
+            .unwrap()
+            .comment("Bob's reply", *id, vec![], &t.bob.signer)
+            .unwrap();
+        t.alice.repo.fetch(&t.bob);
+
+        (id, reply)
+    }
+
+    #[test]
+    fn test_cached_store_invalidation() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = CachedStore::new(issue_store(&repo));
+        let id = *issues
+            .create("First", "Blah blah blah.", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "First");
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "First");
+
+        // Write to the repository without going through the cached store.
+        let mut issue = issues.get_mut(&id).unwrap();
+        issue.edit("Second", &node.signer).unwrap();
+
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "Second");
+
+        // Changes to other objects don't affect the cached copy.
+        let other = *issues
+            .create("Other", "Blah blah blah.", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let heads = store.cache.borrow().objects[&id].0.clone();
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "Second");
+        assert_eq!(store.cache.borrow().objects[&id].0, heads);
+        assert_eq!(store.get(&other).unwrap().unwrap().title(), "Other");
+    }
+
+    #[test]
+    fn test_cached_store_count() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = CachedStore::new(issue_store(&repo));
+
+        assert_eq!(store.count().unwrap(), 0);
+        assert!(store.is_empty().unwrap());
+
+        let ids = ["First", "Second"]
+            .into_iter()
+            .map(|title| {
+                *issues
+                    .create(title, "Blah", &[], &[], [], &node.signer)
+                    .unwrap()
+                    .id()
+            })
+            .collect::<Vec<_>>();
+
+        // Objects created outside of the cached store are picked up via the storage generation.
+        assert_eq!(store.count().unwrap(), 2);
+        assert_eq!(store.cache.borrow().count, Some(2));
+        assert_eq!(store.count().unwrap(), 2);
+
+        // Removing an object through the cached store invalidates the count.
+        store.remove(&ids[0], &node.signer).unwrap();
+        assert_eq!(store.cache.borrow().count, None);
+        assert_eq!(store.count().unwrap(), 1);
+
+        // As does tombstoning one, and its cached copy is dropped.
+        assert!(store.get(&ids[1]).unwrap().is_some());
+        store.tombstone(&ids[1], &node.signer).unwrap();
+        assert_eq!(store.cache.borrow().count, None);
+        assert!(!store.cache.borrow().objects.contains_key(&ids[1]));
+        assert_eq!(store.get(&ids[1]).unwrap(), None);
+        assert_eq!(store.count().unwrap(), 0);
+        assert!(store.is_empty().unwrap());
+    }
+
+    /// Signer that counts the signatures it produces.
+    struct CountingSigner<'a> {
+        signer: &'a crate::crypto::test::signer::MockSigner,
+        count: std::sync::atomic::AtomicUsize,
+    }
+
+    impl<'a> CountingSigner<'a> {
+        fn new(signer: &'a crate::crypto::test::signer::MockSigner) -> Self {
+            Self {
+                signer,
+                count: std::sync::atomic::AtomicUsize::new(0),
+            }
+        }
+
+        fn count(&self) -> usize {
+            self.count.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    impl<'a> Signer for CountingSigner<'a> {
+        fn public_key(&self) -> &PublicKey {
+            self.signer.public_key()
+        }
+
+        fn sign(&self, msg: &[u8]) -> crate::crypto::Signature {
+            self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.signer.sign(msg)
+        }
+
+        fn try_sign(
+            &self,
+            msg: &[u8],
+        ) -> Result<crate::crypto::Signature, crate::crypto::SignerError> {
+            Ok(self.sign(msg))
+        }
+    }
+
+    #[test]
+    fn test_update_batch() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        let ids = (0..10)
+            .map(|i| {
+                *issues
+                    .create(format!("Issue #{i}"), "Blah", &[], &[], [], &node.signer)
+                    .unwrap()
+                    .id()
+            })
+            .collect::<Vec<_>>();
+        let close = || issue::Action::Lifecycle {
+            state: issue::State::Closed {
+                reason: issue::CloseReason::Other,
+            },
+        };
+
+        // Close the first half of the issues one by one.
+        let signer = CountingSigner::new(&node.signer);
+        for id in &ids[..5] {
+            store
+                .update(*id, "Close", close(), vec![], &signer)
+                .unwrap();
+        }
+        let separate = signer.count();
+
+        // Close the second half in one batch.
+        let signer = CountingSigner::new(&node.signer);
+        let updated = store
+            .update_batch(
+                ids[5..]
+                    .iter()
+                    .map(|id| BatchUpdate::new(*id, "Close", close())),
+                &signer,
+            )
+            .unwrap();
+        let batched = signer.count();
+
+        assert_eq!(updated.len(), 5);
+        // Refs are only signed once for the whole batch.
+        assert_eq!(separate - batched, 4);
+
+        for id in &ids {
+            assert!(matches!(
+                store.get(id).unwrap().unwrap().state(),
+                issue::State::Closed { .. }
+            ));
+        }
+    }
+
+    /// Rewrite the heads of an object with the given commit time. Since changes are signed
+    /// over their tree, the rewritten commits are still valid changes.
+    fn set_commit_time(repo: &storage::Repository, typename: &TypeName, id: &ObjectId, time: i64) {
+        use cob::object::Storage as _;
+
+        let raw = repo.raw();
+        let odb = raw.odb().unwrap();
+
+        for head in repo.objects(typename, id).unwrap().iter() {
+            let commit = raw.find_commit(head.target.id.into()).unwrap();
+            let old = format!(" {} ", commit.time().seconds());
+            let new = format!(" {time} ");
+            let object = odb.read(commit.id()).unwrap();
+            let data = std::str::from_utf8(object.data())
+                .unwrap()
+                .split('\n')
+                .map(|line| {
+                    if line.starts_with("author ") || line.starts_with("committer ") {
+                        line.replacen(&old, &new, 1)
+                    } else {
+                        line.to_owned()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let oid = odb
+                .write(git::raw::ObjectType::Commit, data.as_bytes())
+                .unwrap();
+
+            raw.reference(head.name.as_str(), oid, true, "set commit time")
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_all_by_recent() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        // Only non-root changes are rewritten, since the root change is the object id.
+        let mut create = |title: &str, time: i64| {
+            let mut issue = issues
+                .create(title, "Blah", &[], &[], [], &node.signer)
+                .unwrap();
+            let id = *issue.id();
+
+            issue.edit(title, &node.signer).unwrap();
+            set_commit_time(&repo, Issue::type_name(), &id, time);
+            id
+        };
+        let a = create("A", 3000);
+        let b = create("B", 1000);
+        let c = create("C", 2000);
+        let ids = |store: &Store<Issue, _>| {
+            store
+                .all_by_recent()
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&store), vec![a, c, b]);
+
+        // Updating an object makes it the most recent.
+        let mut issue = issues.get_mut(&b).unwrap();
+        issue.edit("B2", &node.signer).unwrap();
+
+        assert_eq!(ids(&store), vec![b, a, c]);
+    }
+
+    #[test]
+    fn test_modified_since() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        let mut create = |title: &str, time: i64| {
+            let mut issue = issues
+                .create(title, "Blah", &[], &[], [], &node.signer)
+                .unwrap();
+            let id = *issue.id();
+
+            issue.edit(title, &node.signer).unwrap();
+            set_commit_time(&repo, Issue::type_name(), &id, time);
+            id
+        };
+        let a = create("A", 3000);
+        let b = create("B", 1000);
+        let c = create("C", 2000);
+        let since = |secs: u64| {
+            let mut ids = store
+                .modified_since(cob::Timestamp::from_secs(secs))
+                .unwrap();
+            ids.sort();
+            ids
+        };
+        let mut recent = vec![a, c];
+        recent.sort();
+
+        assert_eq!(since(1500), recent);
+        assert_eq!(since(2000), vec![a]);
+        assert!(since(3000).is_empty());
+        assert_eq!(since(0).len(), 3);
+
+        // Updating an object makes it modified again.
+        let mut issue = issues.get_mut(&b).unwrap();
+        issue.edit("B2", &node.signer).unwrap();
+
+        assert_eq!(since(3000), vec![b]);
+    }
+
+    #[test]
+    fn test_similar() {
+        let (node, repo) = setup();
+        let store = issue_store(&repo);
+        let open = |title: &str| {
+            NonEmpty::from((
+                issue::Action::Comment {
+                    body: String::from("Blah blah blah."),
+                    reply_to: None,
+                    embeds: vec![],
+                },
+                vec![issue::Action::Edit {
+                    title: title.to_owned(),
+                }],
+            ))
+        };
+        assert!(store
+            .similar("Crash when fetching", 0.5)
+            .unwrap()
+            .is_empty());
+
+        let (crash, _) = store
+            .create("Open", open("Crash when fetching"), vec![], &node.signer)
+            .unwrap();
+        store
+            .create("Open", open("Add a dark theme"), vec![], &node.signer)
+            .unwrap();
+
+        // The similar issue is found before the new one is created.
+        assert_eq!(
+            store.similar("crash when Fetching!", 0.5).unwrap(),
+            vec![crash]
+        );
+        let (duplicate, _) = store
+            .create("Open", open("crash when Fetching!"), vec![], &node.signer)
+            .unwrap();
+
+        // Closed issues are not reported.
+        store
+            .update(
+                crash,
+                "Close",
+                issue::Action::Lifecycle {
+                    state: issue::State::Closed {
+                        reason: issue::CloseReason::Solved,
+                    },
+                },
+                vec![],
+                &node.signer,
+            )
+            .unwrap();
+        assert_eq!(
+            store.similar("Crash while fetching", 0.5).unwrap(),
+            vec![duplicate]
+        );
+    }
+
+    #[test]
+    fn test_get_many() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        let first = *issues
+            .create("First", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let second = *issues
+            .create("Second", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let missing = ObjectId::from(crate::test::arbitrary::oid());
+
+        let objects = store.get_many(&[second, missing, first]).unwrap();
+        assert_eq!(
+            objects
+                .iter()
+                .map(|(id, issue)| (*id, issue.as_ref().map(|i| i.title())))
+                .collect::<Vec<_>>(),
+            vec![
+                (second, Some("Second")),
+                (missing, None),
+                (first, Some("First"))
+            ]
+        );
+        assert_eq!(objects[0].1, store.get(&second).unwrap());
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(
+            similarity("Crash when fetching", "crash, when fetching!"),
+            1.0
+        );
+        assert_eq!(
+            similarity("Crash when fetching", "Crash while fetching"),
+            0.5
+        );
+        assert_eq!(similarity("Crash", "Dark theme"), 0.0);
+        assert_eq!(similarity("", "..."), 0.0);
+    }
+
+    #[test]
+    fn test_create_at() {
+        let (node, repo) = setup();
+        let store = issue_store(&repo);
+        let created = cob::Timestamp::from_secs(1_000_000_000);
+        let (imported, issue) = store
+            .create_at(
+                "Import issue",
+                NonEmpty::from((
+                    issue::Action::Comment {
+                        body: String::from("Imported from another tracker"),
+                        reply_to: None,
+                        embeds: vec![],
+                    },
+                    vec![issue::Action::Edit {
+                        title: String::from("Imported"),
+                    }],
+                )),
+                vec![],
+                created,
+                &node.signer,
+            )
+            .unwrap();
+        assert_eq!(issue.timestamp(), created);
+        assert_eq!(store.get(&imported).unwrap().unwrap().timestamp(), created);
+
+        let updated = store
+            .update_at(
+                imported,
+                "Close issue",
+                issue::Action::Lifecycle {
+                    state: issue::State::Closed {
+                        reason: issue::CloseReason::Solved,
+                    },
+                },
+                vec![],
+                cob::Timestamp::from_secs(1_100_000_000),
+                &node.signer,
+            )
+            .unwrap();
+        let head = updated.object.history().graph().get(&updated.head).unwrap();
+        assert_eq!(head.timestamp, 1_100_000_000);
+
+        // Objects created without a timestamp are more recent than the imported one.
+        let mut issues = issues(&repo);
+        let id = *issues
+            .create("Recent", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let ids = store
+            .all_by_recent()
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![id, imported]);
+
+        // Timestamps too far in the future are rejected.
+        let future = cob::Timestamp::from_secs(
+            cob::Timestamp::now().as_secs() + 2 * MAX_TIMESTAMP_SKEW.as_secs(),
+        );
+        assert!(matches!(
+            store.update_at(
+                imported,
+                "Edit",
+                issue::Action::Edit {
+                    title: String::from("Future")
+                },
+                vec![],
+                future,
+                &node.signer
+            ),
+            Err(Error::FutureTimestamp(_))
+        ));
+    }
+
+    #[test]
+    fn test_update_with_effects() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        let id = *issues
+            .create("First", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+
+        let (updated, effects) = store
+            .update_with_effects(
+                id,
+                "Edit",
+                NonEmpty::from((
+                    // Overwritten by the next edit.
+                    edit("Second"),
+                    vec![
+                        edit("Third"),
+                        // The issue is already open.
+                        issue::Action::Lifecycle {
+                            state: issue::State::Open,
+                        },
+                    ],
+                )),
+                vec![],
+                &node.signer,
+            )
+            .unwrap();
+
+        assert_eq!(updated.object.title(), "Third");
+        assert_eq!(
+            effects,
+            NonEmpty::from((Effect::Ignored, vec![Effect::Applied, Effect::Ignored]))
+        );
+
+        let (_, effects) = store
+            .update_with_effects(id, "Edit", edit("Fourth"), vec![], &node.signer)
+            .unwrap();
+        assert_eq!(effects, NonEmpty::new(Effect::Applied));
+    }
+
+    #[test]
+    fn test_update_with_effects_concurrent() {
+        let t = Network::default();
+        let alice = issue_store(&t.alice.repo);
+        let bob = issue_store(&t.bob.repo);
+
+        t.bob.repo.fetch(&t.alice);
+        let id = *issues(&t.bob.repo)
+            .create("First", "Blah", &[], &[], [], &t.bob.signer)
+            .unwrap()
+            .id();
+        t.alice.repo.fetch(&t.bob);
+
+        // Bob changes the title, and Alice changes it concurrently, before she knows about
+        // Bob's change. Bob's change is the latest, so his title wins.
+        let later = cob::Timestamp::from_secs(cob::Timestamp::now().as_secs() + 60);
+        bob.update_at(id, "Edit", edit("Bob's"), vec![], later, &t.bob.signer)
+            .unwrap();
+        let (updated, effects) = alice
+            .update_with_effects(id, "Edit", edit("Alice's"), vec![], &t.alice.signer)
+            .unwrap();
+        assert_eq!(effects, NonEmpty::new(Effect::Applied));
+
+        // Once Alice knows about Bob's change, her edit turns out to have lost.
+        t.alice.repo.fetch(&t.bob);
+        assert_eq!(alice.get(&id).unwrap().unwrap().title(), "Bob's");
+        assert_eq!(
+            alice.effects(&id, &updated.head).unwrap(),
+            Some(vec![Effect::Ignored])
+        );
+
+        // Bob sees the same outcome, whichever order the changes are applied in.
+        t.bob.repo.fetch(&t.alice);
+        assert_eq!(bob.get(&id).unwrap().unwrap().title(), "Bob's");
+        assert_eq!(
+            bob.effects(&id, &updated.head).unwrap(),
+            Some(vec![Effect::Ignored])
+        );
+    }
+
+    #[test]
+    fn test_update_concurrent_lock() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        let id = *issues
+            .create("First", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+
+        // Simulate another writer holding the lock on the object's reference.
+        let name = git::refs::storage::cob(node.signer.public_key(), &issue::TYPENAME, &id);
+        let lock = repo.path().join(format!("{name}.lock"));
+        std::fs::write(&lock, []).unwrap();
+
+        let err = store
+            .update(id, "Edit", edit("Second"), vec![], &node.signer)
+            .unwrap_err();
+        assert!(matches!(err, Error::Conflict(_, oid) if oid == id), "{err}");
+
+        // Once the lock is released, the update goes through.
+        std::fs::remove_file(&lock).unwrap();
+        store
+            .update(id, "Edit", edit("Second"), vec![], &node.signer)
+            .unwrap();
+
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "Second");
+    }
+
+    #[test]
+    fn test_update_contention() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let id = *issues
+            .create("First", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let rid = repo.id();
+
+        // Writers updating the same object from separate handles either get their update
+        // through, or a conflict, but never another error.
+        let results = std::thread::scope(|scope| {
+            let writers = (0..4)
+                .map(|i| {
+                    let (storage, signer) = (&node.storage, &node.signer);
+                    scope.spawn(move || {
+                        let repo = storage.repository(rid).unwrap();
+                        let store = issue_store(&repo);
+
+                        (0..8)
+                            .map(|j| {
+                                let title = format!("Title {i}/{j}");
+                                let update = BatchUpdate::new(id, "Edit", edit(&title));
+                                // Refs aren't signed, so only the object's reference is
+                                // contended.
+                                store.write(update, signer).map(|_| ())
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            writers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for result in &results {
+            assert!(
+                matches!(result, Ok(()) | Err(Error::Conflict(_, oid)) if *oid == id),
+                "{result:?}"
+            );
+        }
+        assert!(results.iter().any(Result::is_ok));
+        let store = issue_store(&repo);
+        assert!(store
+            .get(&id)
+            .unwrap()
+            .unwrap()
+            .title()
+            .starts_with("Title "));
+    }
+
+    #[test]
+    fn test_update_racing_ref() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let store = issue_store(&repo);
+        let id = *issues
+            .create("First", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let first = store
+            .update(id, "Edit", edit("Second"), vec![], &node.signer)
+            .unwrap();
+        let second = store
+            .update(id, "Edit", edit("Third"), vec![], &node.signer)
+            .unwrap();
+        assert_eq!(second.parents, vec![first.head]);
+
+        // A writer that loaded the object before `second` was written must not be able to
+        // move the reference back, discarding `second`.
+        let err = cob::object::Storage::update(
+            &*repo,
+            node.signer.public_key(),
+            &issue::TYPENAME,
+            &id,
+            &first.head,
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), git::raw::ErrorCode::Modified);
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "Third");
+
+        // Updating on top of the latest head still works.
+        store
+            .update(id, "Edit", edit("Fourth"), vec![], &node.signer)
+            .unwrap();
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "Fourth");
+    }
+
+    #[test]
+    fn test_indexed_store() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let db = cob::cache::StoreWriter::memory().unwrap();
+        let store = IndexedStore::new(issue_store(&repo), db);
+        let ids = ["First", "Second", "Third"]
+            .into_iter()
+            .map(|title| {
+                *issues
+                    .create(title, "Blah", &[], &[], [], &node.signer)
+                    .unwrap()
+                    .id()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(store.reindex().unwrap(), 3);
+        // Nothing changed, so nothing is materialized again.
+        assert_eq!(store.reindex().unwrap(), 0);
+
+        // Only the changed object is re-indexed.
+        issues
+            .get_mut(&ids[0])
+            .unwrap()
+            .lifecycle(
+                issue::State::Closed {
+                    reason: issue::CloseReason::Solved,
+                },
+                &node.signer,
+            )
+            .unwrap();
+        assert_eq!(store.reindex().unwrap(), 1);
+
+        let mut list = store.all().unwrap();
+        list.sort_by(|(_, a), (_, b)| a.title.cmp(&b.title));
+
+        assert_eq!(
+            list.iter()
+                .map(|(_, s)| (s.title.as_str(), s.state))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "First",
+                    issue::State::Closed {
+                        reason: issue::CloseReason::Solved
+                    }
+                ),
+                ("Second", issue::State::Open),
+                ("Third", issue::State::Open),
+            ]
+        );
+
+        // Removed objects are dropped from the index.
+        issues.remove(&ids[1], &node.signer).unwrap();
+        assert_eq!(store.reindex().unwrap(), 1);
+        assert_eq!(store.all().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_all_merges_remotes() {
+        let t = Network::default();
+        let (id, _) = open_and_reply(&t);
+
+        // The issue now exists under both Alice's and Bob's namespace in Alice's repository.
+        let store = issue_store(&t.alice.repo);
+        let all = store.all().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(all.len(), 1);
+        assert_eq!(store.count().unwrap(), 1);
+
+        let (object, issue) = all.first().unwrap();
+        assert_eq!(*object, id);
+        assert_eq!(issue.comments().count(), 2);
+    }
+
+    #[test]
+    fn test_get_from() {
+        let t = Network::default();
+        let mut alice_issues = issues(&t.alice.repo);
+        let mut bob_issues = issues(&t.bob.repo);
+        let id = *alice_issues
+            .create("First", "Blah blah blah.", &[], &[], [], &t.alice.signer)
+            .unwrap()
+            .id();
+
+        t.bob.repo.fetch(&t.alice);
+        bob_issues
+            .get_mut(&id)
+            .unwrap()
+            .comment("Bob's reply", *id, vec![], &t.bob.signer)
+            .unwrap();
+        alice_issues
+            .get_mut(&id)
+            .unwrap()
+            .comment("Alice's reply", *id, vec![], &t.alice.signer)
+            .unwrap();
+        t.alice.repo.fetch(&t.bob);
+
+        let store = issue_store(&t.alice.repo);
+        let comments = |issue: Issue| {
+            issue
+                .comments()
+                .map(|(_, c)| c.body().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        // Each remote only sees its own reply.
+        assert_eq!(
+            comments(store.get_from(&id, t.alice.signer.public_key()).unwrap()),
+            vec!["Blah blah blah.", "Alice's reply"]
+        );
+        assert_eq!(
+            comments(store.get_from(&id, t.bob.signer.public_key()).unwrap()),
+            vec!["Blah blah blah.", "Bob's reply"]
+        );
+        // The merged view has both.
+        assert_eq!(comments(store.get(&id).unwrap().unwrap()).len(), 3);
+
+        // Eve doesn't have a copy.
+        assert!(matches!(
+            store.get_from(&id, t.eve.signer.public_key()),
+            Err(Error::NotFound(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let t = Network::default();
+        let mut alice_issues = issues(&t.alice.repo);
+        let mut bob_issues = issues(&t.bob.repo);
+        let alice = *t.alice.signer.public_key();
+        let bob = *t.bob.signer.public_key();
+
+        t.bob.repo.fetch(&t.alice);
+        let id = *bob_issues
+            .create("First", "Blah blah blah.", &[], &[], [], &t.bob.signer)
+            .unwrap()
+            .id();
+        t.alice.repo.fetch(&t.bob);
+
+        let store = issue_store(&t.alice.repo);
+        assert_eq!(store.conflicts(&id).unwrap(), vec![]);
+
+        // Alice changes the title and closes the issue. Bob hasn't seen these changes yet,
+        // but that's no conflict.
+        let mut issue = alice_issues.get_mut(&id).unwrap();
+        issue.edit("Second", &t.alice.signer).unwrap();
+        issue
+            .lifecycle(
+                issue::State::Closed {
+                    reason: issue::CloseReason::Solved,
+                },
+                &t.alice.signer,
+            )
+            .unwrap();
+        assert_eq!(store.conflicts(&id).unwrap(), vec![]);
+
+        // Bob concurrently changes the title too, and replies, which isn't a resolved field.
+        let mut issue = bob_issues.get_mut(&id).unwrap();
+        issue.edit("Third", &t.bob.signer).unwrap();
+        issue
+            .comment("Bob's reply", *id, vec![], &t.bob.signer)
+            .unwrap();
+        t.alice.repo.fetch(&t.bob);
+
+        // Only the title was changed by both.
+        let mut conflicts = store.conflicts(&id).unwrap();
+        for conflict in &mut conflicts {
+            conflict.values.sort();
+        }
+        let mut values = vec![
+            (alice, String::from("Second")),
+            (bob, String::from("Third")),
+        ];
+        values.sort();
+
+        assert_eq!(
+            conflicts,
+            vec![Conflict {
+                field: "title",
+                values
+            }]
+        );
+
+        // The merged view resolves the conflict.
+        let issue = store.get(&id).unwrap().unwrap();
+        assert!(["Second", "Third"].contains(&issue.title()));
+        assert!(matches!(issue.state(), issue::State::Closed { .. }));
+    }
+
+    #[test]
+    fn test_verify_convergence() {
+        let t = Network::default();
+        let mut alice_issues = issues(&t.alice.repo);
+        let mut bob_issues = issues(&t.bob.repo);
+        let id = *alice_issues
+            .create("First", "Blah blah blah.", &[], &[], [], &t.alice.signer)
+            .unwrap()
+            .id();
+        let history = |repo: &crate::storage::git::Repository| {
+            cob::get::<Issue, _>(repo, Issue::type_name(), &id)
+                .unwrap()
+                .unwrap()
+                .history
+        };
+
+        // A linear history can only be replayed in one order.
+        let mut issue = alice_issues.get_mut(&id).unwrap();
+        issue
+            .comment("Alice's reply", *id, vec![], &t.alice.signer)
+            .unwrap();
+        issue.edit("Second", &t.alice.signer).unwrap();
+
+        assert!(
+            test::verify_convergence::<_, Issue>(&history(&*t.alice.repo), &*t.alice.repo, 16)
+                .unwrap()
+        );
+
+        // Concurrent comments are ordered by id in the thread timeline, whichever order they
+        // are applied in.
+        t.bob.repo.fetch(&t.alice);
+        bob_issues
+            .get_mut(&id)
+            .unwrap()
+            .comment("Bob's reply", *id, vec![], &t.bob.signer)
+            .unwrap();
+        alice_issues
+            .get_mut(&id)
+            .unwrap()
+            .comment("Alice's second reply", *id, vec![], &t.alice.signer)
+            .unwrap();
+        t.alice.repo.fetch(&t.bob);
+
+        assert!(
+            test::verify_convergence::<_, Issue>(&history(&*t.alice.repo), &*t.alice.repo, 16)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_draft_lifecycle() {
+        let (node, repo) = setup();
+        let store = issue_store(&repo);
+        let key = node.signer.public_key();
+        let signed = |repo: &crate::storage::git::Repository| -> Refs {
+            (*repo.remote(key).unwrap().refs).clone()
+        };
+        let before = signed(&*repo);
+        let (id, _) = store
+            .create_draft(
+                "Create issue",
+                NonEmpty::from_vec(vec![
+                    issue::Action::Comment {
+                        body: String::from("Blah blah blah."),
+                        reply_to: None,
+                        embeds: vec![],
+                    },
+                    issue::Action::Edit {
+                        title: String::from("Draft"),
+                    },
+                ])
+                .unwrap(),
+                vec![],
+                &node.signer,
+            )
+            .unwrap();
+
+        // Drafts are not visible by default, and are not part of the signed refs.
+        assert_eq!(store.get(&id).unwrap(), None);
+        assert_eq!(store.count().unwrap(), 0);
+        assert_eq!(
+            store
+                .get_including_drafts(&id, key)
+                .unwrap()
+                .unwrap()
+                .title(),
+            "Draft"
+        );
+        assert_eq!(store.all_including_drafts(key).unwrap().len(), 1);
+        assert_eq!(signed(&*repo), before);
+        assert!(!repo
+            .references_of(key)
+            .unwrap()
+            .iter()
+            .any(|(name, _)| name.as_str().contains(&id.to_string())));
+
+        // Once promoted, the object is published.
+        store.promote(&id, &node.signer).unwrap();
+
+        assert_eq!(store.get(&id).unwrap().unwrap().title(), "Draft");
+        assert_eq!(store.count().unwrap(), 1);
+        assert_eq!(store.all_including_drafts(key).unwrap().len(), 1);
+        assert!(signed(&*repo)
+            .keys()
+            .any(|name| name.as_str().contains(&id.to_string())));
+    }
+
+    #[test]
+    fn test_create_keyed() {
+        let (node, repo) = setup();
+        let store = issue_store(&repo);
+        let actions = || {
+            NonEmpty::from_vec(vec![
+                issue::Action::Comment {
+                    body: String::from("Blah blah blah."),
+                    reply_to: None,
+                    embeds: vec![],
+                },
+                issue::Action::Edit {
+                    title: String::from("First"),
+                },
+            ])
+            .unwrap()
+        };
+        let (a, _) = store
+            .create_keyed("import:1", "Create issue", actions(), vec![], &node.signer)
+            .unwrap();
+        let (b, issue) = store
+            .create_keyed("import:1", "Create issue", actions(), vec![], &node.signer)
+            .unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(issue.title(), "First");
+        assert_eq!(store.count().unwrap(), 1);
+        let key = node.signer.public_key();
+        assert_eq!(store.find_by_key(key, "import:1").unwrap(), Some(a));
+        assert_eq!(store.find_by_key(key, "import:2").unwrap(), None);
+
+        let (c, _) = store
+            .create_keyed("import:2", "Create issue", actions(), vec![], &node.signer)
+            .unwrap();
+
+        assert_ne!(a, c);
+        assert_eq!(store.count().unwrap(), 2);
+
+        // Keys are local, and not part of the signed refs.
+        assert!(!repo
+            .references_of(key)
+            .unwrap()
+            .iter()
+            .any(|(name, _)| name.as_str().starts_with("refs/keys/")));
+    }
+
+    thread_local! {
+        /// Number of times [`LabeledIssue::migrate`] was called on this thread.
+        static MIGRATIONS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+        /// Number of times a [`LabeledIssue`] was loaded on this thread.
+        static LOADS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+    }
+
+    fn migrations() -> usize {
+        MIGRATIONS.with(|m| m.get())
+    }
+
+    fn loads() -> usize {
+        LOADS.with(|l| l.get())
+    }
+
+    /// A second version of the issue schema, in which every issue is labeled.
+    #[derive(Debug, PartialEq)]
+    struct LabeledIssue(Issue);
+
+    impl Cob for LabeledIssue {
+        type Action = issue::Action;
+        type Error = issue::Error;
+
+        fn type_name() -> &'static TypeName {
+            Issue::type_name()
+        }
+
+        fn from_root<R: ReadRepository>(
+            op: Op<Self::Action>,
+            repo: &R,
+        ) -> Result<Self, Self::Error> {
+            Issue::from_root(op, repo).map(Self)
+        }
+
+        fn op<'a, R: ReadRepository, I: IntoIterator<Item = &'a cob::Entry>>(
+            &mut self,
+            op: Op<Self::Action>,
+            concurrent: I,
+            repo: &R,
+        ) -> Result<Vec<Effect>, Self::Error> {
+            self.0.op(op, concurrent, repo)
+        }
+
+        fn version() -> Version {
+            Version::new(2).unwrap()
+        }
+
+        fn migrate(&self, from: Version) -> Vec<Self::Action> {
+            assert_eq!(from, Version::default());
+            MIGRATIONS.with(|m| m.set(m.get() + 1));
+
+            vec![issue::Action::Label {
+                labels: [cob::Label::new("migrated").unwrap()].into(),
+            }]
+        }
+    }
+
+    impl<R: ReadRepository> cob::Evaluate<R> for LabeledIssue {
+        type Error = issue::Error;
+
+        fn init(entry: &cob::Entry, repo: &R) -> Result<Self, Self::Error> {
+            LOADS.with(|l| l.set(l.get() + 1));
+
+            <Issue as cob::Evaluate<R>>::init(entry, repo).map(Self)
+        }
+
+        fn apply<'a, I: Iterator<Item = (&'a EntryId, &'a cob::Entry)>>(
+            &mut self,
+            entry: &cob::Entry,
+            concurrent: I,
+            repo: &R,
+        ) -> Result<(), Self::Error> {
+            self.0.apply(entry, concurrent, repo)
+        }
+    }
+
+    #[test]
+    fn test_migration() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        let v1 = issue_store(&repo);
+        let v2 = Store::<LabeledIssue, _>::open(&*repo).unwrap();
+        let id = *issues
+            .create("First", "Blah", &[], &[], [], &node.signer)
+            .unwrap()
+            .id();
+        let labels = |issue: &Issue| {
+            issue
+                .labels()
+                .map(|l| l.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        // Up to date objects aren't migrated.
+        assert_eq!(labels(&v1.get(&id).unwrap().unwrap()), Vec::<String>::new());
+        assert_eq!(migrations(), 0);
+
+        // Objects written with the first version are migrated when loaded, until written back.
+        let LabeledIssue(issue) = v2.get(&id).unwrap().unwrap();
+        assert_eq!(labels(&issue), vec!["migrated"]);
+        assert_eq!(labels(&v1.get(&id).unwrap().unwrap()), Vec::<String>::new());
+
+        // The migration is computed once, and applied every time the object is loaded.
+        let LabeledIssue(issue) = v2.get(&id).unwrap().unwrap();
+        assert_eq!(labels(&issue), vec!["migrated"]);
+        assert_eq!(migrations(), 1);
+
+        // The migration is written back with the next update.
+        let updated = v2
+            .update(
+                id,
+                "Edit",
+                issue::Action::Edit {
+                    title: String::from("Second"),
+                },
+                vec![],
+                &node.signer,
+            )
+            .unwrap();
+        let head = updated.object.history().graph().get(&updated.head).unwrap();
+        assert_eq!(head.manifest.version, LabeledIssue::version());
+        assert_eq!(head.contents().len(), 2);
+        assert_eq!(migrations(), 1);
+
+        // From then on, the object is up to date, and no longer migrated when loaded.
+        let LabeledIssue(issue) = v2.get(&id).unwrap().unwrap();
+        assert_eq!(labels(&issue), vec!["migrated"]);
+        assert_eq!(issue.title(), "Second");
+        assert_eq!(labels(&v1.get(&id).unwrap().unwrap()), vec!["migrated"]);
+        assert_eq!(v2.all().unwrap().count(), 1);
+        assert_eq!(migrations(), 1);
+    }
+
+    #[test]
+    fn test_find_stops_early() {
+        let (node, repo) = setup();
+        let mut issues = issues(&repo);
+        // Loading these issues as `LabeledIssue` tells us when they're loaded.
+        let store = Store::<LabeledIssue, _>::open(&*repo).unwrap();
+        let mut ids = (0..3)
+            .map(|i| {
+                *issues
+                    .create(format!("Issue #{i}"), "Blah", &[], &[], [], &node.signer)
+                    .unwrap()
+                    .id()
+            })
+            .collect::<Vec<_>>();
+        ids.sort();
+
+        let (id, _) = store.find_first(|id, _| *id == ids[1]).unwrap().unwrap();
+        assert_eq!(id, ids[1]);
+        assert_eq!(loads(), 2);
+
+        // Iterating lazily doesn't load the remaining objects either.
+        let mut all = store.all().unwrap();
+        assert_eq!(all.size_hint(), (0, Some(3)));
+        assert_eq!(all.next().unwrap().unwrap().0, ids[0]);
+        assert_eq!(loads(), 3);
+
+        assert!(store.find_first(|_, _| false).unwrap().is_none());
+        assert_eq!(loads(), 6);
+
+        let found = store.find(|id, _| *id != ids[1]).unwrap();
+        assert_eq!(
+            found.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![ids[0], ids[2]]
+        );
+        assert_eq!(loads(), 9);
+    }
+
+    #[test]
+    fn test_tombstone() {
+        let t = Network::default();
+        let mut alice_issues = issues(&t.alice.repo);
+        let mut bob_issues = issues(&t.bob.repo);
+        let id = *alice_issues
+            .create("First", "Blah blah blah.", &[], &[], [], &t.alice.signer)
+            .unwrap()
+            .id();
+
+        // Bob has a copy of the issue from before it was removed, which he updates.
+        t.bob.repo.fetch(&t.alice);
+        bob_issues
+            .get_mut(&id)
+            .unwrap()
+            .comment("Bob's reply", *id, vec![], &t.bob.signer)
+            .unwrap();
+
+        alice_issues.tombstone(&id, &t.alice.signer).unwrap();
+        let store = issue_store(&t.alice.repo);
+        assert_eq!(store.get(&id).unwrap(), None);
+
+        // Merging Bob's older copy doesn't resurrect the issue.
+        t.alice.repo.fetch(&t.bob);
+        assert_eq!(store.get(&id).unwrap(), None);
+        assert_eq!(store.all().unwrap().count(), 0);
+        assert_eq!(store.count().unwrap(), 0);
+
+        let store = store.include_tombstoned();
+        let issue = store.get(&id).unwrap().unwrap();
+        assert!(issue.is_tombstoned());
+        assert_eq!(issue.comments().count(), 2);
+        assert_eq!(store.all().unwrap().count(), 1);
+
+        // Types without a tombstone action can't be tombstoned.
+        let patches = Store::<crate::cob::patch::Patch, _>::open(&*t.alice.repo).unwrap();
+        assert!(matches!(
+            patches.tombstone(&id, &t.alice.signer),
+            Err(Error::Tombstone(_))
+        ));
+    }
+
+    #[test]
+    fn test_blocked() {
+        let t = Network::default();
+        let (alices, _) = open_and_reply(&t);
+        let bobs = *issues(&t.bob.repo)
+            .create("Second", "Spam.", &[], &[], [], &t.bob.signer)
+            .unwrap()
+            .id();
+        t.alice.repo.fetch(&t.bob);
+
+        let store = issue_store(&t.alice.repo).block([*t.bob.signer.public_key()]);
+        assert_eq!(store.get(&bobs).unwrap(), None);
+        assert_eq!(store.count().unwrap(), 1);
+
+        let all = store.all().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].0, alices);
+        // Bob's reply is left out of Alice's issue.
+        assert_eq!(store.get(&alices).unwrap().unwrap().comments().count(), 1);
+        // Bob's copy can't be looked at directly either.
+        assert!(matches!(
+            store.get_from(&alices, t.bob.signer.public_key()),
+            Err(Error::NotFound(..))
+        ));
+        assert!(store.get_from(&alices, t.alice.signer.public_key()).is_ok());
+        assert!(store.conflicts(&alices).unwrap().is_empty());
+        // Bob's issue isn't listed by update time either.
+        let recent = store.all_by_recent().unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].0, alices);
+        assert_eq!(
+            store.modified_since(cob::Timestamp::from_secs(0)).unwrap(),
+            vec![alices]
+        );
+
+        let store = store.include_blocked();
+        assert!(store.get(&bobs).unwrap().is_some());
+        assert_eq!(store.count().unwrap(), 2);
+        assert_eq!(store.all_by_recent().unwrap().len(), 2);
+        assert_eq!(store.all().unwrap().count(), 2);
+        assert_eq!(store.get(&alices).unwrap().unwrap().comments().count(), 2);
+    }
+
+    #[test]
+    fn test_get_verified() {
+        let t = Network::default();
+        let (alices, reply) = open_and_reply(&t);
+        let seconds = *issues(&t.alice.repo)
+            .create("Second", "Blah blah blah.", &[], &[], [], &t.alice.signer)
+            .unwrap()
+            .id();
+
+        // Alice is the only delegate, so Bob's reply isn't trusted.
+        let store = issue_store(&t.alice.repo);
+        assert!(store.get(&alices).unwrap().is_some());
+        crate::assert_matches!(
+            store.get_verified(&alices),
+            Err(Error::Untrusted(_, id, entry, author))
+            if id == alices && entry == reply && author == *t.bob.signer.public_key()
+        );
+        assert!(store.get_verified(&seconds).unwrap().is_some());
+
+        let (verified, untrusted): (Vec<_>, Vec<_>) =
+            store.all_verified().unwrap().partition(Result::is_ok);
+        assert_eq!(verified.len(), 1);
+        assert_eq!(untrusted.len(), 1);
+
+        // Without Bob's copy of the issue, it's trusted.
+        let store = store.block([*t.bob.signer.public_key()]);
+        assert!(store.get_verified(&alices).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_observe() {
+        let (node, repo) = setup();
+        let events = std::sync::Mutex::new(Vec::new());
+        let store =
+            issue_store(&repo).observe(|id, event| events.lock().unwrap().push((*id, event)));
+        let (id, _) = store
+            .create(
+                "Open",
+                NonEmpty::from((
+                    issue::Action::Comment {
+                        body: String::from("Blah blah blah."),
+                        reply_to: None,
+                        embeds: vec![],
+                    },
+                    vec![edit("First")],
+                )),
+                vec![],
+                &node.signer,
+            )
+            .unwrap();
+        let updated = store
+            .update(id, "Edit", edit("Second"), vec![], &node.signer)
+            .unwrap();
+
+        // Failed writes aren't observed.
+        let missing = ObjectId::from(crate::test::arbitrary::oid());
+        assert!(store
+            .update(missing, "Edit", edit("Third"), vec![], &node.signer)
+            .is_err());
+
+        store.remove(&id, &node.signer).unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (id, StoreEvent::Created { head: *id }),
+                (id, StoreEvent::Updated { head: updated.head }),
+                (id, StoreEvent::Removed),
+            ]
+        );
+    }
 }
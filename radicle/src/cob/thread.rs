@@ -8,7 +8,7 @@ use thiserror::Error;
 
 use crate::cob;
 use crate::cob::common::{Reaction, Timestamp, Uri};
-use crate::cob::store::Cob;
+use crate::cob::store::{Cob, Effect};
 use crate::cob::{op, ActorId, Embed, EntryId, Op};
 use crate::git;
 use crate::prelude::ReadRepository;
@@ -347,6 +347,33 @@ impl<T> Thread<T> {
     pub fn timeline(&self) -> impl DoubleEndedIterator<Item = &EntryId> + '_ {
         self.timeline.iter()
     }
+
+    /// Move a change that was just appended to the timeline to its place, given the changes
+    /// that are concurrent with it. See [`insert`].
+    pub(crate) fn reorder(&mut self, id: EntryId, concurrent: &[&cob::Entry]) {
+        if self.timeline.last() == Some(&id) {
+            self.timeline.pop();
+            insert(&mut self.timeline, id, concurrent);
+        }
+    }
+}
+
+/// Insert a change into a timeline, after the changes it depends on. Changes that are
+/// concurrent with it are ordered by id, so that the timeline doesn't depend on the order in
+/// which concurrent changes are applied.
+pub(crate) fn insert(timeline: &mut Vec<EntryId>, id: EntryId, concurrent: &[&cob::Entry]) {
+    // Changes are applied after their dependencies, so the changes in the timeline that aren't
+    // concurrent with this one are its dependencies.
+    let start = timeline
+        .iter()
+        .rposition(|e| !concurrent.iter().any(|c| c.id() == e))
+        .map_or(0, |i| i + 1);
+    let at = timeline[start..]
+        .iter()
+        .position(|e| *e > id)
+        .map_or(timeline.len(), |i| start + i);
+
+    timeline.insert(at, id);
 }
 
 impl Thread {
@@ -444,9 +471,11 @@ impl cob::store::Cob for Thread {
         op: Op<Action>,
         concurrent: I,
         repo: &R,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<Effect>, Error> {
         let identity = op.identity.ok_or(Error::MissingIdentity)?;
         let concurrent = concurrent.into_iter().collect::<Vec<_>>();
+        let mut effects = Vec::with_capacity(op.actions.len());
+
         for action in op.actions {
             self.action(
                 action,
@@ -457,8 +486,11 @@ impl cob::store::Cob for Thread {
                 identity,
                 repo,
             )?;
+            effects.push(Effect::Applied);
         }
-        Ok(())
+        self.reorder(op.id, &concurrent);
+
+        Ok(effects)
     }
 }
 
@@ -480,7 +512,9 @@ impl<R: ReadRepository> cob::Evaluate<R> for Thread {
     ) -> Result<(), Self::Error> {
         let op = Op::try_from(entry)?;
 
-        self.op(op, concurrent.map(|(_, e)| e), repo)
+        self.op(op, concurrent.map(|(_, e)| e), repo)?;
+
+        Ok(())
     }
 }
 
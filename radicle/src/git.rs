@@ -358,6 +358,36 @@ pub mod refs {
                     .with_namespace(remote.into())
             }
         }
+
+        /// Change key references. See [`crate::cob::store::Store::create_keyed`].
+        ///
+        /// These references are not replicated or signed.
+        pub mod keys {
+            use super::*;
+
+            /// Points to the collaborative object created with the given change key. The
+            /// `<key>` is the hash of the key, as a blob.
+            ///
+            /// `refs/namespaces/<remote>/refs/keys/<typename>/<key>`
+            ///
+            pub fn cob<'a>(
+                remote: &RemoteId,
+                typename: &cob::TypeName,
+                key: &str,
+            ) -> Namespaced<'a> {
+                // SAFETY: Hashing a string in memory doesn't fail.
+                #[allow(clippy::unwrap_used)]
+                let key = git2::Oid::hash_object(git2::ObjectType::Blob, key.as_bytes()).unwrap();
+                // SAFETY: OIDs are valid reference names and valid path component.
+                #[allow(clippy::unwrap_used)]
+                let key = RefString::try_from(key.to_string()).unwrap();
+                #[allow(clippy::unwrap_used)]
+                let key = Component::from_refstr(key).unwrap();
+
+                Qualified::from_components(component!("keys"), Component::from(typename), Some(key))
+                    .with_namespace(remote.into())
+            }
+        }
     }
 
     pub mod workdir {
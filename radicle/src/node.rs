@@ -13,6 +13,7 @@ pub mod seed;
 
 use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::io::{BufRead, BufReader};
+use std::marker::PhantomData;
 use std::ops::{ControlFlow, Deref};
 use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
@@ -47,6 +48,10 @@ pub const DEFAULT_SOCKET_NAME: &str = "control.sock";
 pub const DEFAULT_PORT: u16 = 8776;
 /// Default timeout when waiting for the node to respond with data.
 pub const DEFAULT_TIMEOUT: time::Duration = time::Duration::from_secs(9);
+/// Delay before racing the next address when connecting with [`Node::connect_any`].
+pub const CONNECT_RACE_DELAY: time::Duration = time::Duration::from_millis(250);
+/// Maximum number of commands in flight at once with [`Node::pipeline`].
+pub const PIPELINE_DEPTH: usize = 16;
 /// Maximum length in bytes of a node alias.
 pub const MAX_ALIAS_LENGTH: usize = 32;
 /// Penalty threshold at which point we avoid connecting to this node.
@@ -306,18 +311,29 @@ impl Default for ConnectOptions {
     }
 }
 
+/// Options passed to the "fetch" node command.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchOptions {
+    /// Ask the node to stream [`FetchProgress`] updates before the result.
+    #[serde(default, skip_serializing_if = "crate::serde_ext::is_default")]
+    pub progress: bool,
+}
+
 /// Result of a command, on the node control socket.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CommandResult<T> {
-    /// Response on node socket indicating that a command was carried out successfully.
-    Okay(T),
+    // Errors are matched first: since responses such as [`Success`] ignore unknown fields,
+    // an error would otherwise be taken for an empty successful response.
     /// Response on node socket indicating that an error occured.
     Error {
         /// The reason for the error.
         #[serde(rename = "error")]
         reason: String,
     },
+    /// Response on node socket indicating that a command was carried out successfully.
+    Okay(T),
 }
 
 /// A success response.
@@ -426,8 +442,12 @@ pub enum Command {
     AnnounceRefs { rid: RepoId },
 
     /// Announce local repositories to peers.
+    /// If peers are given, only those peers are announced to, otherwise all peers are.
     #[serde(rename_all = "camelCase")]
-    AnnounceInventory,
+    AnnounceInventory {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        peers: Option<Vec<NodeId>>,
+    },
 
     /// Sync local inventory with node.
     SyncInventory,
@@ -435,6 +455,9 @@ pub enum Command {
     /// Get the current node condiguration.
     Config,
 
+    /// Get the features supported by the node.
+    Features,
+
     /// Get the node's listen addresses.
     ListenAddrs,
 
@@ -446,8 +469,14 @@ pub enum Command {
     },
 
     /// Lookup seeds for the given repository in the routing table.
+    /// If a page size is given, the seeds are ordered by Node ID and returned in pages of
+    /// at most that many seeds, one page per response line.
     #[serde(rename_all = "camelCase")]
-    Seeds { rid: RepoId },
+    Seeds {
+        rid: RepoId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        page_size: Option<usize>,
+    },
 
     /// Get the current peer sessions.
     Sessions,
@@ -458,6 +487,8 @@ pub enum Command {
         rid: RepoId,
         nid: NodeId,
         timeout: time::Duration,
+        #[serde(default, skip_serializing_if = "crate::serde_ext::is_default")]
+        opts: FetchOptions,
     },
 
     /// Seed the given repository.
@@ -503,6 +534,9 @@ pub struct Session {
     pub nid: NodeId,
     pub addr: Address,
     pub state: State,
+    /// Wire protocol version spoken with the peer, if connected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
 }
 
 impl Session {
@@ -645,6 +679,34 @@ pub enum AnnounceEvent {
     Announced,
 }
 
+/// The kind of error a fetch failed with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FetchErrorKind {
+    /// The fetch timed out.
+    Timeout,
+    /// There was no connection to the seed, or it was lost during the fetch.
+    Disconnected,
+    /// Our policies don't allow the fetch, eg. the repository isn't seeded.
+    Policy,
+    /// The data received from the seed failed validation.
+    Validation,
+    /// Any other error. Nodes that don't report the kind of error are assumed to
+    /// report this one.
+    #[default]
+    Other,
+}
+
+impl FetchErrorKind {
+    /// Whether a fetch that failed with this kind of error may succeed if tried again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Timeout | Self::Disconnected | Self::Other => true,
+            Self::Policy | Self::Validation => false,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "status", rename_all = "camelCase")]
 pub enum FetchResult {
@@ -652,9 +714,10 @@ pub enum FetchResult {
         updated: Vec<RefUpdate>,
         namespaces: HashSet<NodeId>,
     },
-    // TODO: Create enum for reason.
     Failed {
         reason: String,
+        #[serde(default)]
+        kind: FetchErrorKind,
     },
 }
 
@@ -682,6 +745,32 @@ impl FetchResult {
     }
 }
 
+/// Fetch progress, streamed by the node when [`FetchOptions::progress`] is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchProgress {
+    /// Pack bytes received so far.
+    pub received_bytes: u64,
+    /// Objects indexed so far, in the pack being received. A fetch can receive more than
+    /// one pack.
+    pub indexed_objects: usize,
+    /// Objects in the pack being received, or zero if not known yet.
+    pub total_objects: usize,
+}
+
+/// A line sent by the node in response to a fetch command.
+///
+/// Zero or more progress updates are sent, eg. `{"progress":{"receivedBytes":1024,..}}`,
+/// followed by exactly one [`FetchResult`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FetchEvent {
+    /// Fetch is ongoing.
+    Progress { progress: FetchProgress },
+    /// Fetch has completed.
+    Result(FetchResult),
+}
+
 impl<S: ToString> From<Result<(Vec<RefUpdate>, HashSet<NodeId>), S>> for FetchResult {
     fn from(value: Result<(Vec<RefUpdate>, HashSet<NodeId>), S>) -> Self {
         match value {
@@ -691,6 +780,7 @@ impl<S: ToString> From<Result<(Vec<RefUpdate>, HashSet<NodeId>), S>> for FetchRe
             },
             Err(err) => Self::Failed {
                 reason: err.to_string(),
+                kind: FetchErrorKind::Other,
             },
         }
     }
@@ -734,7 +824,7 @@ impl FetchResults {
     /// Iterate over failed fetches.
     pub fn failed(&self) -> impl Iterator<Item = (&NodeId, &str)> {
         self.0.iter().filter_map(|(nid, r)| {
-            if let FetchResult::Failed { reason } = r {
+            if let FetchResult::Failed { reason, .. } = r {
                 Some((nid, reason.as_str()))
             } else {
                 None
@@ -773,10 +863,12 @@ pub enum Error {
     Io(#[from] io::Error),
     #[error("node: {0}")]
     Node(String),
-    #[error("timed out reading from control socket")]
+    #[error("timed out waiting for the node on its control socket")]
     TimedOut,
     #[error("failed to open node control socket {0:?} ({1})")]
     Connect(PathBuf, io::ErrorKind),
+    #[error("no node home found in {0:?}")]
+    NoHome(PathBuf),
     #[error("command error: {reason}")]
     Command { reason: String },
     #[error("received invalid json `{response}` in response to command: {error}")]
@@ -789,9 +881,17 @@ pub enum Error {
 }
 
 impl Error {
+    /// Convert an I/O error on the control socket, recognizing socket timeouts.
+    fn from_io(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Self::TimedOut,
+            _ => Self::Io(err),
+        }
+    }
+
     /// Check if the error is due to the not being able to connect to the local node.
     pub fn is_connection_err(&self) -> bool {
-        matches!(self, Self::Connect { .. })
+        matches!(self, Self::Connect { .. } | Self::NoHome { .. })
     }
 }
 
@@ -833,6 +933,15 @@ pub trait Handle: Clone + Sync + Send {
         from: NodeId,
         timeout: time::Duration,
     ) -> Result<FetchResult, Self::Error>;
+    /// Fetch a repository from the network, calling `progress` with the progress of the
+    /// fetch until it completes.
+    fn fetch_with_progress(
+        &mut self,
+        id: RepoId,
+        from: NodeId,
+        timeout: time::Duration,
+        progress: impl FnMut(FetchProgress),
+    ) -> Result<FetchResult, Self::Error>;
     /// Start seeding the given repo. May update the scope. Does nothing if the
     /// repo is already seeded.
     fn seed(&mut self, id: RepoId, scope: policy::Scope) -> Result<bool, Self::Error>;
@@ -846,6 +955,9 @@ pub trait Handle: Clone + Sync + Send {
     fn announce_refs(&mut self, id: RepoId) -> Result<RefsAt, Self::Error>;
     /// Announce local inventory.
     fn announce_inventory(&mut self) -> Result<(), Self::Error>;
+    /// Announce local inventory to the given connected peers only. Nothing is announced if
+    /// no peers are given, see [`Handle::announce_inventory`] to announce to all peers.
+    fn announce_inventory_to(&mut self, peers: Vec<NodeId>) -> Result<(), Self::Error>;
     /// Notify the service that our inventory was updated.
     fn sync_inventory(&mut self) -> Result<bool, Self::Error>;
     /// Ask the service to shutdown.
@@ -866,43 +978,292 @@ pub type NodeId = PublicKey;
 #[derive(Debug, Clone)]
 pub struct Node {
     socket: PathBuf,
+    timeout: time::Duration,
+    reconnect: bool,
 }
 
 impl Node {
     /// Connect to the node, via the socket at the given path.
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self::with_timeout(path, DEFAULT_TIMEOUT)
+    }
+
+    /// Connect to the node, via the socket at the given path, giving up on reads and writes
+    /// that take longer than `timeout`.
+    pub fn with_timeout<P: AsRef<Path>>(path: P, timeout: time::Duration) -> Self {
         Self {
             socket: path.as_ref().to_path_buf(),
+            timeout,
+            reconnect: false,
+        }
+    }
+
+    /// Set the timeout used when talking to the node. See [`Node::with_timeout`].
+    pub fn set_timeout(&mut self, timeout: time::Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Get the timeout used when talking to the node.
+    pub fn timeout(&self) -> time::Duration {
+        self.timeout
+    }
+
+    /// Set whether to reconnect and send a command again when the connection breaks while
+    /// sending it, eg. because the node restarted. Disabled by default.
+    ///
+    /// Commands are never re-sent once the node may have received them, ie. when reading the
+    /// response fails, since they aren't all safe to run twice.
+    pub fn set_reconnect(&mut self, reconnect: bool) {
+        self.reconnect = reconnect;
+    }
+
+    /// Connect to the local node, locating its socket via the Radicle home.
+    ///
+    /// The home is resolved as for [`profile::home`], from `RAD_HOME` or `HOME`, and
+    /// `RAD_SOCKET` overrides the socket path. Unlike [`profile::home`], the home is
+    /// never created.
+    pub fn discover() -> Result<Self, Error> {
+        Self::discover_in(&profile::home_path()?)
+    }
+
+    /// Connect to the node of the given Radicle home.
+    fn discover_in(home: &Path) -> Result<Self, Error> {
+        let dir = home.join("node");
+        if !dir.is_dir() {
+            return Err(Error::NoHome(home.to_path_buf()));
+        }
+        let socket = std::env::var_os(profile::env::RAD_SOCKET)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| dir.join(DEFAULT_SOCKET_NAME));
+
+        if !socket.exists() {
+            return Err(Error::Connect(socket, io::ErrorKind::NotFound));
         }
+        Ok(Self::new(socket))
     }
 
     /// Call a command on the node.
+    ///
+    /// Each line of the response is parsed as a [`CommandResult`], with errors reported by the
+    /// node returned as [`Error::Command`]. See [`Node::call_raw`] for the timeouts.
     pub fn call<T: DeserializeOwned>(
         &self,
         cmd: Command,
         timeout: time::Duration,
     ) -> Result<impl Iterator<Item = Result<T, Error>>, Error> {
+        Ok(self
+            .call_raw(cmd, timeout)?
+            .map(|l| l.and_then(|l| parse_response(&l))))
+    }
+
+    /// Call several commands on the node, sending each command before the responses to the
+    /// previous ones have arrived, so that the node works on them concurrently. This is useful
+    /// to run the same command for many repositories.
+    ///
+    /// At most `depth` commands are in flight at once, each on its own connection. The first
+    /// line of each response is returned, in the order of the commands, so this is only suited
+    /// to commands with a single-line response. Errors are returned per command, and don't
+    /// stop the pipeline. See [`Node::call`] for the timeouts.
+    pub fn pipeline<T, I>(
+        &self,
+        cmds: I,
+        depth: usize,
+        timeout: time::Duration,
+    ) -> Pipeline<T, I::IntoIter>
+    where
+        T: DeserializeOwned,
+        I: IntoIterator<Item = Command>,
+    {
+        Pipeline {
+            node: self.clone(),
+            cmds: cmds.into_iter(),
+            depth: depth.max(1),
+            timeout,
+            in_flight: VecDeque::new(),
+            witness: PhantomData,
+        }
+    }
+
+    /// Call a command on the node, returning the lines of the response as they are received,
+    /// without parsing them.
+    ///
+    /// Sending the command times out after the node's timeout, while reading each line of
+    /// the response times out after the given `timeout`. Both surface as [`Error::TimedOut`].
+    /// See [`Node::set_reconnect`] for what happens when the connection breaks.
+    pub fn call_raw(
+        &self,
+        cmd: Command,
+        timeout: time::Duration,
+    ) -> Result<impl Iterator<Item = Result<String, Error>>, Error> {
+        let stream = self.dispatch(&cmd)?;
+        stream.set_read_timeout(Some(timeout))?;
+
+        Ok(BufReader::new(stream)
+            .lines()
+            .map(|l| l.map_err(Error::from_io)))
+    }
+
+    /// Send a command, reconnecting once if enabled. See [`Node::set_reconnect`].
+    fn dispatch(&self, cmd: &Command) -> Result<UnixStream, Error> {
+        match self.send(cmd) {
+            Err(Error::Io(err))
+                if self.reconnect
+                    && matches!(
+                        err.kind(),
+                        io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset
+                    ) =>
+            {
+                log::debug!(target: "radicle", "Reconnecting to node after error: {err}");
+                // If we can't reconnect, the original error is the more useful one.
+                self.send(cmd).map_err(|_| Error::Io(err))
+            }
+            result => result,
+        }
+    }
+
+    /// Open a connection to the node's control socket and send a command on it.
+    fn send(&self, cmd: &Command) -> Result<UnixStream, Error> {
         let stream = UnixStream::connect(&self.socket)
             .map_err(|e| Error::Connect(self.socket.clone(), e.kind()))?;
-        cmd.to_writer(&stream)?;
 
-        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        cmd.to_writer(&stream).map_err(Error::from_io)?;
 
-        Ok(BufReader::new(stream).lines().map(move |l| {
-            let l = l.map_err(|e| match e.kind() {
-                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Error::TimedOut,
-                _ => Error::Io(e),
-            })?;
+        Ok(stream)
+    }
 
-            let result: CommandResult<T> = json::from_str(&l).map_err(|e| Error::InvalidJson {
-                response: l.clone(),
-                error: e,
-            })?;
+    /// Connect to a node using any of the given addresses.
+    ///
+    /// Addresses are tried "happy eyeballs" style: a connection attempt is started for the
+    /// first address, and if it hasn't completed after `delay`, an attempt for the next address
+    /// is started in parallel, and so on. Failed attempts start the next one immediately.
+    /// The first successful attempt wins, and the attempts still in progress are cancelled by
+    /// closing their connection to the node. If all attempts fail, the last failure is
+    /// returned.
+    pub fn connect_any(
+        &self,
+        nid: NodeId,
+        addrs: impl IntoIterator<Item = Address>,
+        opts: ConnectOptions,
+        delay: time::Duration,
+    ) -> Result<ConnectResult, Error> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut addrs = addrs.into_iter().peekable();
+        // The control socket connection of each attempt, to cancel the attempts in progress.
+        let mut attempts = Vec::new();
+        let mut pending = 0;
+        let mut last = None;
+
+        let result = loop {
+            if let Some(addr) = addrs.next() {
+                let cmd = Command::Connect {
+                    addr: (nid, addr).into(),
+                    opts: opts.clone(),
+                };
+                let stream = match self.attempt(cmd, opts.timeout, tx.clone()) {
+                    Ok(stream) => stream,
+                    Err(e) => break Err(e),
+                };
+                attempts.push(stream);
+                pending += 1;
+            } else if pending == 0 {
+                break last.unwrap_or_else(|| {
+                    Ok(ConnectResult::Disconnected {
+                        reason: String::from("no addresses to connect to"),
+                    })
+                });
+            }
+            let result = if addrs.peek().is_some() {
+                match rx.recv_timeout(delay) {
+                    Ok(result) => result,
+                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => continue,
+                    Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+                        break last.unwrap_or(Err(Error::EmptyResponse))
+                    }
+                }
+            } else {
+                match rx.recv() {
+                    Ok(result) => result,
+                    Err(crossbeam_channel::RecvError) => {
+                        break last.unwrap_or(Err(Error::EmptyResponse))
+                    }
+                }
+            };
+            pending -= 1;
 
-            match result {
-                CommandResult::Okay(result) => Ok(result),
-                CommandResult::Error { reason } => Err(Error::Command { reason }),
+            if let Ok(ConnectResult::Connected) = result {
+                break result;
             }
+            last = Some(result);
+        };
+        for stream in attempts {
+            // Attempts that are already done have closed their end, which is fine.
+            stream.shutdown(net::Shutdown::Both).ok();
+        }
+        result
+    }
+
+    /// Send a connect command, and wait for its result on a separate thread, which sends it
+    /// on the given channel. Returns the connection to the node the result is read from,
+    /// which can be shut down to cancel the attempt. See [`Node::connect_any`].
+    fn attempt(
+        &self,
+        cmd: Command,
+        timeout: time::Duration,
+        results: crossbeam_channel::Sender<Result<ConnectResult, Error>>,
+    ) -> Result<UnixStream, Error> {
+        let stream = self.dispatch(&cmd)?;
+        stream.set_read_timeout(Some(timeout))?;
+        let reader = stream.try_clone()?;
+
+        thread::Builder::new()
+            .name(String::from("connect"))
+            .spawn(move || {
+                let result = BufReader::new(reader)
+                    .lines()
+                    .next()
+                    .ok_or(Error::EmptyResponse)
+                    .and_then(|line| parse_response(&line.map_err(Error::from_io)?));
+                results.send(result).ok();
+            })?;
+
+        Ok(stream)
+    }
+
+    /// Get the features supported by the node.
+    ///
+    /// All the bits set by the node are kept, including those of features this version
+    /// doesn't know about. Nodes that predate this command return an [`Error::Command`].
+    pub fn features(&self) -> Result<Features, Error> {
+        self.call::<Features>(Command::Features, self.timeout)?
+            .next()
+            .ok_or(Error::EmptyResponse)?
+    }
+
+    /// Check whether the node supports the given features. See [`Node::features`].
+    pub fn supports(&self, features: Features) -> Result<bool, Error> {
+        self.features().map(|f| f.has(features))
+    }
+
+    /// Iterate over the seeds of the given repository, ordered by Node ID. The node looks the
+    /// seeds up once and sends them `page_size` at a time, so unlike with [`Handle::seeds`],
+    /// the first seeds can be used before the whole list is received.
+    pub fn seeds_paged(
+        &self,
+        rid: RepoId,
+        page_size: usize,
+    ) -> Result<impl Iterator<Item = Result<Seed, Error>>, Error> {
+        let pages = self.call::<Vec<Seed>>(
+            Command::Seeds {
+                rid,
+                page_size: Some(page_size.max(1)),
+            },
+            self.timeout,
+        )?;
+
+        Ok(pages.flat_map(|page| match page {
+            Ok(seeds) => seeds.into_iter().map(Ok).collect::<Vec<_>>(),
+            Err(e) => vec![Err(e)],
         }))
     }
 
@@ -962,6 +1323,60 @@ impl Node {
     }
 }
 
+/// Parse a line of a command's response.
+fn parse_response<T: DeserializeOwned>(line: &str) -> Result<T, Error> {
+    let result: CommandResult<T> = json::from_str(line).map_err(|e| Error::InvalidJson {
+        response: line.to_owned(),
+        error: e,
+    })?;
+
+    match result {
+        CommandResult::Okay(result) => Ok(result),
+        CommandResult::Error { reason } => Err(Error::Command { reason }),
+    }
+}
+
+/// Iterator over the responses to pipelined commands. See [`Node::pipeline`].
+pub struct Pipeline<T, I> {
+    node: Node,
+    cmds: I,
+    depth: usize,
+    timeout: time::Duration,
+    /// Connections of the commands sent, or the errors sending them, in order.
+    in_flight: VecDeque<Result<UnixStream, Error>>,
+    witness: PhantomData<T>,
+}
+
+impl<T, I> Iterator for Pipeline<T, I>
+where
+    T: DeserializeOwned,
+    I: Iterator<Item = Command>,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.in_flight.len() < self.depth {
+            let Some(cmd) = self.cmds.next() else {
+                break;
+            };
+            self.in_flight.push_back(self.node.dispatch(&cmd));
+        }
+        let stream = match self.in_flight.pop_front()? {
+            Ok(stream) => stream,
+            Err(e) => return Some(Err(e)),
+        };
+        if let Err(e) = stream.set_read_timeout(Some(self.timeout)) {
+            return Some(Err(e.into()));
+        }
+        let line = match BufReader::new(stream).lines().next() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => return Some(Err(Error::from_io(e))),
+            None => return Some(Err(Error::EmptyResponse)),
+        };
+        Some(parse_response(&line))
+    }
+}
+
 // TODO(finto): repo_policies, node_policies, and routing should all
 // attempt to return iterators instead of allocating vecs.
 impl Handle for Node {
@@ -969,21 +1384,21 @@ impl Handle for Node {
     type Error = Error;
 
     fn nid(&self) -> Result<NodeId, Error> {
-        self.call::<NodeId>(Command::NodeId, DEFAULT_TIMEOUT)?
+        self.call::<NodeId>(Command::NodeId, self.timeout)?
             .next()
             .ok_or(Error::EmptyResponse)?
             .map_err(Error::from)
     }
 
     fn listen_addrs(&self) -> Result<Vec<net::SocketAddr>, Error> {
-        self.call::<Vec<net::SocketAddr>>(Command::ListenAddrs, DEFAULT_TIMEOUT)?
+        self.call::<Vec<net::SocketAddr>>(Command::ListenAddrs, self.timeout)?
             .next()
             .ok_or(Error::EmptyResponse)?
             .map_err(Error::from)
     }
 
     fn is_running(&self) -> bool {
-        let Ok(mut lines) = self.call::<Success>(Command::Status, DEFAULT_TIMEOUT) else {
+        let Ok(mut lines) = self.call::<Success>(Command::Status, self.timeout) else {
             return false;
         };
         let Some(Ok(_)) = lines.next() else {
@@ -993,7 +1408,7 @@ impl Handle for Node {
     }
 
     fn config(&self) -> Result<config::Config, Error> {
-        self.call::<config::Config>(Command::Config, DEFAULT_TIMEOUT)?
+        self.call::<config::Config>(Command::Config, self.timeout)?
             .next()
             .ok_or(Error::EmptyResponse)?
             .map_err(Error::from)
@@ -1022,7 +1437,13 @@ impl Handle for Node {
 
     fn seeds(&mut self, rid: RepoId) -> Result<Seeds, Error> {
         let seeds = self
-            .call::<Seeds>(Command::Seeds { rid }, DEFAULT_TIMEOUT)?
+            .call::<Seeds>(
+                Command::Seeds {
+                    rid,
+                    page_size: None,
+                },
+                self.timeout,
+            )?
             .next()
             .ok_or(Error::EmptyResponse)??;
 
@@ -1041,8 +1462,9 @@ impl Handle for Node {
                     rid,
                     nid: from,
                     timeout,
+                    opts: FetchOptions::default(),
                 },
-                DEFAULT_TIMEOUT,
+                self.timeout,
             )?
             .next()
             .ok_or(Error::EmptyResponse)??;
@@ -1050,29 +1472,55 @@ impl Handle for Node {
         Ok(result)
     }
 
+    fn fetch_with_progress(
+        &mut self,
+        rid: RepoId,
+        from: NodeId,
+        timeout: time::Duration,
+        mut progress: impl FnMut(FetchProgress),
+    ) -> Result<FetchResult, Error> {
+        let events = self.call::<FetchEvent>(
+            Command::Fetch {
+                rid,
+                nid: from,
+                timeout,
+                opts: FetchOptions { progress: true },
+            },
+            self.timeout,
+        )?;
+        // Nodes that don't report progress only send the final result.
+        for event in events {
+            match event? {
+                FetchEvent::Progress { progress: p } => progress(p),
+                FetchEvent::Result(result) => return Ok(result),
+            }
+        }
+        Err(Error::EmptyResponse)
+    }
+
     fn follow(&mut self, nid: NodeId, alias: Option<Alias>) -> Result<bool, Error> {
-        let mut line = self.call::<Success>(Command::Follow { nid, alias }, DEFAULT_TIMEOUT)?;
+        let mut line = self.call::<Success>(Command::Follow { nid, alias }, self.timeout)?;
         let response = line.next().ok_or(Error::EmptyResponse)??;
 
         Ok(response.updated)
     }
 
     fn seed(&mut self, rid: RepoId, scope: policy::Scope) -> Result<bool, Error> {
-        let mut line = self.call::<Success>(Command::Seed { rid, scope }, DEFAULT_TIMEOUT)?;
+        let mut line = self.call::<Success>(Command::Seed { rid, scope }, self.timeout)?;
         let response = line.next().ok_or(Error::EmptyResponse)??;
 
         Ok(response.updated)
     }
 
     fn unfollow(&mut self, nid: NodeId) -> Result<bool, Error> {
-        let mut line = self.call::<Success>(Command::Unfollow { nid }, DEFAULT_TIMEOUT)?;
+        let mut line = self.call::<Success>(Command::Unfollow { nid }, self.timeout)?;
         let response = line.next().ok_or(Error::EmptyResponse)??;
 
         Ok(response.updated)
     }
 
     fn unseed(&mut self, rid: RepoId) -> Result<bool, Error> {
-        let mut line = self.call::<Success>(Command::Unseed { rid }, DEFAULT_TIMEOUT)?;
+        let mut line = self.call::<Success>(Command::Unseed { rid }, self.timeout)?;
         let response = line.next().ok_or(Error::EmptyResponse {})??;
 
         Ok(response.updated)
@@ -1080,7 +1528,7 @@ impl Handle for Node {
 
     fn announce_refs(&mut self, rid: RepoId) -> Result<RefsAt, Error> {
         let refs: RefsAt = self
-            .call(Command::AnnounceRefs { rid }, DEFAULT_TIMEOUT)?
+            .call(Command::AnnounceRefs { rid }, self.timeout)?
             .next()
             .ok_or(Error::EmptyResponse)??;
 
@@ -1088,14 +1536,25 @@ impl Handle for Node {
     }
 
     fn announce_inventory(&mut self) -> Result<(), Error> {
-        for line in self.call::<Success>(Command::AnnounceInventory, DEFAULT_TIMEOUT)? {
+        let cmd = Command::AnnounceInventory { peers: None };
+
+        for line in self.call::<Success>(cmd, self.timeout)? {
+            line?;
+        }
+        Ok(())
+    }
+
+    fn announce_inventory_to(&mut self, peers: Vec<NodeId>) -> Result<(), Error> {
+        let cmd = Command::AnnounceInventory { peers: Some(peers) };
+
+        for line in self.call::<Success>(cmd, self.timeout)? {
             line?;
         }
         Ok(())
     }
 
     fn sync_inventory(&mut self) -> Result<bool, Error> {
-        let mut line = self.call::<Success>(Command::SyncInventory, DEFAULT_TIMEOUT)?;
+        let mut line = self.call::<Success>(Command::SyncInventory, self.timeout)?;
         let response = line.next().ok_or(Error::EmptyResponse {})??;
 
         Ok(response.updated)
@@ -1112,7 +1571,7 @@ impl Handle for Node {
 
     fn sessions(&self) -> Result<Self::Sessions, Error> {
         let sessions = self
-            .call::<Vec<Session>>(Command::Sessions, DEFAULT_TIMEOUT)?
+            .call::<Vec<Session>>(Command::Sessions, self.timeout)?
             .next()
             .ok_or(Error::EmptyResponse {})??;
 
@@ -1120,9 +1579,12 @@ impl Handle for Node {
     }
 
     fn shutdown(self) -> Result<(), Error> {
-        for line in self.call::<Success>(Command::Shutdown, DEFAULT_TIMEOUT)? {
-            line?;
-        }
+        // Only the acknowledgement is read, since the node goes away right after sending it.
+        // The socket is closed when the response is dropped.
+        self.call::<Success>(Command::Shutdown, self.timeout)?
+            .next()
+            .ok_or(Error::EmptyResponse)??;
+
         // Wait until the shutdown has completed.
         while self.is_running() {
             thread::sleep(time::Duration::from_secs(1));
@@ -1163,6 +1625,378 @@ mod test {
         assert!(Alias::from_str("cloudhead\n").is_err());
     }
 
+    #[test]
+    fn test_fetch_event() {
+        assert_matches!(
+            json::from_str::<CommandResult<FetchEvent>>(
+                r#"{"progress":{"receivedBytes":1024,"indexedObjects":3,"totalObjects":8}}"#
+            ),
+            Ok(CommandResult::Okay(FetchEvent::Progress {
+                progress: FetchProgress {
+                    received_bytes: 1024,
+                    indexed_objects: 3,
+                    total_objects: 8,
+                }
+            }))
+        );
+        assert_matches!(
+            json::from_str::<CommandResult<FetchEvent>>(
+                r#"{"status":"success","updated":[],"namespaces":[]}"#
+            ),
+            Ok(CommandResult::Okay(FetchEvent::Result(
+                FetchResult::Success { .. }
+            )))
+        );
+        assert_matches!(
+            json::from_str::<CommandResult<FetchEvent>>(r#"{"status":"failed","reason":"oops"}"#),
+            Ok(CommandResult::Okay(FetchEvent::Result(
+                FetchResult::Failed { .. }
+            )))
+        );
+        assert_matches!(
+            json::from_str::<CommandResult<FetchEvent>>(r#"{"error":"oops"}"#),
+            Ok(CommandResult::Error { .. })
+        );
+        assert_eq!(
+            json::to_string(&FetchEvent::Progress {
+                progress: FetchProgress {
+                    received_bytes: 1,
+                    indexed_objects: 2,
+                    total_objects: 3,
+                }
+            })
+            .unwrap(),
+            r#"{"progress":{"receivedBytes":1,"indexedObjects":2,"totalObjects":3}}"#
+        );
+    }
+
+    #[test]
+    fn test_discover() {
+        use std::os::unix::net::UnixListener;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().join("home");
+
+        assert_matches!(Node::discover_in(&home), Err(Error::NoHome(path)) if path == home);
+
+        let home = profile::Home::new(&home).unwrap();
+        assert_matches!(
+            Node::discover_in(home.path()),
+            Err(Error::Connect(_, io::ErrorKind::NotFound))
+        );
+
+        let socket = home.node().join(DEFAULT_SOCKET_NAME);
+        let _listener = UnixListener::bind(&socket).unwrap();
+        let node = Node::discover_in(home.path()).unwrap();
+
+        assert_eq!(node.socket, socket);
+    }
+
+    /// Serve a single command on a mock control socket, replying with the given lines.
+    fn serve(socket: &Path, response: &'static str) -> thread::JoinHandle<Command> {
+        use std::io::Write as _;
+        use std::os::unix::net::UnixListener;
+
+        let listener = UnixListener::bind(socket).unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+
+            json::from_str(line.trim_end()).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_policy_responses() {
+        let tmp = tempfile::tempdir().unwrap();
+        let nid = crate::test::arbitrary::gen::<NodeId>(1);
+        let rid = crate::test::arbitrary::gen::<RepoId>(1);
+        let mut sockets = (0..).map(|i| tmp.path().join(format!("node-{i}.sock")));
+
+        // The node replies whether the policy changed.
+        let socket = sockets.next().unwrap();
+        let server = serve(&socket, "{\"updated\":true}\n");
+        assert!(Node::new(&socket).follow(nid, None).unwrap());
+        assert_matches!(server.join().unwrap(), Command::Follow { nid: n, .. } if n == nid);
+
+        let socket = sockets.next().unwrap();
+        let server = serve(&socket, "{}\n");
+        assert!(!Node::new(&socket).unseed(rid).unwrap());
+        assert_matches!(server.join().unwrap(), Command::Unseed { rid: r } if r == rid);
+
+        // Empty and malformed responses are errors.
+        let socket = sockets.next().unwrap();
+        let server = serve(&socket, "");
+        assert_matches!(
+            Node::new(&socket).seed(rid, policy::Scope::All),
+            Err(Error::EmptyResponse)
+        );
+        server.join().unwrap();
+
+        let socket = sockets.next().unwrap();
+        let server = serve(&socket, "updated\n");
+        assert_matches!(
+            Node::new(&socket).unfollow(nid),
+            Err(Error::InvalidJson { .. })
+        );
+        server.join().unwrap();
+
+        let socket = sockets.next().unwrap();
+        let server = serve(&socket, "{\"error\":\"database is locked\"}\n");
+        assert_matches!(
+            Node::new(&socket).follow(nid, None),
+            Err(Error::Command { reason }) if reason == "database is locked"
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_call_raw() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let response = "{\"error\":\"repository not found\"}\nnot json\n";
+
+        let server = serve(&socket, response);
+        let lines = Node::new(&socket)
+            .call_raw(Command::Config, DEFAULT_TIMEOUT)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec!["{\"error\":\"repository not found\"}", "not json"]
+        );
+        assert_matches!(server.join().unwrap(), Command::Config);
+
+        // The same response, parsed.
+        let socket = tmp.path().join("node-parsed.sock");
+        let server = serve(&socket, response);
+        let mut results = Node::new(&socket)
+            .call::<config::Config>(Command::Config, DEFAULT_TIMEOUT)
+            .unwrap();
+        assert_matches!(
+            results.next(),
+            Some(Err(Error::Command { reason })) if reason == "repository not found"
+        );
+        assert_matches!(results.next(), Some(Err(Error::InvalidJson { .. })));
+        assert_matches!(results.next(), None);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_features() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        // A seed, with a feature we don't know about.
+        let server = serve(&socket, "9\n");
+        let features = Node::new(&socket).features().unwrap();
+
+        assert_matches!(server.join().unwrap(), Command::Features);
+        assert_eq!(features, Features::SEED.with(Features::from(0b1000)));
+        assert_eq!(u64::from(features), 9);
+
+        let socket = tmp.path().join("node-supports.sock");
+        let server = serve(&socket, "1\n");
+        assert!(Node::new(&socket).supports(Features::SEED).unwrap());
+        server.join().unwrap();
+
+        let socket = tmp.path().join("node-unsupported.sock");
+        let server = serve(&socket, "1\n");
+        assert!(!Node::new(&socket).supports(Features::from(0b10)).unwrap());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_pipeline() {
+        use std::io::Write as _;
+        use std::os::unix::net::UnixListener;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        // A node that only replies once it has received all the commands, which it answers
+        // in reverse order.
+        let server = thread::spawn(move || {
+            let mut streams = Vec::new();
+            for _ in 0..3 {
+                let (stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                streams.push(stream);
+            }
+            for (i, mut stream) in streams.into_iter().enumerate().rev() {
+                let response = if i == 1 {
+                    String::from("{\"error\":\"repository not found\"}\n")
+                } else {
+                    format!("\"{i}\"\n")
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let results = Node::new(&socket)
+            .pipeline::<String, _>(
+                [Command::NodeId, Command::NodeId, Command::NodeId],
+                3,
+                DEFAULT_TIMEOUT,
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert_matches!(&results[0], Ok(r) if r == "0");
+        assert_matches!(
+            &results[1],
+            Err(Error::Command { reason }) if reason == "repository not found"
+        );
+        assert_matches!(&results[2], Ok(r) if r == "2");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_timeout() {
+        use std::os::unix::net::UnixListener;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let (done_tx, done_rx) = crossbeam_channel::bounded::<()>(1);
+
+        // A wedged node: accepts the command but never replies.
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            done_rx.recv().ok();
+        });
+
+        let mut node = Node::new(&socket);
+        assert_eq!(node.timeout(), DEFAULT_TIMEOUT);
+
+        node.set_timeout(time::Duration::from_millis(100));
+        assert_matches!(node.nid(), Err(Error::TimedOut));
+        assert!(!node.is_running());
+
+        done_tx.send(()).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_shutdown() {
+        use std::io::Write as _;
+        use std::os::unix::net::UnixListener;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+
+            json::to_writer(&mut stream, &CommandResult::ok()).unwrap();
+            stream.write_all(b"\n").unwrap();
+
+            // The listener is dropped here, as if the node had shut down.
+            line
+        });
+        Node::new(&socket).shutdown().unwrap();
+
+        let command: Command = json::from_str(server.join().unwrap().trim_end()).unwrap();
+        assert_matches!(command, Command::Shutdown);
+
+        // Once the node is gone, shutting it down fails to connect.
+        assert_matches!(
+            Node::new(&socket).shutdown(),
+            Err(Error::Connect(_, io::ErrorKind::ConnectionRefused))
+        );
+        assert_matches!(
+            Node::new(tmp.path().join("missing.sock")).shutdown(),
+            Err(Error::Connect(_, io::ErrorKind::NotFound))
+        );
+    }
+
+    #[test]
+    fn test_connect_any() {
+        use std::io::Write as _;
+        use std::os::unix::net::UnixListener;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let dead = Address::from(net::SocketAddr::from(([127, 0, 0, 1], 1)));
+        let live = Address::from(net::SocketAddr::from(([127, 0, 0, 1], 2)));
+        let nid = crate::test::arbitrary::gen::<NodeId>(1);
+        let (cancelled, cancellations) = std::sync::mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let cancelled = cancelled.clone();
+
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(&stream);
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+
+                    if line.contains("127.0.0.1:1\"") {
+                        // The dead address never connects, until the attempt is cancelled.
+                        line.clear();
+                        reader.read_line(&mut line).ok();
+                        cancelled.send(()).ok();
+                        return;
+                    }
+                    json::to_writer(&mut stream, &ConnectResult::Connected).unwrap();
+                    stream.write_all(b"\n").ok();
+                });
+            }
+        });
+
+        let node = Node::new(&socket);
+        let opts = ConnectOptions {
+            persistent: false,
+            timeout: time::Duration::from_secs(9),
+        };
+        let started = time::Instant::now();
+        let result = node
+            .connect_any(nid, [dead, live], opts, time::Duration::from_millis(100))
+            .unwrap();
+
+        assert_matches!(result, ConnectResult::Connected);
+        assert!(started.elapsed() < time::Duration::from_secs(3));
+
+        // The losing attempt is cancelled, rather than left waiting for the dead address.
+        cancellations
+            .recv_timeout(time::Duration::from_secs(3))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fetch_result_kind() {
+        assert_matches!(
+            json::from_str::<FetchResult>(
+                r#"{"status":"failed","reason":"oops","kind":"validation"}"#
+            ),
+            Ok(FetchResult::Failed {
+                kind: FetchErrorKind::Validation,
+                ..
+            })
+        );
+        // Nodes that don't report the kind of error.
+        assert_matches!(
+            json::from_str::<FetchResult>(r#"{"status":"failed","reason":"oops"}"#),
+            Ok(FetchResult::Failed {
+                kind: FetchErrorKind::Other,
+                ..
+            })
+        );
+        assert!(FetchErrorKind::Timeout.is_transient());
+        assert!(!FetchErrorKind::Policy.is_transient());
+    }
+
     #[test]
     fn test_command_result() {
         #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -327,9 +327,16 @@ where
     /// Retrieve `alias` of given node.
     /// Calls `Self::get` under the hood.
     fn alias(&self, nid: &NodeId) -> Option<Alias> {
-        self.get(nid)
-            .map(|node| node.map(|n| n.alias))
-            .unwrap_or(None)
+        match self.get(nid) {
+            Ok(node) => node.map(|n| n.alias),
+            Err(e) => {
+                log::warn!(
+                    target: "radicle",
+                    "Failed to lookup alias of {nid} in address book: {e}"
+                );
+                None
+            }
+        }
     }
 }
 
@@ -324,9 +324,16 @@ impl<T> AliasStore for Store<T> {
     /// Retrieve `alias` of given node.
     /// Calls `Self::node_policy` under the hood.
     fn alias(&self, nid: &NodeId) -> Option<Alias> {
-        self.follow_policy(nid)
-            .map(|node| node.and_then(|n| n.alias))
-            .unwrap_or(None)
+        match self.follow_policy(nid) {
+            Ok(node) => node.and_then(|n| n.alias),
+            Err(e) => {
+                log::warn!(
+                    target: "radicle",
+                    "Failed to lookup alias of {nid} in policies: {e}"
+                );
+                None
+            }
+        }
     }
 }
 
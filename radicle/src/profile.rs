@@ -136,6 +136,9 @@ pub struct Config {
     /// CLI configuration.
     #[serde(default)]
     pub cli: cli::Config,
+    /// Sync configuration.
+    #[serde(default, skip_serializing_if = "crate::serde_ext::is_default")]
+    pub sync: cli::SyncConfig,
     /// Node configuration.
     pub node: node::Config,
 }
@@ -150,6 +153,7 @@ impl Config {
             preferred_seeds: node.network.public_seeds(),
             web: web::Config::default(),
             cli: cli::Config::default(),
+            sync: cli::SyncConfig::default(),
             node,
         }
     }
@@ -321,10 +325,7 @@ impl Profile {
 
     /// Return a multi-source store for aliases.
     pub fn aliases(&self) -> Aliases {
-        let policies = self.home.policies().ok();
-        let db = self.home.database().ok();
-
-        Aliases { policies, db }
+        self.home.aliases()
     }
 }
 
@@ -368,10 +369,15 @@ impl AliasStore for Aliases {
 
 /// Get the path to the radicle home folder.
 pub fn home() -> Result<Home, io::Error> {
+    Home::new(home_path()?)
+}
+
+/// Get the path to the radicle home folder, without creating it.
+pub fn home_path() -> Result<PathBuf, io::Error> {
     if let Some(home) = env::var_os(env::RAD_HOME) {
-        Ok(Home::new(PathBuf::from(home))?)
+        Ok(PathBuf::from(home))
     } else if let Some(home) = env::var_os("HOME") {
-        Ok(Home::new(PathBuf::from(home).join(".radicle"))?)
+        Ok(PathBuf::from(home).join(".radicle"))
     } else {
         Err(io::Error::new(
             io::ErrorKind::NotFound,
@@ -448,6 +454,12 @@ impl Home {
         self.path.join("cobs")
     }
 
+    /// Directory of the files kept by the CLI between invocations. It is created by the CLI
+    /// when first needed.
+    pub fn cli(&self) -> PathBuf {
+        self.path.join("cli")
+    }
+
     pub fn socket(&self) -> PathBuf {
         env::var_os(env::RAD_SOCKET)
             .map(PathBuf::from)
@@ -488,6 +500,38 @@ impl Home {
         Ok(db)
     }
 
+    /// Return a multi-source store for aliases.
+    ///
+    /// Alias stores that can't be opened are skipped with a warning, so that
+    /// lookups fall back to the remaining stores, or to no alias at all.
+    pub fn aliases(&self) -> Aliases {
+        let node = self.node();
+        let policies = self
+            .policies()
+            .map_err(|e| {
+                if node.join(node::POLICIES_DB_FILE).exists() {
+                    log::warn!(
+                        target: "radicle",
+                        "Failed to open policies for alias lookups: {e}"
+                    );
+                }
+            })
+            .ok();
+        let db = self
+            .database()
+            .map_err(|e| {
+                if node.join(node::NODE_DB_FILE).exists() {
+                    log::warn!(
+                        target: "radicle",
+                        "Failed to open node database for alias lookups: {e}"
+                    );
+                }
+            })
+            .ok();
+
+        Aliases { policies, db }
+    }
+
     /// Return a read-only handle for the issues cache.
     pub fn issues<'a, R>(
         &self,
@@ -498,7 +542,7 @@ impl Home {
     {
         let path = self.cobs().join(cob::cache::COBS_DB_FILE);
         let db = cob::cache::Store::reader(path)?;
-        let store = cob::issue::Issues::open(repository)?;
+        let store = cob::issue::Issues::open(repository)?.block(self.blocked());
         Ok(cob::issue::Cache::reader(store, db))
     }
 
@@ -512,7 +556,7 @@ impl Home {
     {
         let path = self.cobs().join(cob::cache::COBS_DB_FILE);
         let db = cob::cache::Store::open(path)?;
-        let store = cob::issue::Issues::open(repository)?;
+        let store = cob::issue::Issues::open(repository)?.block(self.blocked());
         Ok(cob::issue::Cache::open(store, db))
     }
 
@@ -526,7 +570,7 @@ impl Home {
     {
         let path = self.cobs().join(cob::cache::COBS_DB_FILE);
         let db = cob::cache::Store::reader(path)?;
-        let store = cob::patch::Patches::open(repository)?;
+        let store = cob::patch::Patches::open(repository)?.block(self.blocked());
         Ok(cob::patch::Cache::reader(store, db))
     }
 
@@ -540,7 +584,7 @@ impl Home {
     {
         let path = self.cobs().join(cob::cache::COBS_DB_FILE);
         let db = cob::cache::Store::open(path)?;
-        let store = cob::patch::Patches::open(repository)?;
+        let store = cob::patch::Patches::open(repository)?.block(self.blocked());
         Ok(cob::patch::Cache::open(store, db))
     }
 }
@@ -554,6 +598,31 @@ impl Home {
 
         Ok(config)
     }
+
+    /// Return the nodes blocked by the follow policy, whose contributions are hidden from the
+    /// collaborative objects opened through the profile. See [`cob::store::Store::block`].
+    ///
+    /// If the policies can't be read, no node is blocked, and a warning is logged, so that
+    /// the objects can still be opened.
+    fn blocked(&self) -> Vec<NodeId> {
+        // Without a policies database, eg. if the node never ran, no node is blocked.
+        if !self.node().join(node::POLICIES_DB_FILE).exists() {
+            return Vec::new();
+        }
+        match self.policies().and_then(|p| p.follow_policies()) {
+            Ok(policies) => policies
+                .filter(|p| p.policy == policy::Policy::Block)
+                .map(|p| p.nid)
+                .collect(),
+            Err(e) => {
+                log::warn!(
+                    target: "radicle",
+                    "Failed to read follow policies, no node is blocked: {e}"
+                );
+                Vec::new()
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -562,6 +631,8 @@ mod test {
     use std::fs;
 
     use super::Home;
+    use crate::node::{self, AliasStore as _};
+    use crate::test::arbitrary;
 
     // Checks that if we have:
     // '/run/user/1000/.tmpqfK6ih/../.tmpqfK6ih/Radicle/Home'
@@ -586,4 +657,49 @@ mod test {
 
         assert_eq!(home.path, path);
     }
+
+    #[test]
+    fn aliases_corrupt_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = Home::new(tmp.path()).unwrap();
+        let nid = arbitrary::gen::<node::NodeId>(1);
+
+        // Simulate corrupt alias stores: lookups should degrade to no alias
+        // instead of failing.
+        fs::write(home.node().join(node::POLICIES_DB_FILE), b"garbage").unwrap();
+        fs::write(home.node().join(node::NODE_DB_FILE), b"garbage").unwrap();
+
+        let aliases = home.aliases();
+        assert!(aliases.policies.is_none());
+        assert_eq!(aliases.alias(&nid), None);
+
+        // A working store is still used when the other one is corrupt.
+        fs::remove_file(home.node().join(node::POLICIES_DB_FILE)).unwrap();
+        home.policies_mut()
+            .unwrap()
+            .follow(&nid, Some("alice"))
+            .unwrap();
+
+        let aliases = home.aliases();
+        assert_eq!(aliases.alias(&nid), Some(node::Alias::new("alice")));
+    }
+
+    #[test]
+    fn blocked_corrupt_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = Home::new(tmp.path()).unwrap();
+        let nid = arbitrary::gen::<node::NodeId>(1);
+
+        // A corrupt policies store blocks no one, instead of failing.
+        fs::write(home.node().join(node::POLICIES_DB_FILE), b"garbage").unwrap();
+        assert!(home.blocked().is_empty());
+
+        fs::remove_file(home.node().join(node::POLICIES_DB_FILE)).unwrap();
+        home.policies_mut()
+            .unwrap()
+            .set_follow_policy(&nid, node::policy::Policy::Block)
+            .unwrap();
+
+        assert_eq!(home.blocked(), vec![nid]);
+    }
 }
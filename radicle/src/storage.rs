@@ -546,8 +546,21 @@ pub trait ReadRepository: Sized + ValidateRepository {
 
     /// Get the merge base of two commits.
     fn merge_base(&self, left: &Oid, right: &Oid) -> Result<Oid, git::ext::Error>;
+
+    /// Get the current storage generation of this repository.
+    ///
+    /// The generation is a digest of the name and target of every reference, so it changes
+    /// whenever any reference is written, including by other processes and by writes that
+    /// don't update the signed refs. References are read without looking up the objects
+    /// they point to.
+    fn generation(&self) -> Result<Generation, git::ext::Error>;
 }
 
+/// Identifies the state of a repository's references at a point in time.
+/// See [`ReadRepository::generation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Generation(Oid);
+
 /// Access the remotes of a repository.
 pub trait RemoteRepository {
     /// Get the given remote.
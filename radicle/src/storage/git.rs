@@ -19,7 +19,7 @@ use crate::identity::{Identity, Project};
 use crate::storage::refs;
 use crate::storage::refs::{Refs, SignedRefs, SignedRefsAt};
 use crate::storage::{
-    Inventory, ReadRepository, ReadStorage, Remote, Remotes, RepositoryError, SetHead,
+    Generation, Inventory, ReadRepository, ReadStorage, Remote, Remotes, RepositoryError, SetHead,
     SignRepository, WriteRepository, WriteStorage,
 };
 
@@ -814,6 +814,31 @@ impl ReadRepository for Repository {
             .map(Oid::from)
             .map_err(git::ext::Error::from)
     }
+
+    fn generation(&self) -> Result<Generation, git::ext::Error> {
+        let mut refs = Vec::new();
+        for r in self.backend.references()? {
+            let r = r?;
+            let target = match r.target() {
+                Some(oid) => oid.to_string().into_bytes(),
+                None => r.symbolic_target_bytes().unwrap_or_default().to_vec(),
+            };
+            refs.push((r.name_bytes().to_vec(), target));
+        }
+        // References aren't listed in a stable order.
+        refs.sort();
+
+        let mut buf = Vec::new();
+        for (name, target) in refs {
+            buf.extend_from_slice(&name);
+            buf.push(b' ');
+            buf.extend_from_slice(&target);
+            buf.push(b'\n');
+        }
+        let digest = git2::Oid::hash_object(git2::ObjectType::Blob, &buf)?;
+
+        Ok(Generation(digest.into()))
+    }
 }
 
 impl WriteRepository for Repository {
@@ -1297,6 +1322,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_generation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let rid = *storage.inventory().unwrap().first().unwrap();
+        let repo = storage.repository(rid).unwrap();
+        let generation = repo.generation().unwrap();
+
+        assert_eq!(repo.generation().unwrap(), generation);
+
+        // Writing a reference that isn't signed still changes the generation.
+        let (_, head) = repo.head().unwrap();
+        repo.backend
+            .reference(
+                "refs/namespaces/scratch/refs/heads/tmp",
+                *head,
+                false,
+                "test",
+            )
+            .unwrap();
+        let changed = repo.generation().unwrap();
+        assert_ne!(changed, generation);
+
+        repo.backend
+            .find_reference("refs/namespaces/scratch/refs/heads/tmp")
+            .unwrap()
+            .delete()
+            .unwrap();
+        assert_eq!(repo.generation().unwrap(), generation);
+    }
+
     #[test]
     fn test_sign_refs() {
         let tmp = tempfile::tempdir().unwrap();
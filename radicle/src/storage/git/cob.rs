@@ -145,16 +145,35 @@ impl cob::object::Storage for Repository {
         object_id: &cob::ObjectId,
         entry: &cob::EntryId,
     ) -> Result<(), Self::UpdateError> {
-        self.backend.reference(
-            git::refs::storage::cob(identifier, typename, object_id).as_str(),
-            (*entry).into(),
-            true,
-            &format!(
-                "Updating collaborative object '{}/{}' with new entry {}",
-                typename, object_id, entry,
-            ),
-        )?;
-
+        let name = git::refs::storage::cob(identifier, typename, object_id);
+        let entry = git2::Oid::from(*entry);
+        let message = format!(
+            "Updating collaborative object '{}/{}' with new entry {}",
+            typename, object_id, entry,
+        );
+
+        // Only fast-forward the reference, and only if it wasn't moved since we last read it.
+        // This prevents concurrent local writers from silently overwriting each other's
+        // changes: the losing writer gets an error with code [`git2::ErrorCode::Modified`]
+        // and can retry on top of the new head.
+        match self.backend.refname_to_id(name.as_str()) {
+            Ok(current) => {
+                if current != entry && !self.backend.graph_descendant_of(entry, current)? {
+                    return Err(git2::Error::new(
+                        git2::ErrorCode::Modified,
+                        git2::ErrorClass::Reference,
+                        format!("reference '{name}' was modified concurrently"),
+                    ));
+                }
+                self.backend
+                    .reference_matching(name.as_str(), entry, true, current, &message)?;
+            }
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                self.backend
+                    .reference(name.as_str(), entry, false, &message)?;
+            }
+            Err(e) => return Err(e),
+        }
         Ok(())
     }
 
@@ -356,6 +375,10 @@ impl<'a, R: storage::ReadRepository> ReadRepository for DraftStore<'a, R> {
     fn merge_base(&self, left: &Oid, right: &Oid) -> Result<Oid, git::ext::Error> {
         self.repo.merge_base(left, right)
     }
+
+    fn generation(&self) -> Result<storage::Generation, git::ext::Error> {
+        self.repo.generation()
+    }
 }
 
 impl<'a, R: storage::WriteRepository> cob::object::Storage for DraftStore<'a, R> {
@@ -286,6 +286,10 @@ impl ReadRepository for MockRepository {
     fn merge_base(&self, _left: &Oid, _right: &Oid) -> Result<Oid, git::ext::Error> {
         todo!()
     }
+
+    fn generation(&self) -> Result<Generation, git::ext::Error> {
+        todo!()
+    }
 }
 
 impl WriteRepository for MockRepository {